@@ -279,6 +279,11 @@ use std::time::{
     SystemTime,
     UNIX_EPOCH,
 };
+use read_toml_field::read_toml_field::{
+    decode_toml_string,
+    find_multi_line_string_end,
+    string_value_text,
+};
 
 /// The function reads a single line from a TOML file that starts with a specified field name
 /// and ends with a value. The function returns an empty string if the field is not found, and
@@ -389,16 +394,22 @@ pub fn read_field_from_toml(path: &str, field_name: &str) -> String {
             // Debug print statement
             println!("Extracted value: {}", value);
 
-            // Clean up the value: remove quotes and trim spaces
-            let cleaned_value = value.trim().trim_matches('"').trim();
-            
+            // Clean up the value: remove quotes and decode escapes
+            let cleaned_value = match string_value_text(value) {
+                Ok(cleaned_value) => cleaned_value,
+                Err(e) => {
+                    println!("Invalid string for field '{}': {}", field_name, e);
+                    return String::new();
+                }
+            };
+
             // Verify the cleaned value isn't empty
             if cleaned_value.is_empty() {
                 println!("Warning: Value became empty after cleaning for field '{}'", field_name);
                 return String::new();
             }
 
-            return cleaned_value.to_string();
+            return cleaned_value;
         }
     }
 
@@ -473,12 +484,18 @@ pub fn read_basename_fields_from_toml(path: &str, base_name: &str) -> Vec<String
             }
 
             let value = parts[1].trim();
-            
-            // Clean up the value: remove quotes and trim spaces
-            let cleaned_value = value.trim().trim_matches('"').trim();
-            
+
+            // Clean up the value: remove quotes and decode escapes
+            let cleaned_value = match string_value_text(value) {
+                Ok(cleaned_value) => cleaned_value,
+                Err(e) => {
+                    println!("Invalid string on line {}: {}", line_number + 1, e);
+                    continue;
+                }
+            };
+
             if !cleaned_value.is_empty() {
-                values.push(cleaned_value.to_string());
+                values.push(cleaned_value);
             }
         }
     }
@@ -506,14 +523,8 @@ pub fn read_single_line_string_field_from_toml(path: &str, field_name: &str) ->
         let line = line.map_err(|e| format!("Failed to read line: {}", e))?;
         let trimmed = line.trim();
         
-        if trimmed.starts_with(&format!("{} = ", field_name)) {
-            return Ok(trimmed
-                .splitn(2, '=')
-                .nth(1)
-                .unwrap_or("")
-                .trim()
-                .trim_matches('"')
-                .to_string());
+        if let Some(value) = trimmed.strip_prefix(&format!("{} = ", field_name)) {
+            return string_value_text(value);
         }
     }
     
@@ -536,18 +547,23 @@ pub fn read_multi_line_toml_string(path: &str, field_name: &str) -> Result<Strin
     file.read_to_string(&mut content)
         .map_err(|e| format!("Failed to read file: {}", e))?;
 
-    // Find the start of the field
-    let field_start = format!("{} = \"\"\"", field_name);
-    let start_pos = content.find(&field_start)
+    // Find the start of the field, as a """ basic or ''' literal string
+    let (value_start, quote) = ['"', '\'']
+        .iter()
+        .find_map(|&quote| {
+            content
+                .find(&format!("{} = {}{}{}", field_name, quote, quote, quote))
+                .map(|start_pos| (start_pos + field_name.len() + 3, quote))
+        })
         .ok_or_else(|| format!("Multi-line field '{}' not found", field_name))?;
 
-    // Find the end of the field (next """)
-    let content_after_start = &content[start_pos + field_start.len()..];
-    let end_pos = content_after_start.find("\"\"\"")
-        .ok_or_else(|| format!("Closing triple quotes not found for field '{}'", field_name))?;
+    // Find the end of the field (closing delimiter, skipping escapes)
+    let value_end = find_multi_line_string_end(&content, value_start, quote)
+        .map_err(|_| format!("Closing triple quotes not found for field '{}'", field_name))?;
 
-    // Extract the content between the triple quotes
-    let multi_line_content = &content_after_start[..end_pos];
+    // Decode the content between the triple quotes
+    let multi_line_content = decode_toml_string(&content[value_start..value_end])
+        .map_err(|e| format!("Invalid multi-line string for field '{}': {}", field_name, e))?;
 
     // Clean up the content
     Ok(multi_line_content
@@ -1007,6 +1023,34 @@ string
         
         std::fs::remove_file(test_file).unwrap();
     }
+
+    #[test]
+    fn test_string_escapes_and_literal_strings() {
+        let test_content = "\
+quoted = \"a \\\"quoted\\\" word\"
+windows_path = 'C:\\path\\to\\file'
+gpg_key_literal = '''
+  C:\\keys\\no\\escapes
+'''
+folded = \"\"\"
+one \\
+    line\"\"\"
+";
+        let test_file = "test_string_escapes.toml";
+        write(test_file, test_content).unwrap();
+
+        let quoted = read_single_line_string_field_from_toml(test_file, "quoted");
+        let windows_path = read_field_from_toml(test_file, "windows_path");
+        let literal = read_multi_line_toml_string(test_file, "gpg_key_literal");
+        let folded = read_multi_line_toml_string(test_file, "folded");
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(quoted.unwrap(), "a \"quoted\" word");
+        assert_eq!(windows_path, "C:\\path\\to\\file");
+        assert_eq!(literal.unwrap(), "C:\\keys\\no\\escapes");
+        assert_eq!(folded.unwrap(), "one line");
+    }
 }
 
 ////////////
@@ -18,7 +18,7 @@
 
 /*
 
-## 16 is not hardcoded anywhere in the module.
+## No buffer size is hardcoded in the module.
 
 The `16` lives only in `main.rs`:
 
@@ -26,86 +26,66 @@ The `16` lives only in `main.rs`:
 const DEMO_OUTPUT_BUFFER_BYTES: usize = 16;   // demo's choice, not the module's
 ```
 
-and is passed to the function as a **const generic** at the call site:
+and is passed to the function as a **const generic** at the call site,
+together with the scan buffers and budget:
 
 ```rust
-read_single_line_string_field_from_toml_no_heap::<DEMO_OUTPUT_BUFFER_BYTES>(...)
-//                                                ^^^^^^^^^^^^^^^^^^^^^^^^
-//                                                this is the knob
+read_single_line_string_field_from_toml_no_heap::<
+    DEMO_OUTPUT_BUFFER_BYTES,   // <- output value buffer
+    RSLSF_READ_CHUNK_BYTES,     // <- file read chunk
+    RSLSF_MAX_LINE_BYTES,       // <- line accumulator
+    RSLSF_MAX_BYTES_SCANNED,    // <- scan budget
+>(...)
 ```
 
-You can call it with any compile-time size you like:
+You can call it with any compile-time sizes you like:
 
 ```rust
-read_single_line_string_field_from_toml_no_heap::<8>(path, "x")?;
-read_single_line_string_field_from_toml_no_heap::<32>(path, "x")?;
-read_single_line_string_field_from_toml_no_heap::<256>(path, "x")?;
+read_single_line_string_field_from_toml_no_heap::<8, 256, 512, { 1 << 20 }>(path, "x")?;
+read_single_line_string_field_from_toml_no_heap::<32, 64, 128, 4096>(path, "x")?;
 ```
 
-Each call site picks its own value buffer size. They do not interfere with each other.
+Each call site picks its own stack budget. They do not interfere with each other.
 
-## There are actually three buffers in play. Only one is caller-tunable today.
+## Three buffers and one budget, all caller-tunable.
 
-| Buffer | Purpose | Size today | Where set | Caller-tunable? |
-|---|---|---|---|---|
-| **Output value buffer** | Holds the extracted field value | `OUTPUT_BUFFER_BYTES` (e.g. `16`) | const generic at call site | **Yes** |
-| **File read chunk** | One `file.read()` lands here | `RSLSF_READ_CHUNK_BYTES = 256` | module constant | No (today) |
-| **Line accumulator** | Reassembles a single line across chunks | `RSLSF_MAX_LINE_BYTES = 512` | module constant | No (today) |
+| Parameter | Purpose | Recommended | Where set |
+|---|---|---|---|
+| **Output value buffer** | Holds the extracted field value | per value (e.g. `16`) | `OUTPUT_BUFFER_BYTES` |
+| **File read chunk** | One `file.read()` lands here | `RSLSF_READ_CHUNK_BYTES = 256` | `READ_CHUNK_BYTES` |
+| **Line accumulator** | Reassembles a single line across chunks | `RSLSF_MAX_LINE_BYTES = 512` | `MAX_LINE_BYTES` |
+| **Scan budget** | Failsafe bound on bytes read per call | `RSLSF_MAX_BYTES_SCANNED = 1 MiB` | `MAX_BYTES_SCANNED` |
 
-The two internal buffers (256 and 512) are fixed for every caller. The output buffer is per-call.
+The `RSLSF_*` constants are only recommendations: a microcontroller with
+8 KiB of stack can shrink the line accumulator to 128 bytes at its own
+call sites without touching the module.
 
-## Why the asymmetry, and how to change it if you want symmetry
-
-The asymmetry exists because, in practice:
-
-- Callers care a lot about the **output size** — it determines what value lengths they accept, and they want it as small as their data allows (no wasted stack).
-- Callers usually do **not** care about the internal scan buffer sizes — those just need to be "big enough for any reasonable line."
-
-If your project does want all three tunable (e.g. you are on a microcontroller with 8 KiB of stack and want to shrink the line accumulator to 128 bytes), promote them to const generics as well:
+The scan parameters are checked at compile time: each must be non-zero,
+and neither buffer may be larger than the scan budget. A call site that
+breaks this fails to build, e.g.
 
 ```rust
-pub fn read_single_line_string_field_from_toml_no_heap<
-    const OUTPUT_BUFFER_BYTES: usize,
-    const READ_CHUNK_BYTES:    usize,
-    const MAX_LINE_BYTES:      usize,
->(
-    absolute_toml_file_path: &str,
-    target_field_key: &str,
-) -> Result<([u8; OUTPUT_BUFFER_BYTES], usize), ReadTomlFieldError> { ... }
+read_single_line_string_field_from_toml_no_heap::<16, 0, 512, 4096>(path, "x")?;
+// error: RSLSF: READ_CHUNK_BYTES must be > 0
 ```
 
-Call site then becomes:
-
-```rust
-read_single_line_string_field_from_toml_no_heap::<16, 256, 512>(path, "name")?;
-```
-
-That trade is verbosity at call sites for full caller control of stack footprint. Pick whichever side you want; both are stack-only and heap-free.
-
-Note:
-case by case for need, these can be changed (to be larger or smaller-efficient values)
-
-/// Stack-allocated chunk size used for `File::read`.
-///
-/// Tradeoff: smaller chunks reduce stack pressure; larger chunks reduce syscall
-/// count. 256 B is comfortable on every realistic stack and keeps syscall
-/// overhead acceptable for the small-config use case this module targets.
-const RSLSF_READ_CHUNK_BYTES: usize = 16;
-
-/// Maximum bytes accumulated for a single line during scanning (stack-only).
-///
-/// Lines exceeding this limit do NOT silently truncate; see overflow handling
-/// in [`read_single_line_string_field_from_toml_no_heap`]. 512 B comfortably
-/// covers any realistic single-line TOML key/value in the in-scope subset.
-pub const RSLSF_MAX_LINE_BYTES: usize = 32;
+That trade is verbosity at call sites for full caller control of stack
+footprint; both are stack-only and heap-free.
 
 */
 
 mod read_toml_single_line_string_field_no_heap;
 
 use read_toml_single_line_string_field_no_heap::{
-    ReadTomlFieldError, read_single_line_string_field_from_toml_no_heap,
+    RSLSF_MAX_BYTES_SCANNED, RSLSF_MAX_LINE_BYTES, RSLSF_READ_CHUNK_BYTES, ReadTomlFieldError,
+    RsLsfFieldStatus, RsLsfSliceSource, read_bool_field_from_toml_no_heap, read_i64_array_field_from_toml_no_heap,
+    read_i64_field_from_toml_no_heap,
+    read_single_line_string_field_from_source_no_heap, read_single_line_string_field_from_toml_no_heap,
+    read_single_line_string_field_from_toml_no_heap_with_span,
+    read_single_line_string_fields_from_toml_no_heap, read_u64_field_from_toml_no_heap,
 };
+#[cfg(debug_assertions)]
+use read_toml_single_line_string_field_no_heap::write_rslsf_caret_diagnostic;
 
 use std::io::Write;
 
@@ -114,19 +94,28 @@ use std::io::Write;
 /// without being wastefully large.
 const DEMO_OUTPUT_BUFFER_BYTES: usize = 16;
 
+/// Most `retries` elements the demo accepts.
+const DEMO_RETRIES_CAPACITY: usize = 8;
+
 /// Name of the demo TOML file written under the OS temp directory.
 const DEMO_TOML_FILE_NAME: &str = "rslsf_demo_config.toml";
 
 /// Contents of the demo TOML file. Kept simple and inside the in-scope
-/// subset documented by the reader module (no sections, no escapes,
-/// no multi-line strings).
+/// subset documented by the reader module (one plain table, one-line
+/// strings, inline comments).
 const DEMO_TOML_FILE_CONTENTS: &str = "\
 # rslsf_demo: sample configuration
 # This file is overwritten on every run.
 
 node_id   = \"alice-node-01\"
 mode      = \"production\"
-port      = 8080
+port      = 8080   # inline comments are dropped
+verbose   = false
+utc_offset_minutes = -300
+retries   = [1, 2, 4, 8]
+
+[network]
+bind_port = 7000
 ";
 
 /// Process exit codes are kept few and fixed to avoid leaking detail.
@@ -174,11 +163,34 @@ fn main() {
     print_field_or_terse_error("node_id", demo_file_path_as_str);
     print_field_or_terse_error("mode", demo_file_path_as_str);
     print_field_or_terse_error("port", demo_file_path_as_str);
+    print_field_or_terse_error("network.bind_port", demo_file_path_as_str);
     print_field_or_terse_error("missing_key", demo_file_path_as_str);
 
-    // read demo file
-    print_field_or_terse_error("text", &"test.toml".to_string());
-    print_field_or_terse_error("longtext", &"test.toml".to_string());
+    // The same fields again, all in a single pass over the file.
+    print_fields_from_one_pass_or_terse_error(
+        &["node_id", "mode", "port", "network.bind_port", "missing_key"],
+        demo_file_path_as_str,
+    );
+
+    // Typed values, parsed on the stack.
+    print_typed_fields_or_terse_error(demo_file_path_as_str);
+
+    // The same text straight from memory, through the no_std core: no
+    // file, as firmware reading a linked-in or flash-resident config.
+    let read_result = read_single_line_string_field_from_source_no_heap::<
+        DEMO_OUTPUT_BUFFER_BYTES,
+        RSLSF_READ_CHUNK_BYTES,
+        RSLSF_MAX_LINE_BYTES,
+        RSLSF_MAX_BYTES_SCANNED,
+    >(
+        &mut RsLsfSliceSource::new(DEMO_TOML_FILE_CONTENTS.as_bytes()),
+        "node_id",
+    );
+    print_read_result("node_id (from memory)", read_result);
+
+    // read demo file; a failing field is also shown in place (debug builds)
+    print_field_with_diagnostic_or_terse_error("text", "test.toml");
+    print_field_with_diagnostic_or_terse_error("longtext", "test.toml");
 
     std::process::exit(EXIT_OK);
 }
@@ -189,11 +201,145 @@ fn main() {
 /// On any error the line is `ERR <key> <error-code>` with NO path,
 /// NO file contents, NO OS error text — matching the module's policy.
 fn print_field_or_terse_error(target_field_key: &str, absolute_toml_file_path: &str) {
-    let read_result = read_single_line_string_field_from_toml_no_heap::<DEMO_OUTPUT_BUFFER_BYTES>(
+    let read_result = read_single_line_string_field_from_toml_no_heap::<
+        DEMO_OUTPUT_BUFFER_BYTES,
+        RSLSF_READ_CHUNK_BYTES,
+        RSLSF_MAX_LINE_BYTES,
+        RSLSF_MAX_BYTES_SCANNED,
+    >(
+        absolute_toml_file_path,
+        target_field_key,
+    );
+    print_read_result(target_field_key, read_result);
+}
+
+/// Same as [`print_field_or_terse_error`], but debug builds first print a
+/// caret diagnostic to stderr when the error points at a place in the
+/// file. Operator-facing detail (path and file line) stays out of
+/// release builds, per the module's defensive policy.
+fn print_field_with_diagnostic_or_terse_error(target_field_key: &str, absolute_toml_file_path: &str) {
+    let read_result = read_single_line_string_field_from_toml_no_heap_with_span::<
+        DEMO_OUTPUT_BUFFER_BYTES,
+        RSLSF_READ_CHUNK_BYTES,
+        RSLSF_MAX_LINE_BYTES,
+        RSLSF_MAX_BYTES_SCANNED,
+    >(
         absolute_toml_file_path,
         target_field_key,
     );
 
+    match read_result {
+        Ok((output_buffer, written_length, _value_span)) => {
+            print_read_result(target_field_key, Ok((output_buffer, written_length)));
+        }
+        Err(spanned_error) => {
+            #[cfg(debug_assertions)]
+            if let Some(error_span) = spanned_error.span {
+                let _ = write_rslsf_caret_diagnostic(
+                    &mut std::io::stderr(),
+                    &error_span,
+                    terse_error_code(spanned_error.error),
+                );
+            }
+            print_read_result(target_field_key, Err(spanned_error.error));
+        }
+    }
+}
+
+/// Read several fields in one pass and print one fixed-format line per
+/// key, with the same codes as the single-key reads.
+fn print_fields_from_one_pass_or_terse_error<const KEY_COUNT: usize>(
+    target_field_keys: &[&str; KEY_COUNT],
+    absolute_toml_file_path: &str,
+) {
+    let field_slots = match read_single_line_string_fields_from_toml_no_heap::<
+        KEY_COUNT,
+        DEMO_OUTPUT_BUFFER_BYTES,
+        RSLSF_READ_CHUNK_BYTES,
+        RSLSF_MAX_LINE_BYTES,
+        RSLSF_MAX_BYTES_SCANNED,
+    >(
+        absolute_toml_file_path,
+        target_field_keys,
+    ) {
+        Ok(field_slots) => field_slots,
+        Err(error_variant) => {
+            println!("ERR * {}", terse_error_code(error_variant));
+            std::process::exit(EXIT_READ_FAILED);
+        }
+    };
+
+    let mut key_index: usize = 0;
+    while key_index < KEY_COUNT {
+        let field_slot = &field_slots[key_index];
+        let read_result = match field_slot.status {
+            RsLsfFieldStatus::Found => Ok((field_slot.output_buffer, field_slot.written_length)),
+            RsLsfFieldStatus::NotFound => Err(ReadTomlFieldError::RsLsfFieldNotFound),
+            RsLsfFieldStatus::ValueExceedsOutputBuffer => Err(ReadTomlFieldError::RsLsfValueExceedsOutputBuffer),
+            RsLsfFieldStatus::MatchingLineExceedsScanBuffer => {
+                Err(ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer)
+            }
+            RsLsfFieldStatus::InvalidEscapeSequence => Err(ReadTomlFieldError::RsLsfInvalidEscapeSequence),
+            RsLsfFieldStatus::MalformedString => Err(ReadTomlFieldError::RsLsfMalformedString),
+        };
+        print_read_result(target_field_keys[key_index], read_result);
+        key_index += 1;
+    }
+}
+
+/// Read the demo's integer, boolean and array fields with the typed
+/// readers and print one fixed-format line each.
+fn print_typed_fields_or_terse_error(absolute_toml_file_path: &str) {
+    match read_u64_field_from_toml_no_heap::<RSLSF_READ_CHUNK_BYTES, RSLSF_MAX_LINE_BYTES, RSLSF_MAX_BYTES_SCANNED>(
+        absolute_toml_file_path,
+        "port",
+        1..=65535,
+    ) {
+        Ok(port) => println!("OK  port = {} (u64)", port),
+        Err(error_variant) => exit_with_terse_error("port", error_variant),
+    }
+
+    match read_i64_field_from_toml_no_heap::<RSLSF_READ_CHUNK_BYTES, RSLSF_MAX_LINE_BYTES, RSLSF_MAX_BYTES_SCANNED>(
+        absolute_toml_file_path,
+        "utc_offset_minutes",
+        -720..=840,
+    ) {
+        Ok(utc_offset_minutes) => println!("OK  utc_offset_minutes = {} (i64)", utc_offset_minutes),
+        Err(error_variant) => exit_with_terse_error("utc_offset_minutes", error_variant),
+    }
+
+    match read_bool_field_from_toml_no_heap::<RSLSF_READ_CHUNK_BYTES, RSLSF_MAX_LINE_BYTES, RSLSF_MAX_BYTES_SCANNED>(
+        absolute_toml_file_path,
+        "verbose",
+    ) {
+        Ok(verbose) => println!("OK  verbose = {} (bool)", verbose),
+        Err(error_variant) => exit_with_terse_error("verbose", error_variant),
+    }
+
+    match read_i64_array_field_from_toml_no_heap::<
+        DEMO_RETRIES_CAPACITY,
+        RSLSF_READ_CHUNK_BYTES,
+        RSLSF_MAX_LINE_BYTES,
+        RSLSF_MAX_BYTES_SCANNED,
+    >(absolute_toml_file_path, "retries")
+    {
+        Ok((retries, retry_count)) => println!("OK  retries = {:?} (i64 array)", &retries[..retry_count]),
+        Err(error_variant) => exit_with_terse_error("retries", error_variant),
+    }
+}
+
+/// Print `ERR <key> <error-code>` and exit: the demo treats every typed
+/// read failure as fatal.
+fn exit_with_terse_error(target_field_key: &str, error_variant: ReadTomlFieldError) -> ! {
+    println!("ERR {} {}", target_field_key, terse_error_code(error_variant));
+    std::process::exit(EXIT_READ_FAILED);
+}
+
+/// Print the fixed-format line for one read result.
+fn print_read_result(
+    target_field_key: &str,
+    read_result: Result<([u8; DEMO_OUTPUT_BUFFER_BYTES], usize), ReadTomlFieldError>,
+) {
     match read_result {
         Ok((output_buffer, written_length)) => {
             // Validate UTF-8 at the boundary, since we want to print as text.
@@ -247,5 +393,12 @@ fn terse_error_code(error_variant: ReadTomlFieldError) -> &'static str {
         ReadTomlFieldError::RsLsfValueExceedsOutputBuffer => "E_VALUE_TOO_BIG",
         ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer => "E_LINE_TOO_BIG",
         ReadTomlFieldError::RsLsfSafetyBudgetExhausted => "E_SAFETY_BUDGET",
+        ReadTomlFieldError::RsLsfValueNotInteger => "E_NOT_INTEGER",
+        ReadTomlFieldError::RsLsfIntegerOutOfRange => "E_INT_RANGE",
+        ReadTomlFieldError::RsLsfValueNotBoolean => "E_NOT_BOOL",
+        ReadTomlFieldError::RsLsfValueNotIntegerArray => "E_NOT_INT_ARRAY",
+        ReadTomlFieldError::RsLsfArrayExceedsCapacity => "E_ARRAY_FULL",
+        ReadTomlFieldError::RsLsfInvalidEscapeSequence => "E_BAD_ESCAPE",
+        ReadTomlFieldError::RsLsfMalformedString => "E_BAD_STRING",
     }
 }
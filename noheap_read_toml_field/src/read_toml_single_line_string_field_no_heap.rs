@@ -22,27 +22,45 @@
 //! This module exposes a single function,
 //! [`read_single_line_string_field_from_toml_no_heap`], that reads a single
 //! short-string field from a TOML file using only stack-allocated buffers.
+//! [`read_single_line_string_field_from_toml_no_heap_with_span`] does the
+//! same and also reports where the value (or the problem) is in the file,
+//! and [`write_rslsf_caret_diagnostic`] renders such a span for an operator.
+//! [`read_single_line_string_fields_from_toml_no_heap`] reads several keys
+//! in one pass, filling a fixed array of output slots.
+//!
+//! # Layout
+//!
+//! The scanning logic lives in the `no_std` core, `rslsf_core`, which only
+//! reads TOML text through the [`RsLsfByteSource`] trait and never touches
+//! `std`: it runs against an in-memory slice ([`RsLsfSliceSource`]), a
+//! flash region, or any reader. This file is the thin `std` adapter on
+//! top: [`RsLsfFileSource`] and [`RsLsfIoReadSource`], the path-based
+//! readers, and the caret diagnostic. Copy `rslsf_core.rs` alone into a
+//! `#![no_std]` crate, or both files for `std` use.
 //!
 //! # In Scope
 //!
-//! * One key per call, top-level (no `[section]`).
+//! * One key per call (or a fixed array of keys in one pass): a top-level
+//!   key, or a table path such as `network.node_id`, found under a
+//!   `[network]` header or written as the dotted key `network.node_id = ...`
+//!   (also mixed: `tls.mode` under `[network]` for `network.tls.mode`). A
+//!   bare key only matches before the first header, as in TOML.
 //! * Single-line values up to a caller-chosen `OUTPUT_BUFFER_BYTES` length.
-//! * Values quoted with simple double quotes (`"..."`) or unquoted (numbers,
-//!   bare identifiers).
-//! * Lines using LF or CRLF terminators.
-//! * Lines beginning with `#` (after trimming) are treated as comments.
+//! * Values as one-line basic strings (`"..."`, escapes such as `\n`, `\"`
+//!   and `\u00e9` decoded into the output buffer), literal strings
+//!   (`'...'`, copied verbatim) or unquoted (numbers, bare identifiers).
+//! * Lines using LF or CRLF terminators (a bare CR is ordinary line content).
+//! * Lines beginning with `#` (after trimming) are treated as comments, and
+//!   a `#` after a value starts an inline comment (`port = 8080  # note`).
+//! * A UTF-8 BOM at the start of the input is skipped.
 //!
 //! # Explicitly Out Of Scope (Non-Goals)
 //!
-//! * Full TOML grammar (no arrays, tables, inline tables, multi-line strings,
-//!   escape sequences, dotted keys, datetimes).
+//! * Full TOML grammar (no arrays, arrays of tables, inline tables,
+//!   multi-line strings, quoted keys, datetimes). A string not closed on
+//!   its line is rejected with `RsLsfMalformedString`, a bad escape with
+//!   `RsLsfInvalidEscapeSequence`.
 //! * Re-encoding the value (caller decides whether to `core::str::from_utf8`).
-//! * Trailing inline comments on the same line as the value
-//!   (e.g. `name = "x"  # note` — the `# note` becomes part of the value).
-//!   If your project relies on inline comments, switch to a real parser.
-//! * UTF-8 BOM at file start (the BOM bytes will appear in the first line's
-//!   key prefix and cause a non-match for that key). Strip BOMs upstream if
-//!   your toolchain may produce them.
 //!
 //! # Defensive Policy
 //!
@@ -59,344 +77,124 @@
 //! It is safe to call from multiple threads with distinct paths.
 //! ============================================================================
 
-/*
-Example use, main.rs code
-
-//! ============================================================================
-//! Binary: rslsf_demo
-//! ----------------------------------------------------------------------------
-//! Demonstrates and smoke-tests `read_single_line_string_field_from_toml_no_heap`
-//! against a real on-disk TOML file.
-//!
-//! # Project Context
-//! This binary is intentionally minimal. Its job is to:
-//!   1. Write a small known TOML file to a deterministic absolute path.
-//!   2. Read a single short field from it using the no-heap reader.
-//!   3. Print a terse, fixed-set status — no path, no value contents in error
-//!      branches — matching the production logging policy of the module.
-//!
-//! Note: this binary uses `println!` for the *success* path only as a
-//! human-facing demonstration. Real production callers should route output
-//! through their own logging facility.
-//! ============================================================================
-
-/*
-
-## 16 is not hardcoded anywhere in the module.
-
-The `16` lives only in `main.rs`:
-
-```rust
-const DEMO_OUTPUT_BUFFER_BYTES: usize = 16;   // demo's choice, not the module's
-```
-
-and is passed to the function as a **const generic** at the call site:
-
-```rust
-read_single_line_string_field_from_toml_no_heap::<DEMO_OUTPUT_BUFFER_BYTES>(...)
-//                                                ^^^^^^^^^^^^^^^^^^^^^^^^
-//                                                this is the knob
-```
-
-You can call it with any compile-time size you like:
-
-```rust
-read_single_line_string_field_from_toml_no_heap::<8>(path, "x")?;
-read_single_line_string_field_from_toml_no_heap::<32>(path, "x")?;
-read_single_line_string_field_from_toml_no_heap::<256>(path, "x")?;
-```
-
-Each call site picks its own value buffer size. They do not interfere with each other.
-
-## There are actually three buffers in play. Only one is caller-tunable today.
-
-| Buffer | Purpose | Size today | Where set | Caller-tunable? |
-|---|---|---|---|---|
-| **Output value buffer** | Holds the extracted field value | `OUTPUT_BUFFER_BYTES` (e.g. `16`) | const generic at call site | **Yes** |
-| **File read chunk** | One `file.read()` lands here | `RSLSF_READ_CHUNK_BYTES = 256` | module constant | No (today) |
-| **Line accumulator** | Reassembles a single line across chunks | `RSLSF_MAX_LINE_BYTES = 512` | module constant | No (today) |
-
-The two internal buffers (256 and 512) are fixed for every caller. The output buffer is per-call.
-
-## Why the asymmetry, and how to change it if you want symmetry
-
-The asymmetry exists because, in practice:
-
-- Callers care a lot about the **output size** — it determines what value lengths they accept, and they want it as small as their data allows (no wasted stack).
-- Callers usually do **not** care about the internal scan buffer sizes — those just need to be "big enough for any reasonable line."
-
-If your project does want all three tunable (e.g. you are on a microcontroller with 8 KiB of stack and want to shrink the line accumulator to 128 bytes), promote them to const generics as well:
+mod rslsf_core;
+#[cfg(test)]
+mod rslsf_property_tests;
+
+pub use rslsf_core::{
+    RSLSF_MAX_BYTES_SCANNED, RSLSF_MAX_LINE_BYTES, RSLSF_READ_CHUNK_BYTES, ReadTomlFieldError, RsLsfByteSource,
+    RsLsfFieldSlot, RsLsfFieldStatus, RsLsfSliceSource, RsLsfSpan, RsLsfSpannedError,
+    read_bool_field_from_source_no_heap, read_i64_array_field_from_source_no_heap,
+    read_i64_field_from_source_no_heap, read_single_line_string_field_from_source_no_heap,
+    read_single_line_string_field_from_source_no_heap_with_span,
+    read_single_line_string_fields_from_source_no_heap, read_u64_field_from_source_no_heap,
+};
 
-```rust
-pub fn read_single_line_string_field_from_toml_no_heap<
-    const OUTPUT_BUFFER_BYTES: usize,
-    const READ_CHUNK_BYTES:    usize,
-    const MAX_LINE_BYTES:      usize,
->(
-    absolute_toml_file_path: &str,
-    target_field_key: &str,
-) -> Result<([u8; OUTPUT_BUFFER_BYTES], usize), ReadTomlFieldError> { ... }
-```
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::RangeInclusive;
 
-Call site then becomes:
+// ----------------------------------------------------------------------------
+// std adapters
+// ----------------------------------------------------------------------------
 
-```rust
-read_single_line_string_field_from_toml_no_heap::<16, 256, 512>(path, "name")?;
-```
+/// A byte source over any `std::io::Read` (a socket, a pipe, a
+/// decompressor). Read errors become a terse `RsLsfFileReadFailed`; the
+/// OS error is dropped, never surfaced. Interrupted reads are retried.
+pub struct RsLsfIoReadSource<Reader: Read> {
+    reader: Reader,
+}
 
-That trade is verbosity at call sites for full caller control of stack footprint. Pick whichever side you want; both are stack-only and heap-free.
+impl<Reader: Read> RsLsfIoReadSource<Reader> {
+    pub fn new(reader: Reader) -> Self {
+        RsLsfIoReadSource { reader }
+    }
+}
 
-*/
+impl<Reader: Read> RsLsfByteSource for RsLsfIoReadSource<Reader> {
+    fn read_chunk(&mut self, chunk_buffer: &mut [u8]) -> Result<usize, ReadTomlFieldError> {
+        // Bounded: a reader that is interrupted forever is treated as failed.
+        let mut attempt_count: u32 = 0;
+        while attempt_count < RSLSF_MAX_INTERRUPTED_READ_RETRIES {
+            match self.reader.read(chunk_buffer) {
+                Ok(count) => return Ok(count),
+                Err(read_error) if read_error.kind() == std::io::ErrorKind::Interrupted => attempt_count += 1,
+                Err(_) => return Err(ReadTomlFieldError::RsLsfFileReadFailed),
+            }
+        }
+        Err(ReadTomlFieldError::RsLsfFileReadFailed)
+    }
+}
 
-mod read_toml_single_line_string_field_no_heap;
+/// How often [`RsLsfIoReadSource`] retries a read interrupted by a signal.
+const RSLSF_MAX_INTERRUPTED_READ_RETRIES: u32 = 16;
 
-use read_toml_single_line_string_field_no_heap::{
-    ReadTomlFieldError, read_single_line_string_field_from_toml_no_heap,
-};
+/// The byte source behind the path-based readers: opens the file on the
+/// first read, so a call with an invalid key or buffer size is rejected
+/// before the file system is touched. An open failure is a terse
+/// `RsLsfFileOpenFailed` (no path, no OS error).
+pub struct RsLsfFileSource<'path> {
+    absolute_toml_file_path: &'path str,
+    open_file_source: Option<RsLsfIoReadSource<File>>,
+}
 
-use std::io::Write;
-
-/// Size of the on-stack output buffer used in this demo.
-/// Picked to comfortably hold the demo value `"alice-node-01"` (13 bytes)
-/// without being wastefully large.
-const DEMO_OUTPUT_BUFFER_BYTES: usize = 16;
-
-/// Name of the demo TOML file written under the OS temp directory.
-const DEMO_TOML_FILE_NAME: &str = "rslsf_demo_config.toml";
-
-/// Contents of the demo TOML file. Kept simple and inside the in-scope
-/// subset documented by the reader module (no sections, no escapes,
-/// no multi-line strings).
-const DEMO_TOML_FILE_CONTENTS: &str = "\
-# rslsf_demo: sample configuration
-# This file is overwritten on every run.
-
-node_id   = \"alice-node-01\"
-mode      = \"production\"
-port      = 8080
-";
-
-/// Process exit codes are kept few and fixed to avoid leaking detail.
-/// Production policy: never propagate raw OS errors to the exit code.
-const EXIT_OK: i32 = 0;
-const EXIT_DEMO_SETUP_FAILED: i32 = 10;
-const EXIT_READ_FAILED: i32 = 20;
-
-fn main() {
-    // ------------------------------------------------------------------
-    // Step 1: write the demo TOML file to an absolute path under temp_dir.
-    // This step is the demo's responsibility — it is NOT a production
-    // pattern, just a way to give the reader something to read.
-    // ------------------------------------------------------------------
-    let mut demo_file_absolute_path = std::env::temp_dir();
-    demo_file_absolute_path.push(DEMO_TOML_FILE_NAME);
-
-    match std::fs::File::create(&demo_file_absolute_path)
-        .and_then(|mut f| f.write_all(DEMO_TOML_FILE_CONTENTS.as_bytes()))
-    {
-        Ok(()) => {}
-        Err(_) => {
-            // Terse message: do NOT print the path or OS error.
-            eprintln!("rslsf_demo: setup failed");
-            std::process::exit(EXIT_DEMO_SETUP_FAILED);
+impl<'path> RsLsfFileSource<'path> {
+    pub fn new(absolute_toml_file_path: &'path str) -> Self {
+        RsLsfFileSource {
+            absolute_toml_file_path,
+            open_file_source: None,
         }
     }
-
-    // Convert PathBuf to &str for the reader (which takes &str).
-    // If the temp dir has a non-UTF-8 path on this platform, we bail
-    // cleanly without exposing the path.
-    let demo_file_path_as_str: &str = match demo_file_absolute_path.to_str() {
-        Some(s) => s,
-        None => {
-            eprintln!("rslsf_demo: setup failed");
-            std::process::exit(EXIT_DEMO_SETUP_FAILED);
-        }
-    };
-
-    // ------------------------------------------------------------------
-    // Step 2: read three fields and print results in a fixed-format way.
-    // We deliberately call the reader three times to demonstrate that
-    // each call is independent and reuses no state.
-    // ------------------------------------------------------------------
-    print_field_or_terse_error("node_id", demo_file_path_as_str);
-    print_field_or_terse_error("mode", demo_file_path_as_str);
-    print_field_or_terse_error("port", demo_file_path_as_str);
-    print_field_or_terse_error("missing_key", demo_file_path_as_str);
-
-    std::process::exit(EXIT_OK);
 }
 
-/// Read one field and print a single fixed-format line.
-///
-/// On success the line is `OK <key> = <value>`.
-/// On any error the line is `ERR <key> <error-code>` with NO path,
-/// NO file contents, NO OS error text — matching the module's policy.
-fn print_field_or_terse_error(target_field_key: &str, absolute_toml_file_path: &str) {
-    let read_result = read_single_line_string_field_from_toml_no_heap::<DEMO_OUTPUT_BUFFER_BYTES>(
-        absolute_toml_file_path,
-        target_field_key,
-    );
-
-    match read_result {
-        Ok((output_buffer, written_length)) => {
-            // Validate UTF-8 at the boundary, since we want to print as text.
-            // This validation is the caller's responsibility, not the reader's.
-            match core::str::from_utf8(&output_buffer[..written_length]) {
-                Ok(value_as_str) => {
-                    println!("OK  {} = {}", target_field_key, value_as_str);
-                }
-                Err(_) => {
-                    // Value is not UTF-8: report terse, do not print bytes.
-                    println!("ERR {} non_utf8_value", target_field_key);
-                }
+impl RsLsfByteSource for RsLsfFileSource<'_> {
+    fn read_chunk(&mut self, chunk_buffer: &mut [u8]) -> Result<usize, ReadTomlFieldError> {
+        if self.open_file_source.is_none() {
+            match File::open(self.absolute_toml_file_path) {
+                Ok(open_file_handle) => self.open_file_source = Some(RsLsfIoReadSource::new(open_file_handle)),
+                Err(_) => return Err(ReadTomlFieldError::RsLsfFileOpenFailed),
             }
         }
-        Err(error_variant) => {
-            println!(
-                "ERR {} {}",
-                target_field_key,
-                terse_error_code(error_variant),
-            );
-
-            // Demo policy: if the failure is something other than
-            // "field not found" we treat it as a hard failure and
-            // exit. In real production code, the caller would handle
-            // each variant according to its own recovery policy and
-            // would NOT terminate the program.
-            match error_variant {
-                ReadTomlFieldError::RsLsfFieldNotFound => {
-                    // expected for "missing_key"; keep going.
-                }
-                _ => {
-                    std::process::exit(EXIT_READ_FAILED);
-                }
-            }
+        match self.open_file_source.as_mut() {
+            Some(open_file_source) => open_file_source.read_chunk(chunk_buffer),
+            None => Err(ReadTomlFieldError::RsLsfFileOpenFailed),
         }
     }
 }
 
-/// Map each error variant to a short, fixed, log-safe code.
-///
-/// These codes are stable strings the operator can grep for. They contain
-/// no path, no contents, and no OS detail — by design.
-fn terse_error_code(error_variant: ReadTomlFieldError) -> &'static str {
-    match error_variant {
-        ReadTomlFieldError::RsLsfEmptyKey => "E_EMPTY_KEY",
-        ReadTomlFieldError::RsLsfKeyTooLong => "E_KEY_TOO_LONG",
-        ReadTomlFieldError::RsLsfOutputBufferZeroSized => "E_OUTBUF_ZERO",
-        ReadTomlFieldError::RsLsfFileOpenFailed => "E_OPEN",
-        ReadTomlFieldError::RsLsfFileReadFailed => "E_READ",
-        ReadTomlFieldError::RsLsfFieldNotFound => "E_NOT_FOUND",
-        ReadTomlFieldError::RsLsfValueExceedsOutputBuffer => "E_VALUE_TOO_BIG",
-        ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer => "E_LINE_TOO_BIG",
-        ReadTomlFieldError::RsLsfSafetyBudgetExhausted => "E_SAFETY_BUDGET",
-    }
-}
-
-
-
-*/
-
-use std::fs::File;
-use std::io::Read;
-
-// ----------------------------------------------------------------------------
-// Module constants
-// ----------------------------------------------------------------------------
-
-/// Stack-allocated chunk size used for `File::read`.
-///
-/// Tradeoff: smaller chunks reduce stack pressure; larger chunks reduce syscall
-/// count. 256 B is comfortable on every realistic stack and keeps syscall
-/// overhead acceptable for the small-config use case this module targets.
-const RSLSF_READ_CHUNK_BYTES: usize = 16;
-
-/// Maximum bytes accumulated for a single line during scanning (stack-only).
-///
-/// Lines exceeding this limit do NOT silently truncate; see overflow handling
-/// in [`read_single_line_string_field_from_toml_no_heap`]. 512 B comfortably
-/// covers any realistic single-line TOML key/value in the in-scope subset.
-pub const RSLSF_MAX_LINE_BYTES: usize = 32;
-
-/// Failsafe upper bound on total bytes scanned from a single file.
-///
-/// Bounds the read loop even if the OS keeps returning data (NASA P10 rule 2).
-/// Tune for your project's expected configuration size. 1 MiB is generous for
-/// configuration files while preventing pathological/adversarial inputs from
-/// running unbounded work.
-pub const RSLSF_MAX_BYTES_SCANNED: u64 = 1 << 20;
-
-// ----------------------------------------------------------------------------
-// Error type
-// ----------------------------------------------------------------------------
-
-/// Production-safe error type for
-/// [`read_single_line_string_field_from_toml_no_heap`].
-///
-/// # Design
-///
-/// * All variants are zero-sized: no heap, no `String`, no embedded path,
-///   no embedded OS error. This is a deliberate defensive choice — error
-///   values must never become an information-disclosure vector.
-/// * Every variant carries the unique prefix `RsLsf` (Read Single Line
-///   String Field) so it is unambiguously traceable in logs to this
-///   function, satisfying the "unique error per function" rule.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum ReadTomlFieldError {
-    /// RSLSF: caller-supplied key was empty.
-    RsLsfEmptyKey,
-    /// RSLSF: caller-supplied key would not fit in the line scan buffer.
-    RsLsfKeyTooLong,
-    /// RSLSF: caller-supplied `OUTPUT_BUFFER_BYTES` const-generic was zero.
-    RsLsfOutputBufferZeroSized,
-    /// RSLSF: the file could not be opened (does not exist, permission, etc.).
-    RsLsfFileOpenFailed,
-    /// RSLSF: an I/O error occurred while reading.
-    RsLsfFileReadFailed,
-    /// RSLSF: the requested key was not present in the file.
-    RsLsfFieldNotFound,
-    /// RSLSF: the matched value would not fit in `OUTPUT_BUFFER_BYTES`.
-    RsLsfValueExceedsOutputBuffer,
-    /// RSLSF: a line whose leading bytes matched the requested key exceeded
-    /// `RSLSF_MAX_LINE_BYTES`; refusing to silently truncate.
-    RsLsfMatchingLineExceedsScanBuffer,
-    /// RSLSF: the failsafe byte/iteration budget was exhausted.
-    RsLsfSafetyBudgetExhausted,
-}
-
 // ----------------------------------------------------------------------------
-// Public API
+// Public API (file paths)
 // ----------------------------------------------------------------------------
+//
+// Each path-based reader is the core reader of the same shape over an
+// `RsLsfFileSource`; see the core function for the full contract.
 
-/// Reads a single top-level single-line string field from a TOML file using
-/// only stack-allocated memory (buffer size set by call to function).
+/// Reads a single single-line string field, top-level or in a table, from
+/// a TOML file using only stack-allocated memory (buffer size set by call
+/// to function).
 ///
 /// e.g.
-/// read_single_line_string_field_from_toml_no_heap::<8>(path, "x")?;
-/// read_single_line_string_field_from_toml_no_heap::<32>(path, "x")?;
-/// read_single_line_string_field_from_toml_no_heap::<256>(path, "x")?;
+/// read_single_line_string_field_from_toml_no_heap::<8, 256, 512, { 1 << 20 }>(path, "x")?;
+/// read_single_line_string_field_from_toml_no_heap::<32, 64, 128, 4096>(path, "x")?;
 ///
-/// # Type Parameters
-/// * `OUTPUT_BUFFER_BYTES` — the fixed size of the returned byte buffer.
-///   Must be `> 0`. Pick the smallest value that comfortably fits your
-///   project's value (e.g. `16` for a short identifier).
+/// Type parameters, key rules, results and the stack footprint are those
+/// of [`read_single_line_string_field_from_source_no_heap`].
 ///
 /// # Arguments
 /// * `absolute_toml_file_path` — absolute path to the TOML file. Relative
 ///   paths technically work but are discouraged per project policy because
 ///   they depend on the current working directory.
-/// * `target_field_key` — the exact top-level key to find. Must be non-empty
-///   and shorter than [`RSLSF_MAX_LINE_BYTES`].
-///
-/// # Returns
-/// * `Ok((output_buffer, written_length))` on success. `output_buffer` is
-///   `[u8; OUTPUT_BUFFER_BYTES]`, and `written_length` bytes of it are
-///   meaningful. Bytes past `written_length` are zero.
-/// * `Err(ReadTomlFieldError)` on any failure; never panics, never allocates.
+/// * `target_field_key` — the exact key to find: `node_id` for a top-level
+///   key, `network.node_id` for one in the `[network]` table.
 ///
 /// # Example (illustrative)
 /// ```ignore
-/// match read_single_line_string_field_from_toml_no_heap::<16>(
+/// match read_single_line_string_field_from_toml_no_heap::<
+///     16,
+///     RSLSF_READ_CHUNK_BYTES,
+///     RSLSF_MAX_LINE_BYTES,
+///     RSLSF_MAX_BYTES_SCANNED,
+/// >(
 ///     "/etc/myapp/config.toml",
 ///     "node_id",
 /// ) {
@@ -412,295 +210,256 @@ pub enum ReadTomlFieldError {
 ///     }
 /// }
 /// ```
-/// # Type Parameters
-/// * `OUTPUT_BUFFER_BYTES` — chosen by the caller at the call site, at
-///   compile time, via the turbofish syntax `::<N>`. This is the size of
-///   the value buffer returned to the caller; it is the maximum value
-///   length this call will accept. There is no default and no hardcoded
-///   value inside this module. Pick the smallest `N` that fits the
-///   longest legitimate value your caller will accept; values longer
-///   than `N` produce `RsLsfValueExceedsOutputBuffer` and are never
-///   silently truncated.
-///
-/// # Internal buffers (not caller-tunable in this version)
-/// Two internal scan buffers are sized by module-level constants:
-/// [`RSLSF_READ_CHUNK_BYTES`] and [`RSLSF_MAX_LINE_BYTES`]. If your
-/// project needs to tune those (e.g. tighter stack budget on embedded
-/// targets), promote them to additional const generics on this function.
-///
-pub fn read_single_line_string_field_from_toml_no_heap<const OUTPUT_BUFFER_BYTES: usize>(
+pub fn read_single_line_string_field_from_toml_no_heap<
+    const OUTPUT_BUFFER_BYTES: usize,
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
     absolute_toml_file_path: &str,
     target_field_key: &str,
 ) -> Result<([u8; OUTPUT_BUFFER_BYTES], usize), ReadTomlFieldError> {
-    // ----------------------------------------------------------------------
-    // Debug-Assert / Test-Assert / Production-Catch
-    // ----------------------------------------------------------------------
-    // Debug-only assertion: panics only in non-test debug builds, so
-    // developers see violated preconditions during local iteration.
-    #[cfg(all(debug_assertions, not(test)))]
-    {
-        debug_assert!(
-            OUTPUT_BUFFER_BYTES > 0,
-            "RSLSF: OUTPUT_BUFFER_BYTES must be > 0",
-        );
-        debug_assert!(
-            !target_field_key.is_empty(),
-            "RSLSF: target_field_key must not be empty",
-        );
-    }
-
-    // Production catch-handles: never panic; convert violations into errors.
-    if OUTPUT_BUFFER_BYTES == 0 {
-        return Err(ReadTomlFieldError::RsLsfOutputBufferZeroSized);
-    }
-    if target_field_key.is_empty() {
-        return Err(ReadTomlFieldError::RsLsfEmptyKey);
-    }
-    if target_field_key.len() >= RSLSF_MAX_LINE_BYTES {
-        return Err(ReadTomlFieldError::RsLsfKeyTooLong);
-    }
-
-    // ----------------------------------------------------------------------
-    // Open the file (terse error: no path leakage)
-    // ----------------------------------------------------------------------
-    let mut open_file_handle: File = match File::open(absolute_toml_file_path) {
-        Ok(handle) => handle,
-        Err(_) => return Err(ReadTomlFieldError::RsLsfFileOpenFailed),
-    };
+    read_single_line_string_field_from_source_no_heap::<
+        OUTPUT_BUFFER_BYTES,
+        READ_CHUNK_BYTES,
+        MAX_LINE_BYTES,
+        MAX_BYTES_SCANNED,
+    >(&mut RsLsfFileSource::new(absolute_toml_file_path), target_field_key)
+}
 
-    // ----------------------------------------------------------------------
-    // Stack-allocated scratch buffers
-    // ----------------------------------------------------------------------
-    let mut chunk_read_buffer: [u8; RSLSF_READ_CHUNK_BYTES] = [0u8; RSLSF_READ_CHUNK_BYTES];
-    let mut current_line_buffer: [u8; RSLSF_MAX_LINE_BYTES] = [0u8; RSLSF_MAX_LINE_BYTES];
-    let mut current_line_length: usize = 0;
-    let mut current_line_overflowed_buffer: bool = false;
-    let mut cumulative_bytes_scanned: u64 = 0;
-
-    // Iteration failsafe: even if `read` were to misbehave and always return 1
-    // byte, this caps the outer loop. (Belt-and-suspenders with the byte cap.)
-    let mut safety_iteration_count: u64 = 0;
-    let safety_iteration_limit: u64 =
-        (RSLSF_MAX_BYTES_SCANNED / (RSLSF_READ_CHUNK_BYTES as u64)) + 16;
+/// Same as [`read_single_line_string_field_from_toml_no_heap`], but also
+/// reports WHERE in the file the value (or the problem) is, with spans
+/// naming `absolute_toml_file_path`; see
+/// [`read_single_line_string_field_from_source_no_heap_with_span`].
+/// [`write_rslsf_caret_diagnostic`] renders such a span for an operator.
+pub fn read_single_line_string_field_from_toml_no_heap_with_span<
+    'path,
+    const OUTPUT_BUFFER_BYTES: usize,
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    absolute_toml_file_path: &'path str,
+    target_field_key: &str,
+) -> Result<([u8; OUTPUT_BUFFER_BYTES], usize, RsLsfSpan<'path>), RsLsfSpannedError<'path>> {
+    read_single_line_string_field_from_source_no_heap_with_span::<
+        OUTPUT_BUFFER_BYTES,
+        READ_CHUNK_BYTES,
+        MAX_LINE_BYTES,
+        MAX_BYTES_SCANNED,
+    >(
+        &mut RsLsfFileSource::new(absolute_toml_file_path),
+        absolute_toml_file_path,
+        target_field_key,
+    )
+}
 
-    // ----------------------------------------------------------------------
-    // Read loop
-    // ----------------------------------------------------------------------
-    loop {
-        safety_iteration_count = safety_iteration_count.saturating_add(1);
-        if safety_iteration_count > safety_iteration_limit {
-            return Err(ReadTomlFieldError::RsLsfSafetyBudgetExhausted);
-        }
+/// Reads several single-line string fields in ONE pass over the file: one
+/// open and one scan, not one per key. See
+/// [`read_single_line_string_fields_from_source_no_heap`].
+pub fn read_single_line_string_fields_from_toml_no_heap<
+    const KEY_COUNT: usize,
+    const OUTPUT_BUFFER_BYTES: usize,
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    absolute_toml_file_path: &str,
+    target_field_keys: &[&str; KEY_COUNT],
+) -> Result<[RsLsfFieldSlot<OUTPUT_BUFFER_BYTES>; KEY_COUNT], ReadTomlFieldError> {
+    read_single_line_string_fields_from_source_no_heap::<
+        KEY_COUNT,
+        OUTPUT_BUFFER_BYTES,
+        READ_CHUNK_BYTES,
+        MAX_LINE_BYTES,
+        MAX_BYTES_SCANNED,
+    >(&mut RsLsfFileSource::new(absolute_toml_file_path), target_field_keys)
+}
 
-        let bytes_read_this_chunk: usize = match open_file_handle.read(&mut chunk_read_buffer) {
-            Ok(count) => count,
-            Err(_) => return Err(ReadTomlFieldError::RsLsfFileReadFailed),
-        };
+/// Reads a non-negative TOML integer from the file into a `u64` inside
+/// `allowed_range`; see [`read_u64_field_from_source_no_heap`].
+pub fn read_u64_field_from_toml_no_heap<
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    absolute_toml_file_path: &str,
+    target_field_key: &str,
+    allowed_range: RangeInclusive<u64>,
+) -> Result<u64, ReadTomlFieldError> {
+    read_u64_field_from_source_no_heap::<READ_CHUNK_BYTES, MAX_LINE_BYTES, MAX_BYTES_SCANNED>(
+        &mut RsLsfFileSource::new(absolute_toml_file_path),
+        target_field_key,
+        allowed_range,
+    )
+}
 
-        // ------------------------------------------------------------------
-        // End-of-file: process any final unterminated line, then report.
-        // ------------------------------------------------------------------
-        if bytes_read_this_chunk == 0 {
-            if current_line_overflowed_buffer {
-                // If the overflowing trailing line *could* have been our key,
-                // it is unsafe to silently skip it: report explicitly.
-                if line_prefix_could_match_key(
-                    &current_line_buffer[..current_line_length],
-                    target_field_key,
-                ) {
-                    return Err(ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer);
-                }
-            } else if current_line_length > 0 {
-                match try_match_line_against_key::<OUTPUT_BUFFER_BYTES>(
-                    &current_line_buffer[..current_line_length],
-                    target_field_key,
-                )? {
-                    Some(found_value_tuple) => return Ok(found_value_tuple),
-                    None => {}
-                }
-            }
-            return Err(ReadTomlFieldError::RsLsfFieldNotFound);
-        }
+/// Reads a TOML integer from the file into an `i64` inside
+/// `allowed_range`; see [`read_i64_field_from_source_no_heap`].
+pub fn read_i64_field_from_toml_no_heap<
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    absolute_toml_file_path: &str,
+    target_field_key: &str,
+    allowed_range: RangeInclusive<i64>,
+) -> Result<i64, ReadTomlFieldError> {
+    read_i64_field_from_source_no_heap::<READ_CHUNK_BYTES, MAX_LINE_BYTES, MAX_BYTES_SCANNED>(
+        &mut RsLsfFileSource::new(absolute_toml_file_path),
+        target_field_key,
+        allowed_range,
+    )
+}
 
-        cumulative_bytes_scanned =
-            cumulative_bytes_scanned.saturating_add(bytes_read_this_chunk as u64);
-        if cumulative_bytes_scanned > RSLSF_MAX_BYTES_SCANNED {
-            return Err(ReadTomlFieldError::RsLsfSafetyBudgetExhausted);
-        }
+/// Reads a TOML boolean from the file; see
+/// [`read_bool_field_from_source_no_heap`].
+pub fn read_bool_field_from_toml_no_heap<
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    absolute_toml_file_path: &str,
+    target_field_key: &str,
+) -> Result<bool, ReadTomlFieldError> {
+    read_bool_field_from_source_no_heap::<READ_CHUNK_BYTES, MAX_LINE_BYTES, MAX_BYTES_SCANNED>(
+        &mut RsLsfFileSource::new(absolute_toml_file_path),
+        target_field_key,
+    )
+}
 
-        // ------------------------------------------------------------------
-        // Byte-by-byte line accumulator. Bounded by `bytes_read_this_chunk`
-        // (always <= RSLSF_READ_CHUNK_BYTES), so this inner loop is bounded.
-        // ------------------------------------------------------------------
-        let mut byte_index_in_chunk: usize = 0;
-        while byte_index_in_chunk < bytes_read_this_chunk {
-            let current_byte: u8 = chunk_read_buffer[byte_index_in_chunk];
-            byte_index_in_chunk += 1;
-
-            match current_byte {
-                b'\n' => {
-                    if current_line_overflowed_buffer {
-                        if line_prefix_could_match_key(
-                            &current_line_buffer[..current_line_length],
-                            target_field_key,
-                        ) {
-                            return Err(ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer);
-                        }
-                        // Otherwise: this unrelated long line cannot affect us;
-                        // continue scanning the file.
-                    } else if current_line_length > 0 {
-                        match try_match_line_against_key::<OUTPUT_BUFFER_BYTES>(
-                            &current_line_buffer[..current_line_length],
-                            target_field_key,
-                        )? {
-                            Some(found_value_tuple) => return Ok(found_value_tuple),
-                            None => {}
-                        }
-                    }
-                    current_line_length = 0;
-                    current_line_overflowed_buffer = false;
-                }
-                b'\r' => {
-                    // Drop CR so CRLF and LF line endings are both handled.
-                    // A bare CR (old Mac line endings) is also dropped; not
-                    // a supported terminator per the in-scope policy above.
-                }
-                _ => {
-                    if current_line_length < RSLSF_MAX_LINE_BYTES {
-                        current_line_buffer[current_line_length] = current_byte;
-                        current_line_length += 1;
-                    } else {
-                        // Overflow: stop accumulating but keep the prefix so
-                        // we can decide at line end whether overflow matters.
-                        current_line_overflowed_buffer = true;
-                    }
-                }
-            }
-        }
-    }
+/// Reads a single-line TOML integer array from the file into a
+/// fixed-capacity stack array; see
+/// [`read_i64_array_field_from_source_no_heap`].
+pub fn read_i64_array_field_from_toml_no_heap<
+    const ARRAY_CAPACITY: usize,
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    absolute_toml_file_path: &str,
+    target_field_key: &str,
+) -> Result<([i64; ARRAY_CAPACITY], usize), ReadTomlFieldError> {
+    read_i64_array_field_from_source_no_heap::<ARRAY_CAPACITY, READ_CHUNK_BYTES, MAX_LINE_BYTES, MAX_BYTES_SCANNED>(
+        &mut RsLsfFileSource::new(absolute_toml_file_path),
+        target_field_key,
+    )
 }
 
 // ----------------------------------------------------------------------------
-// Internal helpers (pure, stateless, no heap)
+// Diagnostics
 // ----------------------------------------------------------------------------
 
-/// Attempt to match a single fully-accumulated line against `target_field_key`.
+/// Writes a caret-style diagnostic for `span` to `writer`:
 ///
-/// Returns:
-/// * `Ok(Some((buf, len)))` — line matched the key and the value fit.
-/// * `Ok(None)`              — line did not match the key (skip and continue).
-/// * `Err(...)`              — line matched the key but the value will not
-///                              fit in `OUTPUT_BUFFER_BYTES`.
-fn try_match_line_against_key<const OUTPUT_BUFFER_BYTES: usize>(
-    raw_line_bytes: &[u8],
-    target_field_key: &str,
-) -> Result<Option<([u8; OUTPUT_BUFFER_BYTES], usize)>, ReadTomlFieldError> {
-    let trimmed_line_bytes: &[u8] = trim_ascii_whitespace(raw_line_bytes);
-
-    // Empty lines and full-line comments cannot be key-value pairs.
-    if trimmed_line_bytes.is_empty() {
-        return Ok(None);
-    }
-    if trimmed_line_bytes[0] == b'#' {
-        return Ok(None);
-    }
-
-    let key_bytes: &[u8] = target_field_key.as_bytes();
-    if trimmed_line_bytes.len() < key_bytes.len() {
-        return Ok(None);
-    }
-
-    // The line must begin with the exact key bytes...
-    if &trimmed_line_bytes[..key_bytes.len()] != key_bytes {
-        return Ok(None);
-    }
-
-    // ...followed by optional whitespace and a single '='. This prevents
-    // partial-prefix collisions such as key "name" against line "name_long".
-    let post_key_bytes: &[u8] = &trimmed_line_bytes[key_bytes.len()..];
-    let mut cursor_position: usize = 0;
-    while cursor_position < post_key_bytes.len()
-        && is_ascii_space_or_tab_byte(post_key_bytes[cursor_position])
-    {
-        cursor_position += 1;
+/// ```text
+/// error: value too long
+///  --> /etc/myapp/config.toml:4:11
+///   |
+/// 4 | node_id = "alice-node-01-and-more"
+///   |           ^^^^^^^^^^^^^^^^^^^^^^^^
+/// ```
+///
+/// The line is re-read from `span.file_path` into a stack buffer of
+/// [`RSLSF_MAX_LINE_BYTES`]; a longer line is shown cut at that length.
+/// Carets are placed per character (UTF-8 continuation bytes take no
+/// column), with tabs kept so they line up in a terminal.
+///
+/// # Policy
+/// This prints the file path AND a line of the file. It is meant for an
+/// operator at a terminal or for debug builds, NOT for production logs,
+/// which should keep to the terse error codes.
+pub fn write_rslsf_caret_diagnostic<W: Write>(
+    writer: &mut W,
+    span: &RsLsfSpan<'_>,
+    message: &str,
+) -> std::io::Result<()> {
+    // ----------------------------------------------------------------------
+    // Re-read the line the span starts on (stack buffer only)
+    // ----------------------------------------------------------------------
+    let column_offset: u64 = span.column.saturating_sub(1);
+    let line_start_offset: u64 = span.byte_start.saturating_sub(column_offset);
+    let mut line_buffer: [u8; RSLSF_MAX_LINE_BYTES] = [0u8; RSLSF_MAX_LINE_BYTES];
+    let mut line_length: usize = 0;
+
+    let mut open_file_handle: File = File::open(span.file_path)?;
+    open_file_handle.seek(SeekFrom::Start(line_start_offset))?;
+    // Bounded: each pass either fills more of the buffer or stops.
+    while line_length < RSLSF_MAX_LINE_BYTES {
+        let bytes_read: usize = open_file_handle.read(&mut line_buffer[line_length..])?;
+        if bytes_read == 0 {
+            break;
+        }
+        line_length += bytes_read;
     }
-    if cursor_position >= post_key_bytes.len() || post_key_bytes[cursor_position] != b'=' {
-        return Ok(None);
+    let mut shown_line_length: usize = 0;
+    while shown_line_length < line_length && line_buffer[shown_line_length] != b'\n' {
+        shown_line_length += 1;
     }
-    cursor_position += 1;
-    while cursor_position < post_key_bytes.len()
-        && is_ascii_space_or_tab_byte(post_key_bytes[cursor_position])
-    {
-        cursor_position += 1;
+    if shown_line_length > 0 && line_buffer[shown_line_length - 1] == b'\r' {
+        shown_line_length -= 1;
     }
+    let shown_line_bytes: &[u8] = &line_buffer[..shown_line_length];
 
-    // Strip a single pair of surrounding double quotes, if present.
-    let raw_value_bytes: &[u8] = &post_key_bytes[cursor_position..];
-    let stripped_value_bytes: &[u8] = strip_surrounding_double_quotes(raw_value_bytes);
-
-    if stripped_value_bytes.len() > OUTPUT_BUFFER_BYTES {
-        return Err(ReadTomlFieldError::RsLsfValueExceedsOutputBuffer);
+    // ----------------------------------------------------------------------
+    // Header, gutter and source line
+    // ----------------------------------------------------------------------
+    let mut line_number_digits: u64 = 1;
+    let mut remaining_line_number: u64 = span.line_number / 10;
+    while remaining_line_number > 0 {
+        line_number_digits += 1;
+        remaining_line_number /= 10;
     }
 
-    let mut output_buffer: [u8; OUTPUT_BUFFER_BYTES] = [0u8; OUTPUT_BUFFER_BYTES];
-    output_buffer[..stripped_value_bytes.len()].copy_from_slice(stripped_value_bytes);
-    Ok(Some((output_buffer, stripped_value_bytes.len())))
-}
-
-/// True iff, after leading-whitespace trim, `raw_line_bytes` starts with the
-/// exact bytes of `candidate_key`. Used solely to decide whether an overflowed
-/// line could have been the line we cared about.
-fn line_prefix_could_match_key(raw_line_bytes: &[u8], candidate_key: &str) -> bool {
-    let mut leading_index: usize = 0;
-    while leading_index < raw_line_bytes.len()
-        && is_ascii_space_or_tab_byte(raw_line_bytes[leading_index])
-    {
-        leading_index += 1;
-    }
-    let post_whitespace_bytes: &[u8] = &raw_line_bytes[leading_index..];
-    let key_bytes: &[u8] = candidate_key.as_bytes();
-    if post_whitespace_bytes.len() < key_bytes.len() {
-        return false;
-    }
-    &post_whitespace_bytes[..key_bytes.len()] == key_bytes
-}
+    writeln!(writer, "error: {}", message)?;
+    write_repeated_byte(writer, b' ', line_number_digits)?;
+    writeln!(writer, "--> {}", span)?;
+    write_repeated_byte(writer, b' ', line_number_digits)?;
+    writeln!(writer, " |")?;
+    write!(writer, "{} | ", span.line_number)?;
+    writer.write_all(shown_line_bytes)?;
+    writeln!(writer)?;
 
-/// Trim ASCII whitespace (space, tab, CR, LF) from both ends, without
-/// allocating. Returns a sub-slice of the input.
-fn trim_ascii_whitespace(input_bytes: &[u8]) -> &[u8] {
-    let mut start_index: usize = 0;
-    let mut end_index: usize = input_bytes.len();
-    while start_index < end_index && is_ascii_whitespace_byte(input_bytes[start_index]) {
-        start_index += 1;
+    // ----------------------------------------------------------------------
+    // Carets under the span's bytes on this line (at least one)
+    // ----------------------------------------------------------------------
+    write_repeated_byte(writer, b' ', line_number_digits)?;
+    write!(writer, " | ")?;
+    let caret_start: usize = (column_offset as usize).min(shown_line_bytes.len());
+    let span_length: u64 = span.byte_end.saturating_sub(span.byte_start);
+    let caret_end: usize = (caret_start as u64)
+        .saturating_add(span_length)
+        .min(shown_line_bytes.len() as u64) as usize;
+    for &line_byte in &shown_line_bytes[..caret_start] {
+        if line_byte == b'\t' {
+            writer.write_all(b"\t")?;
+        } else if !is_utf8_continuation_byte(line_byte) {
+            writer.write_all(b" ")?;
+        }
     }
-    while end_index > start_index && is_ascii_whitespace_byte(input_bytes[end_index - 1]) {
-        end_index -= 1;
+    let mut caret_count: u64 = 0;
+    for &line_byte in &shown_line_bytes[caret_start..caret_end] {
+        if !is_utf8_continuation_byte(line_byte) {
+            caret_count += 1;
+        }
     }
-    &input_bytes[start_index..end_index]
+    write_repeated_byte(writer, b'^', caret_count.max(1))?;
+    writeln!(writer)
 }
 
-#[inline]
-fn is_ascii_whitespace_byte(byte_value: u8) -> bool {
-    matches!(byte_value, b' ' | b'\t' | b'\r' | b'\n')
+/// Write `byte_value` `count` times, without allocating.
+fn write_repeated_byte<W: Write>(writer: &mut W, byte_value: u8, count: u64) -> std::io::Result<()> {
+    let mut written_count: u64 = 0;
+    while written_count < count {
+        writer.write_all(&[byte_value])?;
+        written_count += 1;
+    }
+    Ok(())
 }
 
 #[inline]
-fn is_ascii_space_or_tab_byte(byte_value: u8) -> bool {
-    matches!(byte_value, b' ' | b'\t')
-}
-
-/// If `input_bytes` is at least two bytes long and both first and last bytes
-/// are `"`, return the inner slice; otherwise return `input_bytes` unchanged.
-/// Does not handle escape sequences — out of scope for this module.
-fn strip_surrounding_double_quotes(input_bytes: &[u8]) -> &[u8] {
-    if input_bytes.len() >= 2
-        && input_bytes[0] == b'"'
-        && input_bytes[input_bytes.len() - 1] == b'"'
-    {
-        &input_bytes[1..input_bytes.len() - 1]
-    } else {
-        input_bytes
-    }
+fn is_utf8_continuation_byte(byte_value: u8) -> bool {
+    byte_value & 0xC0 == 0x80
 }
 
 // ----------------------------------------------------------------------------
@@ -710,7 +469,53 @@ fn strip_surrounding_double_quotes(input_bytes: &[u8]) -> &[u8] {
 mod rslsf_tests {
     use super::*;
     use std::io::Write;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
+
+    /// Scan parameters shared by most tests: deliberately small, so long
+    /// lines and chunk boundaries are cheap to exercise.
+    const TEST_READ_CHUNK_BYTES: usize = 16;
+    const TEST_MAX_LINE_BYTES: usize = 32;
+    const TEST_MAX_BYTES_SCANNED: u64 = RSLSF_MAX_BYTES_SCANNED;
+
+    /// The single-key reader with the test scan parameters.
+    fn read_test_field<const OUTPUT_BUFFER_BYTES: usize>(
+        absolute_toml_file_path: &str,
+        target_field_key: &str,
+    ) -> Result<([u8; OUTPUT_BUFFER_BYTES], usize), ReadTomlFieldError> {
+        read_single_line_string_field_from_toml_no_heap::<
+            OUTPUT_BUFFER_BYTES,
+            TEST_READ_CHUNK_BYTES,
+            TEST_MAX_LINE_BYTES,
+            TEST_MAX_BYTES_SCANNED,
+        >(absolute_toml_file_path, target_field_key)
+    }
+
+    /// The spanned single-key reader with the test scan parameters.
+    fn read_test_field_with_span<'path, const OUTPUT_BUFFER_BYTES: usize>(
+        absolute_toml_file_path: &'path str,
+        target_field_key: &str,
+    ) -> Result<([u8; OUTPUT_BUFFER_BYTES], usize, RsLsfSpan<'path>), RsLsfSpannedError<'path>> {
+        read_single_line_string_field_from_toml_no_heap_with_span::<
+            OUTPUT_BUFFER_BYTES,
+            TEST_READ_CHUNK_BYTES,
+            TEST_MAX_LINE_BYTES,
+            TEST_MAX_BYTES_SCANNED,
+        >(absolute_toml_file_path, target_field_key)
+    }
+
+    /// The one-pass multi-key reader with the test scan parameters.
+    fn read_test_fields<const KEY_COUNT: usize, const OUTPUT_BUFFER_BYTES: usize>(
+        absolute_toml_file_path: &str,
+        target_field_keys: &[&str; KEY_COUNT],
+    ) -> Result<[RsLsfFieldSlot<OUTPUT_BUFFER_BYTES>; KEY_COUNT], ReadTomlFieldError> {
+        read_single_line_string_fields_from_toml_no_heap::<
+            KEY_COUNT,
+            OUTPUT_BUFFER_BYTES,
+            TEST_READ_CHUNK_BYTES,
+            TEST_MAX_LINE_BYTES,
+            TEST_MAX_BYTES_SCANNED,
+        >(absolute_toml_file_path, target_field_keys)
+    }
 
     /// Helper: write a fresh temp file with the given contents and return its
     /// absolute path. Test-only; may use heap/panic-on-failure freely.
@@ -736,7 +541,7 @@ mod rslsf_tests {
         path_buffer
     }
 
-    fn path_as_str(path: &PathBuf) -> &str {
+    fn path_as_str(path: &Path) -> &str {
         path.to_str().expect("test setup: temp path must be UTF-8")
     }
 
@@ -744,7 +549,7 @@ mod rslsf_tests {
     fn rslsf_finds_simple_quoted_value() {
         let test_path = write_unique_temp_toml("simple_quoted", "name = \"alice\"\n");
         let (output_buffer, written_length) =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "name")
+            read_test_field::<16>(path_as_str(&test_path), "name")
                 .expect("should find value");
         assert_eq!(&output_buffer[..written_length], b"alice");
     }
@@ -753,7 +558,7 @@ mod rslsf_tests {
     fn rslsf_finds_unquoted_value() {
         let test_path = write_unique_temp_toml("unquoted", "port = 8080\n");
         let (output_buffer, written_length) =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "port")
+            read_test_field::<16>(path_as_str(&test_path), "port")
                 .expect("should find value");
         assert_eq!(&output_buffer[..written_length], b"8080");
     }
@@ -762,7 +567,7 @@ mod rslsf_tests {
     fn rslsf_handles_crlf_endings() {
         let test_path = write_unique_temp_toml("crlf", "name = \"bob\"\r\nother = \"x\"\r\n");
         let (output_buffer, written_length) =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "name")
+            read_test_field::<16>(path_as_str(&test_path), "name")
                 .expect("should find value");
         assert_eq!(&output_buffer[..written_length], b"bob");
     }
@@ -774,7 +579,7 @@ mod rslsf_tests {
             "# a header comment\n\n   # indented comment\nname = \"carol\"\n",
         );
         let (output_buffer, written_length) =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "name")
+            read_test_field::<16>(path_as_str(&test_path), "name")
                 .expect("should find value");
         assert_eq!(&output_buffer[..written_length], b"carol");
     }
@@ -787,7 +592,7 @@ mod rslsf_tests {
             "name_long = \"WRONG\"\nname = \"RIGHT\"\n",
         );
         let (output_buffer, written_length) =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "name")
+            read_test_field::<16>(path_as_str(&test_path), "name")
                 .expect("should find value");
         assert_eq!(&output_buffer[..written_length], b"RIGHT");
     }
@@ -796,7 +601,7 @@ mod rslsf_tests {
     fn rslsf_returns_field_not_found_when_missing() {
         let test_path = write_unique_temp_toml("missing", "other = \"x\"\n");
         let result =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "name");
+            read_test_field::<16>(path_as_str(&test_path), "name");
         assert_eq!(result, Err(ReadTomlFieldError::RsLsfFieldNotFound));
     }
 
@@ -805,7 +610,7 @@ mod rslsf_tests {
         // "toolongxx" is 9 bytes, which exceeds the 8-byte output buffer.
         let test_path = write_unique_temp_toml("too_long", "name = \"toolongxx\"\n");
         let result =
-            read_single_line_string_field_from_toml_no_heap::<8>(path_as_str(&test_path), "name");
+            read_test_field::<8>(path_as_str(&test_path), "name");
         assert_eq!(
             result,
             Err(ReadTomlFieldError::RsLsfValueExceedsOutputBuffer)
@@ -819,7 +624,7 @@ mod rslsf_tests {
         // is portable to Windows runners.
         let mut bogus_path = std::env::temp_dir();
         bogus_path.push("rslsf_test_definitely_does_not_exist_xyzzy_12345.toml");
-        let result = read_single_line_string_field_from_toml_no_heap::<16>(
+        let result = read_test_field::<16>(
             bogus_path
                 .to_str()
                 .expect("test setup: temp path must be UTF-8"),
@@ -832,17 +637,17 @@ mod rslsf_tests {
     fn rslsf_rejects_empty_key() {
         let test_path = write_unique_temp_toml("empty_key", "name = \"x\"\n");
         let result =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "");
+            read_test_field::<16>(path_as_str(&test_path), "");
         assert_eq!(result, Err(ReadTomlFieldError::RsLsfEmptyKey));
     }
 
     #[test]
     fn rslsf_rejects_key_too_long() {
         let test_path = write_unique_temp_toml("key_too_long", "name = \"x\"\n");
-        // Build a key longer than RSLSF_MAX_LINE_BYTES without using `vec!`
+        // Build a key longer than TEST_MAX_LINE_BYTES without using `vec!`
         // anywhere in production code — this is test-only setup.
-        let oversized_key: String = "k".repeat(RSLSF_MAX_LINE_BYTES + 1);
-        let result = read_single_line_string_field_from_toml_no_heap::<16>(
+        let oversized_key: String = "k".repeat(TEST_MAX_LINE_BYTES + 1);
+        let result = read_test_field::<16>(
             path_as_str(&test_path),
             &oversized_key,
         );
@@ -854,7 +659,7 @@ mod rslsf_tests {
         // No final '\n' — the last line must still be processed at EOF.
         let test_path = write_unique_temp_toml("no_trailing_lf", "name = \"dora\"");
         let (output_buffer, written_length) =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "name")
+            read_test_field::<16>(path_as_str(&test_path), "name")
                 .expect("should find value at EOF without newline");
         assert_eq!(&output_buffer[..written_length], b"dora");
     }
@@ -872,7 +677,7 @@ mod rslsf_tests {
         contents.push_str("target = \"eve\"\n");
         let test_path = write_unique_temp_toml("many_lines", &contents);
         let (output_buffer, written_length) =
-            read_single_line_string_field_from_toml_no_heap::<16>(
+            read_test_field::<16>(
                 path_as_str(&test_path),
                 "target",
             )
@@ -882,11 +687,11 @@ mod rslsf_tests {
 
     #[test]
     fn rslsf_unrelated_long_line_does_not_abort_scan() {
-        // A line far longer than RSLSF_MAX_LINE_BYTES that is NOT the key
+        // A line far longer than TEST_MAX_LINE_BYTES that is NOT the key
         // we are looking for must be silently skipped, not aborted, so the
         // real key further down in the file is still found.
         let oversized_unrelated_line: String = std::iter::once("other_key = \"")
-            .chain(std::iter::repeat("X").take(RSLSF_MAX_LINE_BYTES + 64))
+            .chain(std::iter::repeat_n("X", TEST_MAX_LINE_BYTES + 64))
             .chain(std::iter::once("\"\n"))
             .collect();
         let mut contents = String::new();
@@ -894,7 +699,7 @@ mod rslsf_tests {
         contents.push_str("name = \"frank\"\n");
         let test_path = write_unique_temp_toml("unrelated_overflow", &contents);
         let (output_buffer, written_length) =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "name")
+            read_test_field::<16>(path_as_str(&test_path), "name")
                 .expect("unrelated overflowing line should not block finding the real key");
         assert_eq!(&output_buffer[..written_length], b"frank");
     }
@@ -906,13 +711,13 @@ mod rslsf_tests {
         // overflow explicitly.
         let mut contents = String::new();
         contents.push_str("name = \"");
-        for _ in 0..(RSLSF_MAX_LINE_BYTES + 32) {
+        for _ in 0..(TEST_MAX_LINE_BYTES + 32) {
             contents.push('Z');
         }
         contents.push_str("\"\n");
         let test_path = write_unique_temp_toml("matching_overflow", &contents);
         let result =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "name");
+            read_test_field::<16>(path_as_str(&test_path), "name");
         assert_eq!(
             result,
             Err(ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer)
@@ -924,7 +729,7 @@ mod rslsf_tests {
         // Both leading whitespace and varied spacing around '=' must work.
         let test_path = write_unique_temp_toml("whitespace", "   name\t=\t  \"grace\"   \n");
         let (output_buffer, written_length) =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "name")
+            read_test_field::<16>(path_as_str(&test_path), "name")
                 .expect("should find value with varied whitespace");
         assert_eq!(&output_buffer[..written_length], b"grace");
     }
@@ -937,7 +742,7 @@ mod rslsf_tests {
         let test_path =
             write_unique_temp_toml("duplicate_key", "name = \"first\"\nname = \"second\"\n");
         let (output_buffer, written_length) =
-            read_single_line_string_field_from_toml_no_heap::<16>(path_as_str(&test_path), "name")
+            read_test_field::<16>(path_as_str(&test_path), "name")
                 .expect("should find first value");
         assert_eq!(&output_buffer[..written_length], b"first");
     }
@@ -951,7 +756,7 @@ mod rslsf_tests {
         // first (we get OutputBufferZeroSized, NOT FileOpenFailed).
         let mut bogus_path = std::env::temp_dir();
         bogus_path.push("rslsf_test_zero_buffer_should_not_open.toml");
-        let result = read_single_line_string_field_from_toml_no_heap::<0>(
+        let result = read_test_field::<0>(
             bogus_path
                 .to_str()
                 .expect("test setup: temp path must be UTF-8"),
@@ -959,4 +764,486 @@ mod rslsf_tests {
         );
         assert_eq!(result, Err(ReadTomlFieldError::RsLsfOutputBufferZeroSized));
     }
+
+    #[test]
+    fn rslsf_with_span_reports_value_position() {
+        let test_path = write_unique_temp_toml(
+            "span_value",
+            "# comment\r\n\r\n  node_id = \"alice\"\r\nport = 8080",
+        );
+        let path_str = path_as_str(&test_path);
+
+        let (output_buffer, written_length, value_span) =
+            read_test_field_with_span::<16>(path_str, "node_id")
+                .expect("should find value");
+        assert_eq!(&output_buffer[..written_length], b"alice");
+        assert_eq!(
+            value_span,
+            RsLsfSpan { file_path: path_str, line_number: 3, column: 13, byte_start: 25, byte_end: 32 }
+        );
+
+        // Last line without a trailing newline.
+        let (_, _, value_span) =
+            read_test_field_with_span::<16>(path_str, "port")
+                .expect("should find value at EOF");
+        assert_eq!((value_span.line_number, value_span.column), (4, 8));
+        assert_eq!((value_span.byte_start, value_span.byte_end), (41, 45));
+        assert_eq!(value_span.to_string(), format!("{}:4:8", path_str));
+    }
+
+    #[test]
+    fn rslsf_with_span_reports_error_positions() {
+        let mut contents = String::from("name = \"toolongxx\"\n");
+        contents.push_str("  long = \"");
+        contents.push_str(&"Z".repeat(TEST_MAX_LINE_BYTES + 8));
+        contents.push_str("\"\n");
+        let test_path = write_unique_temp_toml("span_errors", &contents);
+        let path_str = path_as_str(&test_path);
+
+        let too_big = read_test_field_with_span::<8>(path_str, "name")
+            .expect_err("value does not fit");
+        assert_eq!(
+            too_big,
+            RsLsfSpannedError {
+                error: ReadTomlFieldError::RsLsfValueExceedsOutputBuffer,
+                span: Some(RsLsfSpan { file_path: path_str, line_number: 1, column: 8, byte_start: 7, byte_end: 18 }),
+            }
+        );
+
+        let line_too_big = read_test_field_with_span::<8>(path_str, "long")
+            .expect_err("line does not fit");
+        assert_eq!(line_too_big.error, ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer);
+        let line_span = line_too_big.span.expect("overflowing key line has a span");
+        assert_eq!((line_span.line_number, line_span.column), (2, 3));
+        assert_eq!(line_span.byte_end, contents.len() as u64 - 1);
+
+        let not_found = read_test_field_with_span::<8>(path_str, "missing")
+            .expect_err("key is absent");
+        assert_eq!(
+            not_found,
+            RsLsfSpannedError { error: ReadTomlFieldError::RsLsfFieldNotFound, span: None }
+        );
+    }
+
+    #[test]
+    fn rslsf_bare_carriage_return_stays_in_line() {
+        // Only the CR of a CRLF is a line terminator; a bare CR is line
+        // content, so byte positions after it stay exact.
+        let test_path = write_unique_temp_toml("bare_cr", "name = \"a\rb\"\r\n");
+        let (output_buffer, written_length, value_span) =
+            read_test_field_with_span::<16>(path_as_str(&test_path), "name")
+                .expect("should find value");
+        assert_eq!(&output_buffer[..written_length], b"a\rb");
+        assert_eq!((value_span.byte_start, value_span.byte_end), (7, 12));
+    }
+
+    #[test]
+    fn rslsf_caret_diagnostic_points_at_value() {
+        let test_path = write_unique_temp_toml("caret", "# config\n\tnode_id = \"héllo-node-01\"\n");
+        let path_str = path_as_str(&test_path);
+        let spanned_error =
+            read_test_field_with_span::<4>(path_str, "node_id")
+                .expect_err("value does not fit");
+        let error_span = spanned_error.span.expect("value error has a span");
+
+        let mut rendered: Vec<u8> = Vec::new();
+        write_rslsf_caret_diagnostic(&mut rendered, &error_span, "E_VALUE_TOO_BIG")
+            .expect("render to memory");
+        assert_eq!(
+            String::from_utf8(rendered).expect("utf-8 output"),
+            format!(
+                "error: E_VALUE_TOO_BIG\n --> {}:2:12\n  |\n2 | \tnode_id = \"héllo-node-01\"\n  | \t          ^^^^^^^^^^^^^^^\n",
+                path_str
+            )
+        );
+    }
+
+    #[test]
+    fn rslsf_finds_table_and_dotted_key_paths() {
+        let test_path = write_unique_temp_toml(
+            "table_paths",
+            "node_id = \"root\"\n\
+             other.node_id = \"dotted-root\"\n\
+             [ network ]  # main table\n\
+             node_id   = \"net\"\n\
+             tls . mode = \"strict\"\n\
+             [network.backup]\n\
+             node_id = \"backup\"\n\
+             [[peers]]\n\
+             node_id = \"peer\"\n",
+        );
+        let path_str = path_as_str(&test_path);
+        let read_value = |target_field_key: &str| {
+            read_test_field::<16>(path_str, target_field_key)
+                .map(|(output_buffer, written_length)| output_buffer[..written_length].to_vec())
+        };
+
+        assert_eq!(read_value("node_id"), Ok(b"root".to_vec()));
+        assert_eq!(read_value("other.node_id"), Ok(b"dotted-root".to_vec()));
+        assert_eq!(read_value("network.node_id"), Ok(b"net".to_vec()));
+        assert_eq!(read_value("network.tls.mode"), Ok(b"strict".to_vec()));
+        assert_eq!(read_value("network.backup.node_id"), Ok(b"backup".to_vec()));
+        // Keys under a table are not top-level keys, and arrays of tables
+        // are out of scope.
+        assert_eq!(read_value("mode"), Err(ReadTomlFieldError::RsLsfFieldNotFound));
+        assert_eq!(read_value("backup.node_id"), Err(ReadTomlFieldError::RsLsfFieldNotFound));
+        assert_eq!(read_value("peers.node_id"), Err(ReadTomlFieldError::RsLsfFieldNotFound));
+
+        let (_, _, value_span) =
+            read_test_field_with_span::<16>(path_str, "network.node_id")
+                .expect("should find value");
+        assert_eq!((value_span.line_number, value_span.column), (4, 13));
+    }
+
+    #[test]
+    fn rslsf_bare_key_is_not_found_inside_a_table() {
+        let test_path = write_unique_temp_toml("bare_key_in_table", "[server]\nport = 8080\n");
+        let read_result =
+            read_test_field::<16>(path_as_str(&test_path), "port");
+        assert_eq!(read_result, Err(ReadTomlFieldError::RsLsfFieldNotFound));
+    }
+
+    #[test]
+    fn rslsf_overflowed_header_line_is_not_guessed() {
+        // `]` inside the kept prefix: the header is still read.
+        let mut contents = String::from("[network] # ");
+        contents.push_str(&"c".repeat(TEST_MAX_LINE_BYTES));
+        contents.push_str("\nnode_id = \"net\"\n");
+        let test_path = write_unique_temp_toml("long_header_comment", &contents);
+        let (output_buffer, written_length) =
+            read_test_field::<16>(path_as_str(&test_path), "network.node_id")
+                .expect("header with long comment is read");
+        assert_eq!(&output_buffer[..written_length], b"net");
+
+        // `]` beyond the buffer: which table follows is unknown.
+        let mut contents = String::from("node_id = \"root\"\n  [");
+        contents.push_str(&"n".repeat(TEST_MAX_LINE_BYTES));
+        contents.push_str("]\nnode_id = \"net\"\n");
+        let test_path = write_unique_temp_toml("long_header", &contents);
+        let path_str = path_as_str(&test_path);
+        let spanned_error =
+            read_test_field_with_span::<16>(path_str, "network.node_id")
+                .expect_err("table is unknown");
+        assert_eq!(spanned_error.error, ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer);
+        let line_span = spanned_error.span.expect("overflowed header has a span");
+        assert_eq!((line_span.line_number, line_span.column), (2, 3));
+
+        // A top-level key is found before any header, whatever it holds.
+        let (output_buffer, written_length) =
+            read_test_field::<16>(path_str, "node_id")
+                .expect("top-level key precedes the header");
+        assert_eq!(&output_buffer[..written_length], b"root");
+    }
+
+    #[test]
+    fn rslsf_multi_key_reads_all_keys_in_one_pass() {
+        let mut contents = String::from("# startup values\nnode_id = \"alice\"\nlong = \"");
+        contents.push_str(&"x".repeat(TEST_MAX_LINE_BYTES));
+        contents.push_str("\"\nmode = \"production-mode\"\n[network]\nbind_port = 7000\nnode_id = \"net\"\n");
+        let test_path = write_unique_temp_toml("multi_key", &contents);
+
+        let field_slots = read_test_fields::<6, 8>(
+            path_as_str(&test_path),
+            &["network.bind_port", "node_id", "mode", "long", "missing", "network.node_id"],
+        )
+        .expect("file is readable");
+
+        let statuses = field_slots.map(|field_slot| field_slot.status);
+        assert_eq!(
+            statuses,
+            [
+                RsLsfFieldStatus::Found,
+                RsLsfFieldStatus::Found,
+                RsLsfFieldStatus::ValueExceedsOutputBuffer,
+                RsLsfFieldStatus::MatchingLineExceedsScanBuffer,
+                RsLsfFieldStatus::NotFound,
+                RsLsfFieldStatus::Found,
+            ]
+        );
+        assert_eq!(&field_slots[0].output_buffer[..field_slots[0].written_length], b"7000");
+        assert_eq!(&field_slots[1].output_buffer[..field_slots[1].written_length], b"alice");
+        assert_eq!(&field_slots[5].output_buffer[..field_slots[5].written_length], b"net");
+        assert_eq!(field_slots[2].written_length, 0);
+        assert_eq!(field_slots[2].output_buffer, [0u8; 8]);
+    }
+
+    #[test]
+    fn rslsf_multi_key_matches_single_key_results() {
+        let test_path = write_unique_temp_toml(
+            "multi_key_same",
+            "name = \"first\"\nname = \"second\"\r\n[server]\nport = 80\n",
+        );
+        let path_str = path_as_str(&test_path);
+        let target_field_keys = ["name", "server.port", "port"];
+        let field_slots =
+            read_test_fields::<3, 16>(path_str, &target_field_keys)
+                .expect("file is readable");
+
+        for (field_slot, target_field_key) in field_slots.iter().zip(target_field_keys) {
+            match read_test_field::<16>(path_str, target_field_key) {
+                Ok((output_buffer, written_length)) => {
+                    assert_eq!(field_slot.status, RsLsfFieldStatus::Found);
+                    assert_eq!(field_slot.output_buffer, output_buffer);
+                    assert_eq!(field_slot.written_length, written_length);
+                }
+                Err(error_variant) => {
+                    assert_eq!(error_variant, ReadTomlFieldError::RsLsfFieldNotFound);
+                    assert_eq!(field_slot.status, RsLsfFieldStatus::NotFound);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rslsf_multi_key_rejects_invalid_calls() {
+        let test_path = write_unique_temp_toml("multi_key_invalid", "name = \"x\"\n");
+        let path_str = path_as_str(&test_path);
+        let too_long_key = "k".repeat(TEST_MAX_LINE_BYTES);
+
+        assert_eq!(
+            read_test_fields::<2, 8>(path_str, &["name", ""]),
+            Err(ReadTomlFieldError::RsLsfEmptyKey)
+        );
+        assert_eq!(
+            read_test_fields::<1, 8>(path_str, &[too_long_key.as_str()]),
+            Err(ReadTomlFieldError::RsLsfKeyTooLong)
+        );
+        assert_eq!(
+            read_test_fields::<1, 0>(path_str, &["name"]),
+            Err(ReadTomlFieldError::RsLsfOutputBufferZeroSized)
+        );
+        assert_eq!(
+            read_test_fields::<1, 8>("/nonexistent/rslsf.toml", &["name"]),
+            Err(ReadTomlFieldError::RsLsfFileOpenFailed)
+        );
+        assert_eq!(
+            read_test_fields::<0, 8>(path_str, &[]).map(|slots| slots.len()),
+            Ok(0)
+        );
+    }
+
+    #[test]
+    fn rslsf_scan_parameters_are_chosen_per_call_site() {
+        let contents = format!("# {}\nname = \"alice\"\n", "c".repeat(40));
+        let test_path = write_unique_temp_toml("scan_parameters", &contents);
+        let path_str = path_as_str(&test_path);
+
+        // One-byte chunks: every line is reassembled across reads.
+        let (output_buffer, written_length) =
+            read_single_line_string_field_from_toml_no_heap::<16, 1, 64, 4096>(path_str, "name")
+                .expect("one-byte chunks should still find the value");
+        assert_eq!(&output_buffer[..written_length], b"alice");
+
+        // The recommended defaults.
+        let (output_buffer, written_length) = read_single_line_string_field_from_toml_no_heap::<
+            16,
+            RSLSF_READ_CHUNK_BYTES,
+            RSLSF_MAX_LINE_BYTES,
+            RSLSF_MAX_BYTES_SCANNED,
+        >(path_str, "name")
+        .expect("defaults should find the value");
+        assert_eq!(&output_buffer[..written_length], b"alice");
+
+        // The key length limit follows the call site's line buffer.
+        assert_eq!(
+            read_single_line_string_field_from_toml_no_heap::<16, 16, 4, 4096>(path_str, "name"),
+            Err(ReadTomlFieldError::RsLsfKeyTooLong)
+        );
+        assert_eq!(
+            read_single_line_string_fields_from_toml_no_heap::<1, 16, 16, 4, 4096>(path_str, &["name"])
+                .map(|slots| slots[0].status),
+            Err(ReadTomlFieldError::RsLsfKeyTooLong)
+        );
+    }
+
+    #[test]
+    fn rslsf_scan_budget_is_per_call_site() {
+        let contents = format!("# {}\nname = \"alice\"\n", "c".repeat(40));
+        let test_path = write_unique_temp_toml("scan_budget", &contents);
+        let path_str = path_as_str(&test_path);
+
+        // The key sits past the first 32 bytes, so a 32-byte budget runs
+        // out before it is reached, however the reads are chunked.
+        assert_eq!(
+            read_single_line_string_field_from_toml_no_heap::<16, 16, 32, 32>(path_str, "name"),
+            Err(ReadTomlFieldError::RsLsfSafetyBudgetExhausted)
+        );
+        assert_eq!(
+            read_single_line_string_field_from_toml_no_heap::<16, 1, 32, 32>(path_str, "name"),
+            Err(ReadTomlFieldError::RsLsfSafetyBudgetExhausted)
+        );
+        assert_eq!(
+            read_single_line_string_fields_from_toml_no_heap::<1, 16, 16, 32, 32>(path_str, &["name"])
+                .map(|slots| slots[0].status),
+            Err(ReadTomlFieldError::RsLsfSafetyBudgetExhausted)
+        );
+        let (output_buffer, written_length) =
+            read_single_line_string_field_from_toml_no_heap::<16, 16, 64, 4096>(path_str, "name")
+                .expect("a larger budget should find the value");
+        assert_eq!(&output_buffer[..written_length], b"alice");
+    }
+
+    #[test]
+    fn rslsf_typed_integers_parse_and_range_check() {
+        let test_path = write_unique_temp_toml(
+            "typed_integers",
+            "port = 8080\nbig = 18_446_744_073_709_551_615\nhuge = 18446744073709551616\n\
+             neg = -42\nmin = -9223372036854775808\nhex = 0xff\noct = 0o755\nbin = 0b1010\n\
+             quoted = \"8080\"\nfloat = 1.5\nlead = 0123\nunderscore = 1__0\nsigned_hex = -0x1\n",
+        );
+        let path_str = path_as_str(&test_path);
+        let read_u64 = |key: &str, allowed_range: RangeInclusive<u64>| {
+            read_u64_field_from_toml_no_heap::<16, 64, 4096>(path_str, key, allowed_range)
+        };
+        let read_i64 = |key: &str| read_i64_field_from_toml_no_heap::<16, 64, 4096>(path_str, key, i64::MIN..=i64::MAX);
+
+        assert_eq!(read_u64("port", 1..=65535), Ok(8080));
+        assert_eq!(read_u64("port", 1..=1024), Err(ReadTomlFieldError::RsLsfIntegerOutOfRange));
+        assert_eq!(read_u64("big", 0..=u64::MAX), Ok(u64::MAX));
+        assert_eq!(read_u64("huge", 0..=u64::MAX), Err(ReadTomlFieldError::RsLsfIntegerOutOfRange));
+        assert_eq!(read_u64("neg", 0..=u64::MAX), Err(ReadTomlFieldError::RsLsfIntegerOutOfRange));
+        assert_eq!(read_u64("hex", 0..=u64::MAX), Ok(255));
+        assert_eq!(read_u64("oct", 0..=u64::MAX), Ok(0o755));
+        assert_eq!(read_u64("bin", 0..=u64::MAX), Ok(10));
+
+        assert_eq!(read_i64("neg"), Ok(-42));
+        assert_eq!(read_i64("min"), Ok(i64::MIN));
+        assert_eq!(read_i64("big"), Err(ReadTomlFieldError::RsLsfIntegerOutOfRange));
+        assert_eq!(
+            read_i64_field_from_toml_no_heap::<16, 64, 4096>(path_str, "neg", -10..=10),
+            Err(ReadTomlFieldError::RsLsfIntegerOutOfRange)
+        );
+
+        for malformed_key in ["quoted", "float", "lead", "underscore", "signed_hex"] {
+            assert_eq!(read_i64(malformed_key), Err(ReadTomlFieldError::RsLsfValueNotInteger), "{}", malformed_key);
+        }
+        assert_eq!(read_i64("missing"), Err(ReadTomlFieldError::RsLsfFieldNotFound));
+    }
+
+    #[test]
+    fn rslsf_typed_booleans_are_exact() {
+        let test_path = write_unique_temp_toml(
+            "typed_booleans",
+            "on = true\noff = false\nquoted = \"true\"\ncapital = True\nnumeric = 1\n",
+        );
+        let path_str = path_as_str(&test_path);
+        let read_bool = |key: &str| read_bool_field_from_toml_no_heap::<16, 64, 4096>(path_str, key);
+
+        assert_eq!(read_bool("on"), Ok(true));
+        assert_eq!(read_bool("off"), Ok(false));
+        for invalid_key in ["quoted", "capital", "numeric"] {
+            assert_eq!(read_bool(invalid_key), Err(ReadTomlFieldError::RsLsfValueNotBoolean), "{}", invalid_key);
+        }
+    }
+
+    #[test]
+    fn rslsf_typed_integer_arrays_fill_fixed_capacity() {
+        let test_path = write_unique_temp_toml(
+            "typed_arrays",
+            "ports = [80, 443,-1, 0x10 ]\ntrailing = [1, 2,]\nempty = [ ]\n\
+             strings = [\"a\", \"b\"]\nnested = [[1], [2]]\nholes = [1,,2]\nbare = 1\n\
+             overflow = [9223372036854775808]\n",
+        );
+        let path_str = path_as_str(&test_path);
+        let read_array = |key: &str| read_i64_array_field_from_toml_no_heap::<4, 16, 64, 4096>(path_str, key);
+
+        assert_eq!(read_array("ports"), Ok(([80, 443, -1, 16], 4)));
+        assert_eq!(read_array("trailing"), Ok(([1, 2, 0, 0], 2)));
+        assert_eq!(read_array("empty"), Ok(([0; 4], 0)));
+        assert_eq!(
+            read_i64_array_field_from_toml_no_heap::<3, 16, 64, 4096>(path_str, "ports"),
+            Err(ReadTomlFieldError::RsLsfArrayExceedsCapacity)
+        );
+        for invalid_key in ["strings", "nested", "holes", "bare"] {
+            assert_eq!(read_array(invalid_key), Err(ReadTomlFieldError::RsLsfValueNotIntegerArray), "{}", invalid_key);
+        }
+        assert_eq!(read_array("overflow"), Err(ReadTomlFieldError::RsLsfIntegerOutOfRange));
+    }
+
+    /// A reader that is interrupted by a signal before each successful read.
+    struct InterruptedOnceReader<'bytes> {
+        remaining_bytes: &'bytes [u8],
+        interrupt_next_read: bool,
+    }
+
+    impl Read for InterruptedOnceReader<'_> {
+        fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+            self.interrupt_next_read = !self.interrupt_next_read;
+            if !self.interrupt_next_read {
+                return Err(std::io::Error::from(std::io::ErrorKind::Interrupted));
+            }
+            self.remaining_bytes.read(buffer)
+        }
+    }
+
+    #[test]
+    fn rslsf_io_read_source_adapts_any_reader() {
+        let toml_bytes: &[u8] = b"mode = \"production\"\nport = 8080\n";
+        let (output_buffer, written_length) =
+            read_single_line_string_field_from_source_no_heap::<16, 16, 64, 4096>(
+                &mut RsLsfIoReadSource::new(toml_bytes),
+                "mode",
+            )
+            .expect("a slice reader should work");
+        assert_eq!(&output_buffer[..written_length], b"production");
+
+        let mut interrupted_reader = InterruptedOnceReader {
+            remaining_bytes: toml_bytes,
+            interrupt_next_read: false,
+        };
+        assert_eq!(
+            read_u64_field_from_source_no_heap::<16, 64, 4096>(
+                &mut RsLsfIoReadSource::new(&mut interrupted_reader),
+                "port",
+                0..=u64::MAX,
+            ),
+            Ok(8080)
+        );
+
+        assert_eq!(
+            read_single_line_string_field_from_source_no_heap::<16, 16, 64, 4096>(
+                &mut RsLsfIoReadSource::new(FailingReader),
+                "mode",
+            ),
+            Err(ReadTomlFieldError::RsLsfFileReadFailed)
+        );
+    }
+
+    /// A reader whose every read fails.
+    struct FailingReader;
+
+    impl Read for FailingReader {
+        fn read(&mut self, _buffer: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("device gone"))
+        }
+    }
+
+    #[test]
+    fn rslsf_file_source_opens_lazily_and_matches_slice_source() {
+        let toml_text = "node_id = \"alice\"\n[network]\nbind_port = 7000\n";
+        let test_path = write_unique_temp_toml("file_source", toml_text);
+        let path_str = path_as_str(&test_path);
+
+        // Nothing is opened until the first read.
+        let mut unopened_source = RsLsfFileSource::new("/nonexistent/rslsf.toml");
+        let mut chunk_buffer = [0u8; 4];
+        assert_eq!(
+            unopened_source.read_chunk(&mut chunk_buffer),
+            Err(ReadTomlFieldError::RsLsfFileOpenFailed)
+        );
+
+        for target_field_key in ["node_id", "network.bind_port", "missing"] {
+            assert_eq!(
+                read_test_field::<16>(path_str, target_field_key),
+                read_single_line_string_field_from_source_no_heap::<
+                    16,
+                    TEST_READ_CHUNK_BYTES,
+                    TEST_MAX_LINE_BYTES,
+                    TEST_MAX_BYTES_SCANNED,
+                >(&mut RsLsfSliceSource::new(toml_text.as_bytes()), target_field_key),
+                "{}",
+                target_field_key
+            );
+        }
+    }
 }
@@ -0,0 +1,1950 @@
+//! ============================================================================
+//! Module: read_toml_single_line_string_field_no_heap::rslsf_core
+//! ============================================================================
+//!
+//! The `no_std` core of the no-heap TOML reader: the line scanner, the key
+//! matching, the typed value parsers and the error and span types. It uses
+//! only `core`, never `std` or `alloc`, so this one file can be dropped
+//! into a `#![no_std]` firmware crate as is.
+//!
+//! The core never opens anything. It reads TOML text from an
+//! [`RsLsfByteSource`]: [`RsLsfSliceSource`] for bytes already in memory
+//! (a linked-in config, a memory-mapped flash region), or any other source
+//! implementing the one-method trait (a flash driver, a UART). The parent
+//! module adds the `std::fs` adapter, path-based readers and the caret
+//! diagnostic on top.
+//!
+//! Scope, limits and defensive policy are those of the parent module: one
+//! scan per call, stack buffers sized by const generics, a failsafe byte
+//! budget, and terse zero-data errors. Nothing here panics or allocates.
+//! ============================================================================
+
+use core::ops::{ControlFlow, RangeInclusive};
+
+// ----------------------------------------------------------------------------
+// Byte sources
+// ----------------------------------------------------------------------------
+
+/// Where the core reads TOML text from: anything that can fill a stack
+/// chunk with the next bytes.
+///
+/// Implementations must write at most `chunk_buffer.len()` bytes to the
+/// start of `chunk_buffer` and return how many. `Ok(0)` means the end of
+/// the text; short reads are fine. Failures are reported as a terse
+/// [`ReadTomlFieldError`] (`RsLsfFileReadFailed` unless a more specific
+/// variant applies), never with detail that could leak contents.
+pub trait RsLsfByteSource {
+    fn read_chunk(&mut self, chunk_buffer: &mut [u8]) -> Result<usize, ReadTomlFieldError>;
+}
+
+/// A byte source over TOML text already in memory: a `&'static [u8]` from
+/// `include_bytes!`, a memory-mapped flash region, a receive buffer.
+///
+/// Each read copies the next bytes and advances; a fresh source is needed
+/// to read the same text again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RsLsfSliceSource<'bytes> {
+    remaining_bytes: &'bytes [u8],
+}
+
+impl<'bytes> RsLsfSliceSource<'bytes> {
+    pub fn new(toml_bytes: &'bytes [u8]) -> Self {
+        RsLsfSliceSource { remaining_bytes: toml_bytes }
+    }
+}
+
+impl RsLsfByteSource for RsLsfSliceSource<'_> {
+    fn read_chunk(&mut self, chunk_buffer: &mut [u8]) -> Result<usize, ReadTomlFieldError> {
+        let copied_length: usize = chunk_buffer.len().min(self.remaining_bytes.len());
+        chunk_buffer[..copied_length].copy_from_slice(&self.remaining_bytes[..copied_length]);
+        self.remaining_bytes = &self.remaining_bytes[copied_length..];
+        Ok(copied_length)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Recommended scan parameters
+// ----------------------------------------------------------------------------
+//
+// Every reader takes its scan buffers and budget as const generics
+// (`READ_CHUNK_BYTES`, `MAX_LINE_BYTES`, `MAX_BYTES_SCANNED`), so each call
+// site picks its own stack budget. These are the recommended values for a
+// call site with no special constraints:
+//
+//     read_single_line_string_field_from_source_no_heap::<
+//         16,
+//         RSLSF_READ_CHUNK_BYTES,
+//         RSLSF_MAX_LINE_BYTES,
+//         RSLSF_MAX_BYTES_SCANNED,
+//     >(&mut byte_source, "node_id")
+//
+// All three must be non-zero, and neither buffer may exceed the byte
+// budget; a call site that breaks this does not compile.
+
+/// Recommended `READ_CHUNK_BYTES`: the stack-allocated chunk each
+/// [`RsLsfByteSource::read_chunk`] call fills.
+///
+/// Tradeoff: smaller chunks reduce stack pressure; larger chunks reduce syscall
+/// count. 256 B is comfortable on every realistic stack and keeps syscall
+/// overhead acceptable for the small-config use case this module targets.
+pub const RSLSF_READ_CHUNK_BYTES: usize = 256;
+
+/// Recommended `MAX_LINE_BYTES`: the most bytes accumulated for a single
+/// line during scanning (stack-only). Also the size of the line buffer of
+/// the std adapter's caret diagnostic.
+///
+/// Lines exceeding this limit do NOT silently truncate; see overflow handling
+/// in [`read_single_line_string_field_from_source_no_heap`]. 512 B comfortably
+/// covers any realistic single-line TOML key/value in the in-scope subset.
+pub const RSLSF_MAX_LINE_BYTES: usize = 512;
+
+/// Recommended `MAX_BYTES_SCANNED`: failsafe upper bound on total bytes
+/// scanned from a single source.
+///
+/// Bounds the read loop even if the source keeps returning data (NASA P10 rule 2).
+/// Tune for your project's expected configuration size. 1 MiB is generous for
+/// configuration files while preventing pathological/adversarial inputs from
+/// running unbounded work.
+pub const RSLSF_MAX_BYTES_SCANNED: u64 = 1 << 20;
+
+// ----------------------------------------------------------------------------
+// Error type
+// ----------------------------------------------------------------------------
+
+/// Production-safe error type for
+/// [`read_single_line_string_field_from_source_no_heap`].
+///
+/// # Design
+///
+/// * All variants are zero-sized: no heap, no `String`, no embedded path,
+///   no embedded OS error. This is a deliberate defensive choice — error
+///   values must never become an information-disclosure vector.
+/// * Every variant carries the unique prefix `RsLsf` (Read Single Line
+///   String Field) so it is unambiguously traceable in logs to this
+///   function, satisfying the "unique error per function" rule.
+// The shared `RsLsf` prefix is the point (see Design above), not an accident.
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadTomlFieldError {
+    /// RSLSF: caller-supplied key was empty.
+    RsLsfEmptyKey,
+    /// RSLSF: caller-supplied key would not fit in the `MAX_LINE_BYTES`
+    /// line scan buffer.
+    RsLsfKeyTooLong,
+    /// RSLSF: caller-supplied `OUTPUT_BUFFER_BYTES` const-generic was zero.
+    RsLsfOutputBufferZeroSized,
+    /// RSLSF: the file could not be opened (does not exist, permission, etc.).
+    RsLsfFileOpenFailed,
+    /// RSLSF: an I/O error occurred while reading, or a byte source failed
+    /// (or claimed more bytes than its chunk holds).
+    RsLsfFileReadFailed,
+    /// RSLSF: the requested key was not present in the file.
+    RsLsfFieldNotFound,
+    /// RSLSF: the matched value would not fit in `OUTPUT_BUFFER_BYTES`.
+    RsLsfValueExceedsOutputBuffer,
+    /// RSLSF: a line whose leading bytes matched the requested key exceeded
+    /// `MAX_LINE_BYTES`; refusing to silently truncate.
+    RsLsfMatchingLineExceedsScanBuffer,
+    /// RSLSF: the failsafe byte/iteration budget was exhausted.
+    RsLsfSafetyBudgetExhausted,
+    /// RSLSF: the value is quoted or is not a TOML integer.
+    RsLsfValueNotInteger,
+    /// RSLSF: the integer does not fit the requested type or the caller's
+    /// allowed range.
+    RsLsfIntegerOutOfRange,
+    /// RSLSF: the value is not an unquoted `true` or `false`.
+    RsLsfValueNotBoolean,
+    /// RSLSF: the value is not a single-line array of integers.
+    RsLsfValueNotIntegerArray,
+    /// RSLSF: the array has more elements than `ARRAY_CAPACITY`; refusing
+    /// to silently truncate.
+    RsLsfArrayExceedsCapacity,
+    /// RSLSF: a `"basic"` string holds a backslash escape TOML does not
+    /// define (e.g. `\q`, a short `\u` or a surrogate code point).
+    RsLsfInvalidEscapeSequence,
+    /// RSLSF: a quoted value has no closing quote on its line, or text
+    /// other than a `#` comment after it (multi-line strings included).
+    RsLsfMalformedString,
+}
+
+// ----------------------------------------------------------------------------
+// Spans
+// ----------------------------------------------------------------------------
+
+/// Where a value (or a problem) sits in the TOML text, as returned by
+/// [`read_single_line_string_field_from_source_no_heap_with_span`].
+///
+/// `column` and the byte range count BYTES, not characters: the reader
+/// never decodes the text. Displayed as `path:line:column`.
+///
+/// `file_path` is the caller's own path (or source name) argument,
+/// borrowed; the span never holds file contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RsLsfSpan<'path> {
+    pub file_path: &'path str,
+    /// 1-based line number.
+    pub line_number: u64,
+    /// 1-based byte column of `byte_start` within its line.
+    pub column: u64,
+    /// Offset of the first byte from the start of the source.
+    pub byte_start: u64,
+    /// Offset just past the last byte.
+    pub byte_end: u64,
+}
+
+impl core::fmt::Display for RsLsfSpan<'_> {
+    fn fmt(&self, formatter: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(formatter, "{}:{}:{}", self.file_path, self.line_number, self.column)
+    }
+}
+
+/// A [`ReadTomlFieldError`] together with the place in the file it is
+/// about, when there is one (see
+/// [`read_single_line_string_field_from_source_no_heap_with_span`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RsLsfSpannedError<'path> {
+    pub error: ReadTomlFieldError,
+    pub span: Option<RsLsfSpan<'path>>,
+}
+
+// ----------------------------------------------------------------------------
+// Multi-key results
+// ----------------------------------------------------------------------------
+
+/// What a single-pass scan found for one key, see
+/// [`read_single_line_string_fields_from_source_no_heap`].
+///
+/// The outcomes other than `Found` and `NotFound` mirror the single-key
+/// errors of the same name: the key's first line was found but its value
+/// cannot be returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RsLsfFieldStatus {
+    /// The value is in the slot's buffer.
+    Found,
+    /// No line in the file holds the key.
+    NotFound,
+    /// The value would not fit in `OUTPUT_BUFFER_BYTES`.
+    ValueExceedsOutputBuffer,
+    /// The key's line (or, for a table path, a `[table]` header before
+    /// it) exceeded `MAX_LINE_BYTES`; refusing to silently truncate.
+    MatchingLineExceedsScanBuffer,
+    /// The value is a string with an invalid escape sequence.
+    InvalidEscapeSequence,
+    /// The value is a quoted string that is not closed on its line, or is
+    /// followed by something other than a comment.
+    MalformedString,
+}
+
+/// One output slot of [`read_single_line_string_fields_from_source_no_heap`]:
+/// the status for the key at the same index, and its value when found.
+/// Bytes past `written_length` are zero.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RsLsfFieldSlot<const OUTPUT_BUFFER_BYTES: usize> {
+    pub status: RsLsfFieldStatus,
+    pub output_buffer: [u8; OUTPUT_BUFFER_BYTES],
+    pub written_length: usize,
+}
+
+// ----------------------------------------------------------------------------
+// Public API
+// ----------------------------------------------------------------------------
+
+/// Reads a single single-line string field, top-level or in a table, from
+/// the TOML text of any [`RsLsfByteSource`] using only stack-allocated
+/// memory (buffer size set by call to function). The source is read once,
+/// from where it stands, until the key is settled or the source ends.
+///
+/// e.g.
+/// read_single_line_string_field_from_source_no_heap::<8, 256, 512, { 1 << 20 }>(&mut source, "x")?;
+/// read_single_line_string_field_from_source_no_heap::<32, 64, 128, 4096>(&mut source, "x")?;
+///
+/// # Type Parameters
+/// All four are chosen by the caller at the call site, at compile time;
+/// this module has no defaults and no hardcoded sizes.
+/// * `OUTPUT_BUFFER_BYTES` — the fixed size of the returned byte buffer,
+///   and so the longest value this call accepts. Must be `> 0`. Pick the
+///   smallest value that fits the longest legitimate value (e.g. `16` for
+///   a short identifier); longer values produce
+///   `RsLsfValueExceedsOutputBuffer` and are never silently truncated.
+/// * `READ_CHUNK_BYTES` — stack chunk each `read_chunk` call fills
+///   (recommended: [`RSLSF_READ_CHUNK_BYTES`]).
+/// * `MAX_LINE_BYTES` — stack line accumulator; longer lines that could
+///   hold the key are errors (recommended: [`RSLSF_MAX_LINE_BYTES`]).
+/// * `MAX_BYTES_SCANNED` — failsafe byte budget for the whole scan: at
+///   most one byte past it is read, and none past it is scanned, however
+///   the source splits its reads (recommended: [`RSLSF_MAX_BYTES_SCANNED`]).
+///
+/// The three scan parameters are checked at compile time: each must be
+/// non-zero, and neither buffer may be larger than the byte budget.
+///
+/// # Arguments
+/// * `byte_source` — where the TOML text comes from: an
+///   [`RsLsfSliceSource`] over bytes in memory, a flash region, or a file
+///   through the std adapter.
+/// * `target_field_key` — the exact key to find: `node_id` for a top-level
+///   key, `network.node_id` for one in the `[network]` table. Must be non-empty
+///   and shorter than `MAX_LINE_BYTES`.
+///
+/// # Returns
+/// * `Ok((output_buffer, written_length))` on success. `output_buffer` is
+///   `[u8; OUTPUT_BUFFER_BYTES]`, and `written_length` bytes of it are
+///   meaningful. Bytes past `written_length` are zero.
+/// * `Err(ReadTomlFieldError)` on any failure; never panics, never allocates.
+///
+/// # Example (illustrative)
+/// ```ignore
+/// // e.g. a TOML file linked into a firmware image.
+/// let mut config_source = RsLsfSliceSource::new(include_bytes!("config.toml"));
+/// match read_single_line_string_field_from_source_no_heap::<
+///     16,
+///     RSLSF_READ_CHUNK_BYTES,
+///     RSLSF_MAX_LINE_BYTES,
+///     RSLSF_MAX_BYTES_SCANNED,
+/// >(
+///     &mut config_source,
+///     "node_id",
+/// ) {
+///     Ok((buf, len)) => {
+///         // Caller decides whether to validate UTF-8.
+///         if let Ok(s) = core::str::from_utf8(&buf[..len]) {
+///             // use s
+///         }
+///     }
+///     Err(_e) => {
+///         // Production: log a unique short code, do NOT log contents.
+///         // Continue with safe default; do not panic.
+///     }
+/// }
+/// ```
+///
+/// # Stack footprint
+/// About `OUTPUT_BUFFER_BYTES + READ_CHUNK_BYTES + MAX_LINE_BYTES` bytes
+/// for the duration of the call, all chosen at the call site.
+pub fn read_single_line_string_field_from_source_no_heap<
+    const OUTPUT_BUFFER_BYTES: usize,
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    byte_source: &mut impl RsLsfByteSource,
+    target_field_key: &str,
+) -> Result<([u8; OUTPUT_BUFFER_BYTES], usize), ReadTomlFieldError> {
+    match read_single_line_string_field_from_source_no_heap_with_span::<
+        OUTPUT_BUFFER_BYTES,
+        READ_CHUNK_BYTES,
+        MAX_LINE_BYTES,
+        MAX_BYTES_SCANNED,
+    >(
+        byte_source,
+        "",
+        target_field_key,
+    ) {
+        Ok((output_buffer, written_length, _value_span)) => Ok((output_buffer, written_length)),
+        Err(spanned_error) => Err(spanned_error.error),
+    }
+}
+
+/// Same as [`read_single_line_string_field_from_source_no_heap`], but also
+/// reports WHERE in the text the value (or the problem) is, so an operator
+/// can be pointed at `config.toml:4:13`. `source_name` only labels the
+/// spans (a file path, or e.g. `"<flash>"`); it is never opened.
+///
+/// # Returns
+/// * `Ok((output_buffer, written_length, value_span))` — as the plain
+///   reader, plus the [`RsLsfSpan`] of the value as written (quotes
+///   included).
+/// * `Err(RsLsfSpannedError)` — the same terse [`ReadTomlFieldError`],
+///   plus a span when the error is about a specific place in the text:
+///   the value for `RsLsfValueExceedsOutputBuffer`, the escape for
+///   `RsLsfInvalidEscapeSequence`, the rest of the line for
+///   `RsLsfMalformedString`, the key's line for
+///   `RsLsfMatchingLineExceedsScanBuffer`. All other errors have no span.
+///
+/// The span holds only the caller's own source name and numbers; it never
+/// holds file contents. Rendering the offending line is a separate,
+/// explicit step (the std adapter's caret diagnostic).
+pub fn read_single_line_string_field_from_source_no_heap_with_span<
+    'path,
+    const OUTPUT_BUFFER_BYTES: usize,
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    byte_source: &mut impl RsLsfByteSource,
+    source_name: &'path str,
+    target_field_key: &str,
+) -> Result<([u8; OUTPUT_BUFFER_BYTES], usize, RsLsfSpan<'path>), RsLsfSpannedError<'path>> {
+    // Errors reported before any byte of the source is seen carry no span.
+    let without_span = |error: ReadTomlFieldError| RsLsfSpannedError { error, span: None };
+
+    // ----------------------------------------------------------------------
+    // Debug-Assert / Test-Assert / Production-Catch
+    // ----------------------------------------------------------------------
+    // Debug-only assertion: panics only in non-test debug builds, so
+    // developers see violated preconditions during local iteration.
+    #[cfg(all(debug_assertions, not(test)))]
+    {
+        debug_assert!(
+            OUTPUT_BUFFER_BYTES > 0,
+            "RSLSF: OUTPUT_BUFFER_BYTES must be > 0",
+        );
+        debug_assert!(
+            !target_field_key.is_empty(),
+            "RSLSF: target_field_key must not be empty",
+        );
+    }
+
+    // Production catch-handles: never panic; convert violations into errors.
+    if OUTPUT_BUFFER_BYTES == 0 {
+        return Err(without_span(ReadTomlFieldError::RsLsfOutputBufferZeroSized));
+    }
+    if target_field_key.is_empty() {
+        return Err(without_span(ReadTomlFieldError::RsLsfEmptyKey));
+    }
+    if target_field_key.len() >= MAX_LINE_BYTES {
+        return Err(without_span(ReadTomlFieldError::RsLsfKeyTooLong));
+    }
+
+    // ----------------------------------------------------------------------
+    // Scan: the first line that settles the key ends the scan
+    // ----------------------------------------------------------------------
+    let mut table_key_start: Option<usize> = Some(0);
+    let scan_outcome = scan_toml_source_lines::<READ_CHUNK_BYTES, MAX_LINE_BYTES, MAX_BYTES_SCANNED, _, _>(
+        byte_source,
+        source_name,
+        |line_bytes, line_overflowed_buffer, line_position| {
+            match finish_line::<OUTPUT_BUFFER_BYTES>(
+                line_bytes,
+                line_overflowed_buffer,
+                target_field_key,
+                &mut table_key_start,
+                line_position,
+            ) {
+                Ok(None) => ControlFlow::Continue(()),
+                Ok(Some(found_value_tuple)) => ControlFlow::Break(Ok(found_value_tuple)),
+                Err(spanned_error) => ControlFlow::Break(Err(spanned_error)),
+            }
+        },
+    );
+    match scan_outcome {
+        Ok(Some(settled_result)) => settled_result,
+        Ok(None) => Err(without_span(ReadTomlFieldError::RsLsfFieldNotFound)),
+        Err(scan_error) => Err(without_span(scan_error)),
+    }
+}
+
+/// Reads several single-line string fields in ONE pass over the source,
+/// using only stack-allocated memory: loading eight startup values costs
+/// one scan under `MAX_BYTES_SCANNED`, not eight.
+///
+/// e.g.
+/// let slots = read_single_line_string_fields_from_source_no_heap::<3, 16, 256, 512, { 1 << 20 }>(
+///     &mut source,
+///     &["node_id", "mode", "network.bind_port"],
+/// )?;
+///
+/// # Type Parameters
+/// * `KEY_COUNT` — number of keys, inferred from the key array.
+/// * `OUTPUT_BUFFER_BYTES` — the size of EACH slot's value buffer, as for
+///   [`read_single_line_string_field_from_source_no_heap`]. The result takes
+///   `KEY_COUNT * OUTPUT_BUFFER_BYTES` bytes of stack (plus small fields).
+/// * `READ_CHUNK_BYTES`, `MAX_LINE_BYTES`, `MAX_BYTES_SCANNED` — the scan
+///   buffers and budget, as for the single-key reader; the budget covers
+///   the one pass for all keys.
+///
+/// # Arguments
+/// * `byte_source` — where the TOML text comes from, as for the single-key
+///   reader.
+/// * `target_field_keys` — the keys to find, each following the rules of
+///   the single-key reader (top-level key or table path, non-empty,
+///   shorter than `MAX_LINE_BYTES`).
+///
+/// # Returns
+/// * `Ok(slots)` — one [`RsLsfFieldSlot`] per key, in the same order. Each
+///   key is settled by the first line that holds it, exactly as the
+///   single-key reader would (found, or an overflow status); keys never
+///   seen are `NotFound`. The scan stops early once every key is settled.
+/// * `Err(ReadTomlFieldError)` — only for problems with the whole call: an
+///   invalid key (`RsLsfEmptyKey`, `RsLsfKeyTooLong`), a zero-sized
+///   buffer, source/safety-budget failures, or an error from a key's line
+///   that no [`RsLsfFieldStatus`] stands for, with its own kind. Never
+///   panics, never allocates.
+pub fn read_single_line_string_fields_from_source_no_heap<
+    const KEY_COUNT: usize,
+    const OUTPUT_BUFFER_BYTES: usize,
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    byte_source: &mut impl RsLsfByteSource,
+    target_field_keys: &[&str; KEY_COUNT],
+) -> Result<[RsLsfFieldSlot<OUTPUT_BUFFER_BYTES>; KEY_COUNT], ReadTomlFieldError> {
+    // ----------------------------------------------------------------------
+    // Production catch-handles: never panic; convert violations into errors.
+    // ----------------------------------------------------------------------
+    if OUTPUT_BUFFER_BYTES == 0 {
+        return Err(ReadTomlFieldError::RsLsfOutputBufferZeroSized);
+    }
+    let mut key_index: usize = 0;
+    while key_index < KEY_COUNT {
+        if target_field_keys[key_index].is_empty() {
+            return Err(ReadTomlFieldError::RsLsfEmptyKey);
+        }
+        if target_field_keys[key_index].len() >= MAX_LINE_BYTES {
+            return Err(ReadTomlFieldError::RsLsfKeyTooLong);
+        }
+        key_index += 1;
+    }
+
+    let mut field_slots: [RsLsfFieldSlot<OUTPUT_BUFFER_BYTES>; KEY_COUNT] = [RsLsfFieldSlot {
+        status: RsLsfFieldStatus::NotFound,
+        output_buffer: [0u8; OUTPUT_BUFFER_BYTES],
+        written_length: 0,
+    }; KEY_COUNT];
+    if KEY_COUNT == 0 {
+        return Ok(field_slots);
+    }
+
+    // ----------------------------------------------------------------------
+    // Per-key scan state (fixed arrays, no heap)
+    // ----------------------------------------------------------------------
+    let mut table_key_starts: [Option<usize>; KEY_COUNT] = [Some(0); KEY_COUNT];
+    let mut key_settled: [bool; KEY_COUNT] = [false; KEY_COUNT];
+    let mut settled_key_count: usize = 0;
+
+    // Each line is offered to every unsettled key: bounded by KEY_COUNT.
+    let scan_outcome = scan_toml_source_lines::<READ_CHUNK_BYTES, MAX_LINE_BYTES, MAX_BYTES_SCANNED, _, _>(
+        byte_source,
+        "",
+        |line_bytes, line_overflowed_buffer, line_position| {
+            let mut key_index: usize = 0;
+            while key_index < KEY_COUNT {
+                if !key_settled[key_index] {
+                    let settled_status: Option<RsLsfFieldStatus> = match finish_line::<OUTPUT_BUFFER_BYTES>(
+                        line_bytes,
+                        line_overflowed_buffer,
+                        target_field_keys[key_index],
+                        &mut table_key_starts[key_index],
+                        line_position,
+                    ) {
+                        Ok(None) => None,
+                        Ok(Some((output_buffer, written_length, _value_span))) => {
+                            field_slots[key_index].output_buffer = output_buffer;
+                            field_slots[key_index].written_length = written_length;
+                            Some(RsLsfFieldStatus::Found)
+                        }
+                        Err(spanned_error) => match field_status_for_line_error(spanned_error.error) {
+                            Some(status) => Some(status),
+                            None => return ControlFlow::Break(Err(spanned_error.error)),
+                        },
+                    };
+                    if let Some(status) = settled_status {
+                        field_slots[key_index].status = status;
+                        key_settled[key_index] = true;
+                        settled_key_count += 1;
+                    }
+                }
+                key_index += 1;
+            }
+            if settled_key_count == KEY_COUNT {
+                ControlFlow::Break(Ok(()))
+            } else {
+                ControlFlow::Continue(())
+            }
+        },
+    );
+
+    match scan_outcome {
+        Ok(Some(Err(line_error))) => Err(line_error),
+        Ok(_) => Ok(field_slots),
+        Err(scan_error) => Err(scan_error),
+    }
+}
+
+/// The slot status for an error that settled one key of the multi-key
+/// reader, or `None` for an error no status stands for, which then fails
+/// the whole call with its own kind.
+fn field_status_for_line_error(line_error: ReadTomlFieldError) -> Option<RsLsfFieldStatus> {
+    match line_error {
+        ReadTomlFieldError::RsLsfFieldNotFound => Some(RsLsfFieldStatus::NotFound),
+        ReadTomlFieldError::RsLsfValueExceedsOutputBuffer => Some(RsLsfFieldStatus::ValueExceedsOutputBuffer),
+        ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer => {
+            Some(RsLsfFieldStatus::MatchingLineExceedsScanBuffer)
+        }
+        ReadTomlFieldError::RsLsfInvalidEscapeSequence => Some(RsLsfFieldStatus::InvalidEscapeSequence),
+        ReadTomlFieldError::RsLsfMalformedString => Some(RsLsfFieldStatus::MalformedString),
+        ReadTomlFieldError::RsLsfEmptyKey
+        | ReadTomlFieldError::RsLsfKeyTooLong
+        | ReadTomlFieldError::RsLsfOutputBufferZeroSized
+        | ReadTomlFieldError::RsLsfFileOpenFailed
+        | ReadTomlFieldError::RsLsfFileReadFailed
+        | ReadTomlFieldError::RsLsfSafetyBudgetExhausted
+        | ReadTomlFieldError::RsLsfValueNotInteger
+        | ReadTomlFieldError::RsLsfIntegerOutOfRange
+        | ReadTomlFieldError::RsLsfValueNotBoolean
+        | ReadTomlFieldError::RsLsfValueNotIntegerArray
+        | ReadTomlFieldError::RsLsfArrayExceedsCapacity => None,
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Typed companions (integers, booleans, integer arrays)
+// ----------------------------------------------------------------------------
+//
+// Each typed reader finds the value exactly like the string reader, then
+// parses the bytes as written, on the stack. A quoted value is a TOML
+// string, so `port = "8080"` is NOT an integer here. The raw value is
+// held in a `[u8; MAX_LINE_BYTES]` buffer (a value never outgrows its
+// line), so the stack footprint is about `READ_CHUNK_BYTES + 2 *
+// MAX_LINE_BYTES` bytes plus the result.
+
+/// Reads a non-negative TOML integer into a `u64`, and checks it against
+/// `allowed_range` (e.g. `1..=65535` for a port; `0..=u64::MAX` for any).
+///
+/// Accepts decimal (`8080`, `+8080`, `1_000`) and prefixed hex, octal or
+/// binary (`0xff`, `0o755`, `0b1010`), as TOML writes them. Values up to
+/// `u64::MAX` are accepted even though TOML itself stops at `i64::MAX`.
+///
+/// # Returns
+/// * `Ok(value)` — the value, inside `allowed_range`.
+/// * `Err(RsLsfValueNotInteger)` — the value is quoted or not an integer.
+/// * `Err(RsLsfIntegerOutOfRange)` — negative, too large for `u64`, or
+///   outside `allowed_range`.
+/// * Any error of [`read_single_line_string_field_from_source_no_heap`].
+pub fn read_u64_field_from_source_no_heap<
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    byte_source: &mut impl RsLsfByteSource,
+    target_field_key: &str,
+    allowed_range: RangeInclusive<u64>,
+) -> Result<u64, ReadTomlFieldError> {
+    let (value_buffer, value_length) = read_unquoted_value_no_heap::<
+        READ_CHUNK_BYTES,
+        MAX_LINE_BYTES,
+        MAX_BYTES_SCANNED,
+    >(byte_source, target_field_key, ReadTomlFieldError::RsLsfValueNotInteger)?;
+
+    let (is_negative, magnitude) = parse_toml_integer_bytes(&value_buffer[..value_length])?;
+    // `-0` is zero; any other negative value is below `u64::MIN`.
+    if is_negative && magnitude != 0 {
+        return Err(ReadTomlFieldError::RsLsfIntegerOutOfRange);
+    }
+    if !allowed_range.contains(&magnitude) {
+        return Err(ReadTomlFieldError::RsLsfIntegerOutOfRange);
+    }
+    Ok(magnitude)
+}
+
+/// Reads a TOML integer into an `i64`, and checks it against
+/// `allowed_range` (`i64::MIN..=i64::MAX` for any).
+///
+/// Accepts the same spellings as [`read_u64_field_from_source_no_heap`];
+/// a sign is only allowed on decimal values.
+///
+/// # Returns
+/// * `Ok(value)` — the value, inside `allowed_range`.
+/// * `Err(RsLsfValueNotInteger)` — the value is quoted or not an integer.
+/// * `Err(RsLsfIntegerOutOfRange)` — too large for `i64`, or outside
+///   `allowed_range`.
+/// * Any error of [`read_single_line_string_field_from_source_no_heap`].
+pub fn read_i64_field_from_source_no_heap<
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    byte_source: &mut impl RsLsfByteSource,
+    target_field_key: &str,
+    allowed_range: RangeInclusive<i64>,
+) -> Result<i64, ReadTomlFieldError> {
+    let (value_buffer, value_length) = read_unquoted_value_no_heap::<
+        READ_CHUNK_BYTES,
+        MAX_LINE_BYTES,
+        MAX_BYTES_SCANNED,
+    >(byte_source, target_field_key, ReadTomlFieldError::RsLsfValueNotInteger)?;
+
+    let (is_negative, magnitude) = parse_toml_integer_bytes(&value_buffer[..value_length])?;
+    let value: i64 = signed_integer_from_magnitude(is_negative, magnitude)?;
+    if !allowed_range.contains(&value) {
+        return Err(ReadTomlFieldError::RsLsfIntegerOutOfRange);
+    }
+    Ok(value)
+}
+
+/// Reads a TOML boolean: exactly `true` or `false`, unquoted.
+///
+/// # Returns
+/// * `Ok(value)` on success.
+/// * `Err(RsLsfValueNotBoolean)` — anything else, including `"true"`,
+///   `True` or `1`.
+/// * Any error of [`read_single_line_string_field_from_source_no_heap`].
+pub fn read_bool_field_from_source_no_heap<
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    byte_source: &mut impl RsLsfByteSource,
+    target_field_key: &str,
+) -> Result<bool, ReadTomlFieldError> {
+    let (value_buffer, value_length) = read_unquoted_value_no_heap::<
+        READ_CHUNK_BYTES,
+        MAX_LINE_BYTES,
+        MAX_BYTES_SCANNED,
+    >(byte_source, target_field_key, ReadTomlFieldError::RsLsfValueNotBoolean)?;
+
+    match &value_buffer[..value_length] {
+        b"true" => Ok(true),
+        b"false" => Ok(false),
+        _ => Err(ReadTomlFieldError::RsLsfValueNotBoolean),
+    }
+}
+
+/// Reads a single-line TOML array of integers, e.g. `ports = [80, 443]`,
+/// into a fixed-capacity stack array.
+///
+/// Whitespace around elements and one trailing comma are allowed; `[]`
+/// gives a count of zero. Elements use the integer spellings of
+/// [`read_i64_field_from_source_no_heap`].
+///
+/// # Type Parameters
+/// * `ARRAY_CAPACITY` — the most elements this call accepts.
+/// * The scan parameters, as for the string reader.
+///
+/// # Returns
+/// * `Ok((elements, count))` — the first `count` elements are the array,
+///   in order; the rest are zero.
+/// * `Err(RsLsfValueNotIntegerArray)` — not an array, or an element that
+///   is not an integer (strings, floats, nested arrays, ...).
+/// * `Err(RsLsfIntegerOutOfRange)` — an element too large for `i64`.
+/// * `Err(RsLsfArrayExceedsCapacity)` — more than `ARRAY_CAPACITY`
+///   elements; never silently truncated.
+/// * Any error of [`read_single_line_string_field_from_source_no_heap`].
+pub fn read_i64_array_field_from_source_no_heap<
+    const ARRAY_CAPACITY: usize,
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    byte_source: &mut impl RsLsfByteSource,
+    target_field_key: &str,
+) -> Result<([i64; ARRAY_CAPACITY], usize), ReadTomlFieldError> {
+    let (value_buffer, value_length) = read_unquoted_value_no_heap::<
+        READ_CHUNK_BYTES,
+        MAX_LINE_BYTES,
+        MAX_BYTES_SCANNED,
+    >(byte_source, target_field_key, ReadTomlFieldError::RsLsfValueNotIntegerArray)?;
+
+    let value_bytes: &[u8] = &value_buffer[..value_length];
+    if value_bytes.len() < 2 || value_bytes[0] != b'[' || value_bytes[value_bytes.len() - 1] != b']' {
+        return Err(ReadTomlFieldError::RsLsfValueNotIntegerArray);
+    }
+    let element_list_bytes: &[u8] = trim_ascii_whitespace(&value_bytes[1..value_bytes.len() - 1]);
+
+    let mut elements: [i64; ARRAY_CAPACITY] = [0i64; ARRAY_CAPACITY];
+    let mut element_count: usize = 0;
+    let mut element_start: usize = 0;
+    // Each pass consumes one element and its comma: bounded by the value length.
+    while element_start < element_list_bytes.len() {
+        let element_end: usize = match find_byte(&element_list_bytes[element_start..], b',') {
+            Some(comma_offset) => element_start + comma_offset,
+            None => element_list_bytes.len(),
+        };
+        let element_bytes: &[u8] = trim_ascii_whitespace(&element_list_bytes[element_start..element_end]);
+        let (is_negative, magnitude) = match parse_toml_integer_bytes(element_bytes) {
+            Ok(parsed_integer) => parsed_integer,
+            Err(ReadTomlFieldError::RsLsfValueNotInteger) => {
+                return Err(ReadTomlFieldError::RsLsfValueNotIntegerArray);
+            }
+            Err(other_error) => return Err(other_error),
+        };
+        if element_count == ARRAY_CAPACITY {
+            return Err(ReadTomlFieldError::RsLsfArrayExceedsCapacity);
+        }
+        elements[element_count] = signed_integer_from_magnitude(is_negative, magnitude)?;
+        element_count += 1;
+
+        // Past the comma. The list is trimmed, so a comma ending it is the
+        // one allowed trailing comma and simply ends the loop.
+        element_start = element_end + 1;
+    }
+    Ok((elements, element_count))
+}
+
+/// The value of `target_field_key` as written, for the typed readers:
+/// quotes are kept out of the result by rejecting quoted values with
+/// `quoted_value_error`, since a quoted value is a TOML string.
+fn read_unquoted_value_no_heap<
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    byte_source: &mut impl RsLsfByteSource,
+    target_field_key: &str,
+    quoted_value_error: ReadTomlFieldError,
+) -> Result<([u8; MAX_LINE_BYTES], usize), ReadTomlFieldError> {
+    let (value_buffer, value_length, value_span) = match read_single_line_string_field_from_source_no_heap_with_span::<
+        MAX_LINE_BYTES,
+        READ_CHUNK_BYTES,
+        MAX_LINE_BYTES,
+        MAX_BYTES_SCANNED,
+    >(byte_source, "", target_field_key)
+    {
+        Ok(found_value) => found_value,
+        Err(spanned_error) => return Err(spanned_error.error),
+    };
+    // The span covers the value as written: longer than the result only
+    // for a string, whose quotes were stripped (escapes only shrink it).
+    if value_span.byte_end - value_span.byte_start != value_length as u64 {
+        return Err(quoted_value_error);
+    }
+    Ok((value_buffer, value_length))
+}
+
+// ----------------------------------------------------------------------------
+// Line scanner (shared by the single- and multi-key readers)
+// ----------------------------------------------------------------------------
+
+/// Reads `byte_source` once, in stack-sized chunks, and hands each
+/// completed line (CR of a CRLF dropped) to `line_handler`, until the
+/// handler returns `ControlFlow::Break` or the source ends. Spans name the
+/// source `source_name`.
+///
+/// A line longer than `MAX_LINE_BYTES` is handed over as its kept
+/// prefix with the `line_overflowed_buffer` flag set; the handler decides
+/// whether that matters.
+///
+/// Returns `Ok(Some(value))` for the handler's `Break(value)`, `Ok(None)`
+/// at end of input, or the terse source/budget error. The closure is a
+/// generic parameter, so nothing is boxed or allocated.
+fn scan_toml_source_lines<
+    'path,
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+    SettledValue,
+    LineHandler,
+>(
+    byte_source: &mut impl RsLsfByteSource,
+    source_name: &'path str,
+    mut line_handler: LineHandler,
+) -> Result<Option<SettledValue>, ReadTomlFieldError>
+where
+    LineHandler: FnMut(&[u8], bool, &RsLsfLinePosition<'path>) -> ControlFlow<SettledValue>,
+{
+    // ----------------------------------------------------------------------
+    // Compile-time checks of the caller's scan parameters: evaluated for
+    // each combination a call site uses, so a bad one is a build error,
+    // never a runtime surprise.
+    // ----------------------------------------------------------------------
+    const {
+        assert!(READ_CHUNK_BYTES > 0, "RSLSF: READ_CHUNK_BYTES must be > 0");
+        assert!(MAX_LINE_BYTES > 0, "RSLSF: MAX_LINE_BYTES must be > 0");
+        assert!(MAX_BYTES_SCANNED > 0, "RSLSF: MAX_BYTES_SCANNED must be > 0");
+        assert!(
+            READ_CHUNK_BYTES as u64 <= MAX_BYTES_SCANNED,
+            "RSLSF: READ_CHUNK_BYTES must not exceed MAX_BYTES_SCANNED",
+        );
+        assert!(
+            MAX_LINE_BYTES as u64 <= MAX_BYTES_SCANNED,
+            "RSLSF: MAX_LINE_BYTES must not exceed MAX_BYTES_SCANNED",
+        );
+    }
+
+    // ----------------------------------------------------------------------
+    // Stack-allocated scratch buffers
+    // ----------------------------------------------------------------------
+    let mut chunk_read_buffer: [u8; READ_CHUNK_BYTES] = [0u8; READ_CHUNK_BYTES];
+    let mut current_line_buffer: [u8; MAX_LINE_BYTES] = [0u8; MAX_LINE_BYTES];
+    let mut current_line_length: usize = 0;
+    let mut current_line_overflowed_buffer: bool = false;
+    let mut cumulative_bytes_scanned: u64 = 0;
+
+    // Position bookkeeping for spans. Every byte of a line (bare CRs
+    // included) is kept in the line buffer, so index `i` of the buffer is
+    // file offset `current_line_start_offset + i`.
+    let mut current_line_number: u64 = 1;
+    let mut current_line_start_offset: u64 = 0;
+    // A CR is held back until the next byte shows whether it ends a CRLF.
+    let mut carriage_return_pending: bool = false;
+
+    // Iteration failsafe: a source may legitimately return short reads
+    // (down to 1 byte, e.g. a pipe or a byte-at-a-time flash driver), so
+    // allow one iteration per budgeted byte. (Belt-and-suspenders with the
+    // byte cap.)
+    let mut safety_iteration_count: u64 = 0;
+    let safety_iteration_limit: u64 = MAX_BYTES_SCANNED.saturating_add(16);
+
+    // ----------------------------------------------------------------------
+    // Read loop
+    // ----------------------------------------------------------------------
+    loop {
+        safety_iteration_count = safety_iteration_count.saturating_add(1);
+        if safety_iteration_count > safety_iteration_limit {
+            return Err(ReadTomlFieldError::RsLsfSafetyBudgetExhausted);
+        }
+
+        // Never ask for more than one byte past the budget, so the answer
+        // does not depend on how the source splits its reads. A source
+        // claiming more bytes than it was asked for is broken.
+        let remaining_budget: u64 = MAX_BYTES_SCANNED - cumulative_bytes_scanned;
+        let requested_length: usize = if remaining_budget < READ_CHUNK_BYTES as u64 {
+            remaining_budget as usize + 1
+        } else {
+            READ_CHUNK_BYTES
+        };
+        let bytes_read_this_chunk: usize = match byte_source.read_chunk(&mut chunk_read_buffer[..requested_length]) {
+            Ok(count) if count <= requested_length => count,
+            Ok(_) => return Err(ReadTomlFieldError::RsLsfFileReadFailed),
+            Err(source_error) => return Err(source_error),
+        };
+
+        // ------------------------------------------------------------------
+        // End-of-file: process any final unterminated line, then report.
+        // A CR still pending here is a final line terminator; drop it.
+        // ------------------------------------------------------------------
+        if bytes_read_this_chunk == 0 {
+            let mut line_position = RsLsfLinePosition {
+                file_path: source_name,
+                line_number: current_line_number,
+                line_start_offset: current_line_start_offset,
+                line_end_offset: cumulative_bytes_scanned
+                    - u64::from(carriage_return_pending),
+            };
+            return match line_handler(
+                without_byte_order_mark(&current_line_buffer[..current_line_length], &mut line_position),
+                current_line_overflowed_buffer,
+                &line_position,
+            ) {
+                ControlFlow::Break(settled_value) => Ok(Some(settled_value)),
+                ControlFlow::Continue(()) => Ok(None),
+            };
+        }
+
+        // Only bytes within the budget are scanned; the byte past it means
+        // the budget is exhausted, unless a line within it settles first.
+        let chunk_start_offset: u64 = cumulative_bytes_scanned;
+        let budget_exhausted_in_chunk: bool = bytes_read_this_chunk as u64 > remaining_budget;
+        let bytes_to_scan_this_chunk: usize = if budget_exhausted_in_chunk {
+            remaining_budget as usize
+        } else {
+            bytes_read_this_chunk
+        };
+        cumulative_bytes_scanned += bytes_to_scan_this_chunk as u64;
+
+        // ------------------------------------------------------------------
+        // Byte-by-byte line accumulator. Bounded by `bytes_to_scan_this_chunk`
+        // (always <= READ_CHUNK_BYTES), so this inner loop is bounded.
+        // ------------------------------------------------------------------
+        let mut byte_index_in_chunk: usize = 0;
+        while byte_index_in_chunk < bytes_to_scan_this_chunk {
+            let current_byte: u8 = chunk_read_buffer[byte_index_in_chunk];
+            let current_byte_offset: u64 = chunk_start_offset + byte_index_in_chunk as u64;
+            byte_index_in_chunk += 1;
+
+            if current_byte == b'\n' {
+                // Drop the CR of a CRLF so CRLF and LF line endings are
+                // both handled.
+                let mut line_position = RsLsfLinePosition {
+                    file_path: source_name,
+                    line_number: current_line_number,
+                    line_start_offset: current_line_start_offset,
+                    line_end_offset: current_byte_offset - u64::from(carriage_return_pending),
+                };
+                carriage_return_pending = false;
+                if let ControlFlow::Break(settled_value) = line_handler(
+                    without_byte_order_mark(&current_line_buffer[..current_line_length], &mut line_position),
+                    current_line_overflowed_buffer,
+                    &line_position,
+                ) {
+                    return Ok(Some(settled_value));
+                }
+                current_line_length = 0;
+                current_line_overflowed_buffer = false;
+                current_line_number = current_line_number.saturating_add(1);
+                current_line_start_offset = current_byte_offset + 1;
+                continue;
+            }
+
+            // A CR not followed by LF (old Mac line endings) is not a
+            // supported terminator per the in-scope policy above; it is
+            // kept as an ordinary byte of the line.
+            if carriage_return_pending {
+                push_line_byte(
+                    &mut current_line_buffer,
+                    &mut current_line_length,
+                    &mut current_line_overflowed_buffer,
+                    b'\r',
+                );
+            }
+            carriage_return_pending = current_byte == b'\r';
+            if !carriage_return_pending {
+                push_line_byte(
+                    &mut current_line_buffer,
+                    &mut current_line_length,
+                    &mut current_line_overflowed_buffer,
+                    current_byte,
+                );
+            }
+        }
+
+        if budget_exhausted_in_chunk {
+            return Err(ReadTomlFieldError::RsLsfSafetyBudgetExhausted);
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Internal helpers (pure, stateless, no heap)
+// ----------------------------------------------------------------------------
+
+/// The UTF-8 byte order mark some editors write at the start of a file.
+const UTF8_BYTE_ORDER_MARK: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+/// Where a completed line sits in the file, for building spans.
+struct RsLsfLinePosition<'path> {
+    file_path: &'path str,
+    line_number: u64,
+    /// File offset of the first byte of the line.
+    line_start_offset: u64,
+    /// File offset just past the last byte of the line (terminator excluded).
+    line_end_offset: u64,
+}
+
+impl<'path> RsLsfLinePosition<'path> {
+    /// Span of bytes `start..end` of this line (indexes into the line).
+    fn span(&self, start_in_line: usize, end_in_line: usize) -> RsLsfSpan<'path> {
+        RsLsfSpan {
+            file_path: self.file_path,
+            line_number: self.line_number,
+            column: start_in_line as u64 + 1,
+            byte_start: self.line_start_offset + start_in_line as u64,
+            byte_end: self.line_start_offset + end_in_line as u64,
+        }
+    }
+}
+
+/// The first line without a leading UTF-8 byte order mark (`EF BB BF`),
+/// its position moved past the mark so spans still hit the right bytes.
+/// Any other line is returned unchanged.
+fn without_byte_order_mark<'line>(
+    line_bytes: &'line [u8],
+    line_position: &mut RsLsfLinePosition<'_>,
+) -> &'line [u8] {
+    if line_position.line_start_offset == 0 && line_bytes.starts_with(&UTF8_BYTE_ORDER_MARK) {
+        line_position.line_start_offset = UTF8_BYTE_ORDER_MARK.len() as u64;
+        &line_bytes[UTF8_BYTE_ORDER_MARK.len()..]
+    } else {
+        line_bytes
+    }
+}
+
+/// Appends one byte to the line accumulator, or marks it overflowed.
+fn push_line_byte<const MAX_LINE_BYTES: usize>(
+    current_line_buffer: &mut [u8; MAX_LINE_BYTES],
+    current_line_length: &mut usize,
+    current_line_overflowed_buffer: &mut bool,
+    current_byte: u8,
+) {
+    if *current_line_length < MAX_LINE_BYTES {
+        current_line_buffer[*current_line_length] = current_byte;
+        *current_line_length += 1;
+    } else {
+        // Overflow: stop accumulating but keep the prefix so
+        // we can decide at line end whether overflow matters.
+        *current_line_overflowed_buffer = true;
+    }
+}
+
+/// Decide what a completed line means for the search.
+///
+/// A `[table]` header line updates `table_key_start` (see the read loop)
+/// and never matches. Any other line is matched against the part of
+/// `target_field_key` still to be found under the current table.
+///
+/// Returns:
+/// * `Ok(Some(...))` — the line holds the key and the value fit.
+/// * `Ok(None)`      — the line is unrelated (skip and continue).
+/// * `Err(...)`      — the line holds the key but cannot be returned:
+///   it overflowed the scan buffer, its value will not fit in
+///   `OUTPUT_BUFFER_BYTES`, or it is a malformed string (see
+///   [`decode_value_in_line`]). Also a header line that overflowed before
+///   its `]` when the target is inside a table: which table follows is
+///   unknown, and guessing could return another table's value.
+fn finish_line<'path, const OUTPUT_BUFFER_BYTES: usize>(
+    accumulated_line_bytes: &[u8],
+    line_overflowed_buffer: bool,
+    target_field_key: &str,
+    table_key_start: &mut Option<usize>,
+    line_position: &RsLsfLinePosition<'path>,
+) -> Result<Option<([u8; OUTPUT_BUFFER_BYTES], usize, RsLsfSpan<'path>)>, RsLsfSpannedError<'path>> {
+    let target_key_bytes: &[u8] = target_field_key.as_bytes();
+
+    let trimmed_line_bytes: &[u8] = trim_ascii_whitespace(accumulated_line_bytes);
+    if !trimmed_line_bytes.is_empty() && trimmed_line_bytes[0] == b'[' {
+        *table_key_start = match header_table_key_start(trimmed_line_bytes, target_key_bytes) {
+            Some(header_key_start) => header_key_start,
+            None if line_overflowed_buffer && find_byte(target_key_bytes, b'.').is_some() => {
+                return Err(overflowed_line_error(accumulated_line_bytes, line_position));
+            }
+            // A malformed header, or any header for a top-level target:
+            // nothing below it can match.
+            None => None,
+        };
+        return Ok(None);
+    }
+
+    let remaining_key_bytes: &[u8] = match *table_key_start {
+        Some(key_start) => &target_key_bytes[key_start..],
+        None => return Ok(None),
+    };
+
+    if line_overflowed_buffer {
+        // If the overflowing line *could* have been our key, it is unsafe
+        // to silently skip it: report explicitly. Otherwise this unrelated
+        // long line cannot affect us; continue scanning the file. With the
+        // `=` inside the kept prefix the key is known exactly.
+        let could_be_target_line: bool = match find_byte(trimmed_line_bytes, b'=') {
+            Some(_) => locate_value_in_line(accumulated_line_bytes, remaining_key_bytes).is_some(),
+            None => line_prefix_could_match_key(accumulated_line_bytes, remaining_key_bytes),
+        };
+        if could_be_target_line {
+            return Err(overflowed_line_error(accumulated_line_bytes, line_position));
+        }
+        return Ok(None);
+    }
+
+    let (value_start_in_line, line_content_end) =
+        match locate_value_in_line(accumulated_line_bytes, remaining_key_bytes) {
+            Some(value_range) => value_range,
+            None => return Ok(None),
+        };
+    match decode_value_in_line::<OUTPUT_BUFFER_BYTES>(
+        accumulated_line_bytes,
+        value_start_in_line,
+        line_content_end,
+        line_position,
+    ) {
+        Ok(decoded_value_tuple) => Ok(Some(decoded_value_tuple)),
+        Err(spanned_error) => Err(spanned_error),
+    }
+}
+
+/// Decode the value that starts at `value_start_in_line` (the line's
+/// content ends at `line_content_end`) into a fresh output buffer:
+///
+/// * `"basic"` strings without their quotes, escapes decoded (`\n`,
+///   `\"`, `\u00e9`, ...; see [`decode_basic_string_escape`]);
+/// * `'literal'` strings without their quotes, verbatim;
+/// * anything else (numbers, booleans, bare words, one-line arrays)
+///   as written, up to an inline `#` comment, trailing blanks dropped.
+///
+/// After a string only blanks and a `#` comment may follow. The returned
+/// span covers the value as written (quotes included, comment excluded).
+/// Errors: `RsLsfValueExceedsOutputBuffer` (spanning the value),
+/// `RsLsfInvalidEscapeSequence` (spanning the escape) and
+/// `RsLsfMalformedString` (spanning the rest of the line).
+fn decode_value_in_line<'path, const OUTPUT_BUFFER_BYTES: usize>(
+    line_bytes: &[u8],
+    value_start_in_line: usize,
+    line_content_end: usize,
+    line_position: &RsLsfLinePosition<'path>,
+) -> Result<([u8; OUTPUT_BUFFER_BYTES], usize, RsLsfSpan<'path>), RsLsfSpannedError<'path>> {
+    let mut output_buffer: [u8; OUTPUT_BUFFER_BYTES] = [0u8; OUTPUT_BUFFER_BYTES];
+    let mut written_length: usize = 0;
+    // Set instead of returning at once, so the error can span the whole value.
+    let mut value_exceeds_output_buffer: bool = false;
+    let malformed_string_error = RsLsfSpannedError {
+        error: ReadTomlFieldError::RsLsfMalformedString,
+        span: Some(line_position.span(value_start_in_line, line_content_end)),
+    };
+
+    let opening_byte: u8 = if value_start_in_line < line_content_end {
+        line_bytes[value_start_in_line]
+    } else {
+        b' '
+    };
+    let value_end_in_line: usize = if opening_byte == b'"' || opening_byte == b'\'' {
+        // Each pass consumes at least one byte: bounded by the line length.
+        let mut cursor_position: usize = value_start_in_line + 1;
+        let mut closing_quote_index: Option<usize> = None;
+        while cursor_position < line_content_end {
+            let current_byte: u8 = line_bytes[cursor_position];
+            if current_byte == opening_byte {
+                closing_quote_index = Some(cursor_position);
+                break;
+            }
+            let mut decoded_bytes: [u8; 4] = [current_byte, 0, 0, 0];
+            let mut decoded_length: usize = 1;
+            let mut consumed_length: usize = 1;
+            if current_byte == b'\\' && opening_byte == b'"' {
+                match decode_basic_string_escape(&line_bytes[cursor_position..line_content_end]) {
+                    Ok((escape_length, escape_bytes, escape_bytes_length)) => {
+                        decoded_bytes = escape_bytes;
+                        decoded_length = escape_bytes_length;
+                        consumed_length = escape_length;
+                    }
+                    Err(invalid_escape_length) => {
+                        return Err(RsLsfSpannedError {
+                            error: ReadTomlFieldError::RsLsfInvalidEscapeSequence,
+                            span: Some(line_position.span(cursor_position, cursor_position + invalid_escape_length)),
+                        });
+                    }
+                }
+            }
+            if written_length + decoded_length > OUTPUT_BUFFER_BYTES {
+                value_exceeds_output_buffer = true;
+            } else {
+                output_buffer[written_length..written_length + decoded_length]
+                    .copy_from_slice(&decoded_bytes[..decoded_length]);
+                written_length += decoded_length;
+            }
+            cursor_position += consumed_length;
+        }
+
+        let closing_quote_index: usize = match closing_quote_index {
+            Some(closing_quote_index) => closing_quote_index,
+            None => return Err(malformed_string_error),
+        };
+        let after_value_bytes: &[u8] = trim_ascii_whitespace(&line_bytes[closing_quote_index + 1..line_content_end]);
+        if !after_value_bytes.is_empty() && after_value_bytes[0] != b'#' {
+            return Err(malformed_string_error);
+        }
+        closing_quote_index + 1
+    } else {
+        let comment_start: usize = match find_byte(&line_bytes[value_start_in_line..line_content_end], b'#') {
+            Some(comment_offset) => value_start_in_line + comment_offset,
+            None => line_content_end,
+        };
+        let unquoted_value_bytes: &[u8] = trim_ascii_whitespace(&line_bytes[value_start_in_line..comment_start]);
+        if unquoted_value_bytes.len() > OUTPUT_BUFFER_BYTES {
+            value_exceeds_output_buffer = true;
+        } else {
+            output_buffer[..unquoted_value_bytes.len()].copy_from_slice(unquoted_value_bytes);
+            written_length = unquoted_value_bytes.len();
+        }
+        value_start_in_line + unquoted_value_bytes.len()
+    };
+
+    let value_span = line_position.span(value_start_in_line, value_end_in_line);
+    if value_exceeds_output_buffer {
+        return Err(RsLsfSpannedError {
+            error: ReadTomlFieldError::RsLsfValueExceedsOutputBuffer,
+            span: Some(value_span),
+        });
+    }
+    Ok((output_buffer, written_length, value_span))
+}
+
+/// Decode the escape at the start of `escape_bytes` (which begins with
+/// the backslash): `\b \t \n \f \r \" \\`, `\uXXXX` and `\UXXXXXXXX`
+/// (a Unicode scalar value, written out as UTF-8).
+///
+/// Returns `Ok((consumed_length, decoded_bytes, decoded_length))`, or
+/// `Err(length)` with how many bytes of the invalid escape to span.
+fn decode_basic_string_escape(escape_bytes: &[u8]) -> Result<(usize, [u8; 4], usize), usize> {
+    if escape_bytes.len() < 2 {
+        return Err(escape_bytes.len());
+    }
+    let simple_byte: u8 = match escape_bytes[1] {
+        b'b' => 0x08,
+        b't' => b'\t',
+        b'n' => b'\n',
+        b'f' => 0x0C,
+        b'r' => b'\r',
+        b'"' => b'"',
+        b'\\' => b'\\',
+        b'u' | b'U' => 0,
+        _ => return Err(2),
+    };
+    if escape_bytes[1] != b'u' && escape_bytes[1] != b'U' {
+        return Ok((2, [simple_byte, 0, 0, 0], 1));
+    }
+
+    let hex_digit_count: usize = if escape_bytes[1] == b'u' { 4 } else { 8 };
+    let mut code_point: u32 = 0;
+    let mut hex_index: usize = 0;
+    while hex_index < hex_digit_count {
+        let hex_digit: u64 = match escape_bytes.get(2 + hex_index) {
+            Some(&hex_byte) => match digit_value(hex_byte, 16) {
+                Some(hex_digit) => hex_digit,
+                None => return Err(2 + hex_index),
+            },
+            None => return Err(2 + hex_index),
+        };
+        code_point = (code_point << 4) | hex_digit as u32;
+        hex_index += 1;
+    }
+    let escape_length: usize = 2 + hex_digit_count;
+    match char::from_u32(code_point) {
+        Some(decoded_char) => {
+            let mut decoded_bytes: [u8; 4] = [0u8; 4];
+            let decoded_length: usize = decoded_char.encode_utf8(&mut decoded_bytes).len();
+            Ok((escape_length, decoded_bytes, decoded_length))
+        }
+        None => Err(escape_length),
+    }
+}
+
+/// `RsLsfMatchingLineExceedsScanBuffer`, spanning an overflowed line from
+/// its first non-blank byte to its end.
+fn overflowed_line_error<'path>(
+    accumulated_line_bytes: &[u8],
+    line_position: &RsLsfLinePosition<'path>,
+) -> RsLsfSpannedError<'path> {
+    let line_content_start: usize = leading_space_or_tab_count(accumulated_line_bytes);
+    let mut line_span = line_position.span(line_content_start, line_content_start);
+    line_span.byte_end = line_position.line_end_offset;
+    RsLsfSpannedError {
+        error: ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer,
+        span: Some(line_span),
+    }
+}
+
+/// Match a single fully-accumulated line against `target_key_bytes`, the
+/// (possibly dotted) key expected under the current table.
+///
+/// The key is everything before the first `=`, so key `name` never
+/// matches line `name_long = ...`; spaces around dots are allowed
+/// (`network . node_id`), as in TOML.
+///
+/// Returns `Some((value_start, content_end))`, the byte range from the
+/// value to the end of the line's content (inline comment included,
+/// surrounding whitespace excluded) within `raw_line_bytes`, or `None` if
+/// the line does not hold the key.
+fn locate_value_in_line(raw_line_bytes: &[u8], target_key_bytes: &[u8]) -> Option<(usize, usize)> {
+    let trimmed_line_bytes: &[u8] = trim_ascii_whitespace(raw_line_bytes);
+
+    // Empty lines and full-line comments cannot be key-value pairs.
+    if trimmed_line_bytes.is_empty() || trimmed_line_bytes[0] == b'#' {
+        return None;
+    }
+    let trimmed_line_start: usize = raw_line_bytes.len()
+        - trim_ascii_whitespace_start(raw_line_bytes).len();
+
+    let equals_index: usize = find_byte(trimmed_line_bytes, b'=')?;
+    if !dotted_key_matches(trim_ascii_whitespace(&trimmed_line_bytes[..equals_index]), target_key_bytes) {
+        return None;
+    }
+
+    let mut cursor_position: usize = equals_index + 1;
+    while cursor_position < trimmed_line_bytes.len()
+        && is_ascii_space_or_tab_byte(trimmed_line_bytes[cursor_position])
+    {
+        cursor_position += 1;
+    }
+
+    let value_start: usize = trimmed_line_start + cursor_position;
+    let value_end: usize = trimmed_line_start + trimmed_line_bytes.len();
+    Some((value_start, value_end))
+}
+
+/// For a trimmed line starting with `[`, where the key under this header
+/// starts in `target_key_bytes`:
+///
+/// * `Some(Some(k))` — the header names the table `target_key_bytes[..k - 1]`
+///   (e.g. `[network]` for `network.node_id` gives `8`).
+/// * `Some(None)`    — any other table, an `[[array-of-tables]]` element
+///   (out of scope), or a line that is not a valid header.
+/// * `None`          — the closing `]` is not in the line buffer: the
+///   header is malformed, or the line overflowed and the table is unknown.
+fn header_table_key_start(trimmed_line_bytes: &[u8], target_key_bytes: &[u8]) -> Option<Option<usize>> {
+    if trimmed_line_bytes.len() >= 2 && trimmed_line_bytes[1] == b'[' {
+        return Some(None);
+    }
+    let closing_bracket_index: usize = find_byte(trimmed_line_bytes, b']')?;
+
+    // Only a comment may follow the header.
+    let after_header_bytes: &[u8] = trim_ascii_whitespace(&trimmed_line_bytes[closing_bracket_index + 1..]);
+    if !after_header_bytes.is_empty() && after_header_bytes[0] != b'#' {
+        return Some(None);
+    }
+
+    let header_key_bytes: &[u8] = trim_ascii_whitespace(&trimmed_line_bytes[1..closing_bracket_index]);
+    // Bounded by the key length (< MAX_LINE_BYTES).
+    let mut dot_index: usize = 0;
+    while dot_index < target_key_bytes.len() {
+        if target_key_bytes[dot_index] == b'.'
+            && dotted_key_matches(header_key_bytes, &target_key_bytes[..dot_index])
+        {
+            return Some(Some(dot_index + 1));
+        }
+        dot_index += 1;
+    }
+    Some(None)
+}
+
+/// Whether `written_key_bytes` (trimmed) is the dotted key
+/// `expected_key_bytes`, allowing spaces and tabs around each dot. Quoted
+/// keys are out of scope: quotes are compared as ordinary bytes.
+fn dotted_key_matches(written_key_bytes: &[u8], expected_key_bytes: &[u8]) -> bool {
+    if expected_key_bytes.is_empty() {
+        return false;
+    }
+    let mut written_index: usize = 0;
+    let mut expected_index: usize = 0;
+    // Each pass consumes at least one byte of both keys.
+    while expected_index < expected_key_bytes.len() {
+        let expected_byte: u8 = expected_key_bytes[expected_index];
+        if expected_byte == b'.' {
+            written_index += leading_space_or_tab_count(&written_key_bytes[written_index..]);
+        }
+        if written_index >= written_key_bytes.len() || written_key_bytes[written_index] != expected_byte {
+            return false;
+        }
+        written_index += 1;
+        expected_index += 1;
+        if expected_byte == b'.' {
+            written_index += leading_space_or_tab_count(&written_key_bytes[written_index..]);
+        }
+    }
+    written_index == written_key_bytes.len()
+}
+
+/// Index of the first `needle` byte, without allocating.
+fn find_byte(input_bytes: &[u8], needle: u8) -> Option<usize> {
+    let mut byte_index: usize = 0;
+    while byte_index < input_bytes.len() {
+        if input_bytes[byte_index] == needle {
+            return Some(byte_index);
+        }
+        byte_index += 1;
+    }
+    None
+}
+
+/// Whether an overflowed line, whose kept prefix `raw_line_bytes` holds
+/// no `=`, could still be the line of `key_bytes`: after leading blanks
+/// the prefix is the start of the key as it may be written (blanks allowed
+/// around dots, as in [`dotted_key_matches`]), or the whole key followed
+/// only by blanks. Used solely to decide whether skipping the line is safe.
+fn line_prefix_could_match_key(raw_line_bytes: &[u8], key_bytes: &[u8]) -> bool {
+    let written_key_bytes: &[u8] = &raw_line_bytes[leading_space_or_tab_count(raw_line_bytes)..];
+    let mut written_index: usize = 0;
+    let mut expected_index: usize = 0;
+    // Each pass consumes at least one byte of the key.
+    while expected_index < key_bytes.len() {
+        let expected_byte: u8 = key_bytes[expected_index];
+        if expected_byte == b'.' {
+            written_index += leading_space_or_tab_count(&written_key_bytes[written_index..]);
+        }
+        if written_index >= written_key_bytes.len() {
+            // The prefix ends inside the key: the rest may follow.
+            return true;
+        }
+        if written_key_bytes[written_index] != expected_byte {
+            return false;
+        }
+        written_index += 1;
+        expected_index += 1;
+        if expected_byte == b'.' {
+            written_index += leading_space_or_tab_count(&written_key_bytes[written_index..]);
+        }
+    }
+    written_index + leading_space_or_tab_count(&written_key_bytes[written_index..]) == written_key_bytes.len()
+}
+
+/// Number of leading space/tab bytes.
+fn leading_space_or_tab_count(input_bytes: &[u8]) -> usize {
+    let mut leading_index: usize = 0;
+    while leading_index < input_bytes.len() && is_ascii_space_or_tab_byte(input_bytes[leading_index]) {
+        leading_index += 1;
+    }
+    leading_index
+}
+
+/// Trim ASCII whitespace (space, tab, CR, LF) from the start only.
+fn trim_ascii_whitespace_start(input_bytes: &[u8]) -> &[u8] {
+    let mut start_index: usize = 0;
+    while start_index < input_bytes.len() && is_ascii_whitespace_byte(input_bytes[start_index]) {
+        start_index += 1;
+    }
+    &input_bytes[start_index..]
+}
+
+/// Trim ASCII whitespace (space, tab, CR, LF) from both ends, without
+/// allocating. Returns a sub-slice of the input.
+fn trim_ascii_whitespace(input_bytes: &[u8]) -> &[u8] {
+    let mut start_index: usize = 0;
+    let mut end_index: usize = input_bytes.len();
+    while start_index < end_index && is_ascii_whitespace_byte(input_bytes[start_index]) {
+        start_index += 1;
+    }
+    while end_index > start_index && is_ascii_whitespace_byte(input_bytes[end_index - 1]) {
+        end_index -= 1;
+    }
+    &input_bytes[start_index..end_index]
+}
+
+#[inline]
+fn is_ascii_whitespace_byte(byte_value: u8) -> bool {
+    matches!(byte_value, b' ' | b'\t' | b'\r' | b'\n')
+}
+
+#[inline]
+fn is_ascii_space_or_tab_byte(byte_value: u8) -> bool {
+    matches!(byte_value, b' ' | b'\t')
+}
+
+/// Parse one TOML integer as written (no surrounding whitespace) into its
+/// sign and magnitude, without allocating.
+///
+/// Decimal values may carry a sign; a leading zero is only allowed in `0`
+/// itself. `0x`, `0o` and `0b` values are unsigned. An underscore must sit
+/// between two digits. A magnitude past `u64::MAX` is
+/// `RsLsfIntegerOutOfRange`; anything else malformed is
+/// `RsLsfValueNotInteger`.
+fn parse_toml_integer_bytes(integer_bytes: &[u8]) -> Result<(bool, u64), ReadTomlFieldError> {
+    let mut cursor_position: usize = 0;
+    let mut is_negative: bool = false;
+    if !integer_bytes.is_empty() && (integer_bytes[0] == b'+' || integer_bytes[0] == b'-') {
+        is_negative = integer_bytes[0] == b'-';
+        cursor_position = 1;
+    }
+
+    let mut radix: u64 = 10;
+    let unsigned_bytes: &[u8] = &integer_bytes[cursor_position..];
+    if unsigned_bytes.len() >= 2 && unsigned_bytes[0] == b'0' {
+        // A prefix, or a decimal with a forbidden leading zero.
+        radix = match unsigned_bytes[1] {
+            b'x' => 16,
+            b'o' => 8,
+            b'b' => 2,
+            _ => return Err(ReadTomlFieldError::RsLsfValueNotInteger),
+        };
+        if cursor_position != 0 {
+            return Err(ReadTomlFieldError::RsLsfValueNotInteger);
+        }
+        cursor_position += 2;
+    }
+
+    let digit_bytes: &[u8] = &integer_bytes[cursor_position..];
+    if digit_bytes.is_empty() {
+        return Err(ReadTomlFieldError::RsLsfValueNotInteger);
+    }
+    let mut magnitude: u64 = 0;
+    let mut previous_was_digit: bool = false;
+    let mut digit_index: usize = 0;
+    while digit_index < digit_bytes.len() {
+        let digit_byte: u8 = digit_bytes[digit_index];
+        if digit_byte == b'_' {
+            let next_is_digit: bool = digit_index + 1 < digit_bytes.len()
+                && digit_value(digit_bytes[digit_index + 1], radix).is_some();
+            if !previous_was_digit || !next_is_digit {
+                return Err(ReadTomlFieldError::RsLsfValueNotInteger);
+            }
+            previous_was_digit = false;
+        } else {
+            let digit: u64 = match digit_value(digit_byte, radix) {
+                Some(digit) => digit,
+                None => return Err(ReadTomlFieldError::RsLsfValueNotInteger),
+            };
+            magnitude = match magnitude.checked_mul(radix).and_then(|shifted| shifted.checked_add(digit)) {
+                Some(next_magnitude) => next_magnitude,
+                None => {
+                    // Keep checking the syntax: `99999999999999999999x` is
+                    // malformed, not out of range.
+                    let mut rest_index: usize = digit_index + 1;
+                    while rest_index < digit_bytes.len() {
+                        if digit_bytes[rest_index] != b'_' && digit_value(digit_bytes[rest_index], radix).is_none() {
+                            return Err(ReadTomlFieldError::RsLsfValueNotInteger);
+                        }
+                        rest_index += 1;
+                    }
+                    return Err(ReadTomlFieldError::RsLsfIntegerOutOfRange);
+                }
+            };
+            previous_was_digit = true;
+        }
+        digit_index += 1;
+    }
+    Ok((is_negative, magnitude))
+}
+
+/// The value of one digit byte in `radix` (2, 8, 10 or 16), if it is one.
+fn digit_value(digit_byte: u8, radix: u64) -> Option<u64> {
+    let digit: u64 = match digit_byte {
+        b'0'..=b'9' => u64::from(digit_byte - b'0'),
+        b'a'..=b'f' => u64::from(digit_byte - b'a') + 10,
+        b'A'..=b'F' => u64::from(digit_byte - b'A') + 10,
+        _ => return None,
+    };
+    if digit < radix { Some(digit) } else { None }
+}
+
+/// Sign and magnitude as an `i64`; `-(2^63)` is the one magnitude past
+/// `i64::MAX` that fits.
+fn signed_integer_from_magnitude(is_negative: bool, magnitude: u64) -> Result<i64, ReadTomlFieldError> {
+    if is_negative {
+        if magnitude > i64::MIN.unsigned_abs() {
+            return Err(ReadTomlFieldError::RsLsfIntegerOutOfRange);
+        }
+        Ok(0i64.wrapping_sub_unsigned(magnitude))
+    } else {
+        match i64::try_from(magnitude) {
+            Ok(value) => Ok(value),
+            Err(_) => Err(ReadTomlFieldError::RsLsfIntegerOutOfRange),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Tests
+// ----------------------------------------------------------------------------
+// Only `core` here too, so the tests come along when the file is copied.
+#[cfg(test)]
+mod rslsf_core_tests {
+    use super::*;
+
+    const DEMO_TOML_BYTES: &[u8] = b"# in-memory config\n\
+        node_id = \"alice-node-01\"\r\n\
+        port = 8080\n\
+        verbose = true\n\
+        retries = [1, 2, 4]\n\
+        \n\
+        [network]\n\
+        bind_port = 7000\n";
+
+    /// A source handing out at most `bytes_per_read` bytes per call, like a
+    /// byte-at-a-time flash or UART driver.
+    struct TrickleSource<'bytes> {
+        remaining_bytes: &'bytes [u8],
+        bytes_per_read: usize,
+    }
+
+    impl RsLsfByteSource for TrickleSource<'_> {
+        fn read_chunk(&mut self, chunk_buffer: &mut [u8]) -> Result<usize, ReadTomlFieldError> {
+            let copied_length: usize = chunk_buffer.len().min(self.bytes_per_read).min(self.remaining_bytes.len());
+            chunk_buffer[..copied_length].copy_from_slice(&self.remaining_bytes[..copied_length]);
+            self.remaining_bytes = &self.remaining_bytes[copied_length..];
+            Ok(copied_length)
+        }
+    }
+
+    /// A broken source: fails, or claims more bytes than it was given room for.
+    struct BrokenSource {
+        claimed_length: Option<usize>,
+    }
+
+    impl RsLsfByteSource for BrokenSource {
+        fn read_chunk(&mut self, chunk_buffer: &mut [u8]) -> Result<usize, ReadTomlFieldError> {
+            match self.claimed_length {
+                Some(claimed_length) => Ok(chunk_buffer.len() + claimed_length),
+                None => Err(ReadTomlFieldError::RsLsfFileReadFailed),
+            }
+        }
+    }
+
+    #[test]
+    fn rslsf_core_reads_every_value_kind_from_a_slice() {
+        let read_string = |key: &str| {
+            read_single_line_string_field_from_source_no_heap::<16, 16, 64, 4096>(
+                &mut RsLsfSliceSource::new(DEMO_TOML_BYTES),
+                key,
+            )
+        };
+        let (output_buffer, written_length) = read_string("node_id").expect("node_id");
+        assert_eq!(&output_buffer[..written_length], b"alice-node-01");
+        let (output_buffer, written_length) = read_string("network.bind_port").expect("bind_port");
+        assert_eq!(&output_buffer[..written_length], b"7000");
+        assert_eq!(read_string("missing"), Err(ReadTomlFieldError::RsLsfFieldNotFound));
+
+        assert_eq!(
+            read_u64_field_from_source_no_heap::<16, 64, 4096>(
+                &mut RsLsfSliceSource::new(DEMO_TOML_BYTES),
+                "port",
+                1..=65535,
+            ),
+            Ok(8080)
+        );
+        assert_eq!(
+            read_bool_field_from_source_no_heap::<16, 64, 4096>(&mut RsLsfSliceSource::new(DEMO_TOML_BYTES), "verbose"),
+            Ok(true)
+        );
+        assert_eq!(
+            read_i64_array_field_from_source_no_heap::<4, 16, 64, 4096>(
+                &mut RsLsfSliceSource::new(DEMO_TOML_BYTES),
+                "retries",
+            ),
+            Ok(([1, 2, 4, 0], 3))
+        );
+
+        let field_slots = read_single_line_string_fields_from_source_no_heap::<2, 16, 16, 64, 4096>(
+            &mut RsLsfSliceSource::new(DEMO_TOML_BYTES),
+            &["port", "missing"],
+        )
+        .expect("one pass");
+        assert_eq!(field_slots[0].status, RsLsfFieldStatus::Found);
+        assert_eq!(&field_slots[0].output_buffer[..field_slots[0].written_length], b"8080");
+        assert_eq!(field_slots[1].status, RsLsfFieldStatus::NotFound);
+    }
+
+    #[test]
+    fn rslsf_core_spans_name_the_source() {
+        let (_, _, value_span) = read_single_line_string_field_from_source_no_heap_with_span::<16, 16, 64, 4096>(
+            &mut RsLsfSliceSource::new(DEMO_TOML_BYTES),
+            "<flash>",
+            "port",
+        )
+        .expect("port");
+        assert_eq!(value_span.file_path, "<flash>");
+        assert_eq!((value_span.line_number, value_span.column), (3, 8));
+        assert_eq!(&DEMO_TOML_BYTES[value_span.byte_start as usize..value_span.byte_end as usize], b"8080");
+    }
+
+    #[test]
+    fn rslsf_core_handles_short_reads() {
+        // One byte per read: every line and the CRLF are split across
+        // reads, and the scan needs many more reads than chunks.
+        let mut trickle_source = TrickleSource {
+            remaining_bytes: DEMO_TOML_BYTES,
+            bytes_per_read: 1,
+        };
+        let (output_buffer, written_length) =
+            read_single_line_string_field_from_source_no_heap::<16, 256, 512, 4096>(&mut trickle_source, "network.bind_port")
+                .expect("short reads should still find the value");
+        assert_eq!(&output_buffer[..written_length], b"7000");
+
+        // Short reads are not charged as whole chunks: a budget only just
+        // above the text's size still reaches its end ...
+        let mut trickle_source = TrickleSource {
+            remaining_bytes: DEMO_TOML_BYTES,
+            bytes_per_read: 1,
+        };
+        assert_eq!(
+            read_single_line_string_field_from_source_no_heap::<16, 256, 512, 512>(&mut trickle_source, "missing"),
+            Err(ReadTomlFieldError::RsLsfFieldNotFound)
+        );
+        // ... while a budget below it still bounds the scan.
+        let mut trickle_source = TrickleSource {
+            remaining_bytes: DEMO_TOML_BYTES,
+            bytes_per_read: 1,
+        };
+        assert_eq!(
+            read_single_line_string_field_from_source_no_heap::<16, 16, 16, 16>(&mut trickle_source, "port"),
+            Err(ReadTomlFieldError::RsLsfSafetyBudgetExhausted)
+        );
+    }
+
+    #[test]
+    fn rslsf_core_rejects_broken_sources() {
+        assert_eq!(
+            read_single_line_string_field_from_source_no_heap::<16, 16, 64, 4096>(
+                &mut BrokenSource { claimed_length: None },
+                "port",
+            ),
+            Err(ReadTomlFieldError::RsLsfFileReadFailed)
+        );
+        assert_eq!(
+            read_single_line_string_field_from_source_no_heap::<16, 16, 64, 4096>(
+                &mut BrokenSource { claimed_length: Some(1) },
+                "port",
+            ),
+            Err(ReadTomlFieldError::RsLsfFileReadFailed)
+        );
+        // Invalid calls are rejected before the source is touched.
+        assert_eq!(
+            read_single_line_string_field_from_source_no_heap::<16, 16, 64, 4096>(
+                &mut BrokenSource { claimed_length: None },
+                "",
+            ),
+            Err(ReadTomlFieldError::RsLsfEmptyKey)
+        );
+    }
+
+    /// Strings, comments and a BOM, as a config written by another tool
+    /// might have them.
+    const STRINGS_TOML_BYTES: &[u8] = b"\xEF\xBB\xBFtitle = \"Tab\\tQuote\\\" \\u00e9\\U0001F600\" # greeting\n\
+        path = 'C:\\dir\\n' # kept verbatim\n\
+        port = 8080   # inline comment\n\
+        empty = \"\"\n\
+        hash = \"a # b\"\n\
+        bad_escape = \"x\\qy\"\n\
+        short_unicode = \"\\u12\"\n\
+        surrogate = \"\\uD800\"\n\
+        unclosed = \"abc\n\
+        trailing = \"abc\" def\n\
+        multi = \"\"\"\n";
+
+    fn read_strings_field(key: &str) -> Result<([u8; 32], usize, RsLsfSpan<'static>), RsLsfSpannedError<'static>> {
+        read_single_line_string_field_from_source_no_heap_with_span::<32, 16, 64, 4096>(
+            &mut RsLsfSliceSource::new(STRINGS_TOML_BYTES),
+            "<strings>",
+            key,
+        )
+    }
+
+    #[test]
+    fn rslsf_core_decodes_strings_and_drops_comments() {
+        let (output_buffer, written_length, value_span) = read_strings_field("title").expect("title after a BOM");
+        assert_eq!(&output_buffer[..written_length], "Tab\tQuote\" \u{e9}\u{1F600}".as_bytes());
+        // The span is the value as written, quotes in, comment out.
+        assert_eq!(
+            &STRINGS_TOML_BYTES[value_span.byte_start as usize..value_span.byte_end as usize],
+            b"\"Tab\\tQuote\\\" \\u00e9\\U0001F600\""
+        );
+        assert_eq!((value_span.line_number, value_span.column), (1, 9));
+
+        for (key, expected_value) in [
+            ("path", &b"C:\\dir\\n"[..]),
+            ("port", b"8080"),
+            ("empty", b""),
+            ("hash", b"a # b"),
+        ] {
+            let (output_buffer, written_length, _) = read_strings_field(key).expect(key);
+            assert_eq!(&output_buffer[..written_length], expected_value, "{key}");
+        }
+
+        assert_eq!(
+            read_u64_field_from_source_no_heap::<16, 64, 4096>(
+                &mut RsLsfSliceSource::new(STRINGS_TOML_BYTES),
+                "port",
+                1..=65535,
+            ),
+            Ok(8080)
+        );
+        // A decoded string still counts as a string for the typed readers.
+        assert_eq!(
+            read_u64_field_from_source_no_heap::<16, 64, 4096>(
+                &mut RsLsfSliceSource::new(b"port = \"\\u0038080\"\n"),
+                "port",
+                0..=u64::MAX,
+            ),
+            Err(ReadTomlFieldError::RsLsfValueNotInteger)
+        );
+
+        // The decoded length, not the written one, must fit.
+        let (output_buffer, written_length) = read_single_line_string_field_from_source_no_heap::<1, 16, 64, 4096>(
+            &mut RsLsfSliceSource::new(b"letter = \"\\u0041\"\n"),
+            "letter",
+        )
+        .expect("one decoded byte fits");
+        assert_eq!(&output_buffer[..written_length], b"A");
+        let spanned_error = read_single_line_string_field_from_source_no_heap_with_span::<2, 16, 64, 4096>(
+            &mut RsLsfSliceSource::new(b"accent = \"\\u00e9\\u00e9\" # two letters\n"),
+            "<accent>",
+            "accent",
+        )
+        .expect_err("four decoded bytes do not fit in two");
+        assert_eq!(spanned_error.error, ReadTomlFieldError::RsLsfValueExceedsOutputBuffer);
+        let value_span = spanned_error.span.expect("the value");
+        assert_eq!((value_span.column, value_span.byte_end - value_span.byte_start), (10, 14));
+    }
+
+    #[test]
+    fn rslsf_core_rejects_bad_escapes_and_malformed_strings() {
+        for (key, expected_column, expected_escape) in [
+            ("bad_escape", 16, &b"\\q"[..]),
+            ("short_unicode", 18, b"\\u12"),
+            ("surrogate", 14, b"\\uD800"),
+        ] {
+            let spanned_error = read_strings_field(key).expect_err(key);
+            assert_eq!(spanned_error.error, ReadTomlFieldError::RsLsfInvalidEscapeSequence, "{key}");
+            let escape_span = spanned_error.span.expect("the escape");
+            assert_eq!(escape_span.column, expected_column, "{key}");
+            assert_eq!(
+                &STRINGS_TOML_BYTES[escape_span.byte_start as usize..escape_span.byte_end as usize],
+                expected_escape,
+                "{key}"
+            );
+        }
+
+        for key in ["unclosed", "trailing", "multi"] {
+            let spanned_error = read_strings_field(key).expect_err(key);
+            assert_eq!(spanned_error.error, ReadTomlFieldError::RsLsfMalformedString, "{key}");
+        }
+
+        let field_slots = read_single_line_string_fields_from_source_no_heap::<4, 32, 16, 64, 4096>(
+            &mut RsLsfSliceSource::new(STRINGS_TOML_BYTES),
+            &["title", "bad_escape", "unclosed", "port"],
+        )
+        .expect("one pass");
+        assert_eq!(
+            field_slots.map(|field_slot| field_slot.status),
+            [
+                RsLsfFieldStatus::Found,
+                RsLsfFieldStatus::InvalidEscapeSequence,
+                RsLsfFieldStatus::MalformedString,
+                RsLsfFieldStatus::Found,
+            ]
+        );
+    }
+
+    #[test]
+    fn rslsf_core_skips_a_byte_order_mark_only_at_the_start() {
+        // Before a header, too, and with the CRLF split across reads.
+        let mut trickle_source = TrickleSource {
+            remaining_bytes: b"\xEF\xBB\xBF[network]\r\nnode_id = 'n1'\r\n",
+            bytes_per_read: 2,
+        };
+        let (output_buffer, written_length) =
+            read_single_line_string_field_from_source_no_heap::<8, 16, 64, 4096>(&mut trickle_source, "network.node_id")
+                .expect("header after a BOM");
+        assert_eq!(&output_buffer[..written_length], b"n1");
+
+        // A BOM anywhere else is ordinary line content.
+        assert_eq!(
+            read_single_line_string_field_from_source_no_heap::<8, 16, 64, 4096>(
+                &mut RsLsfSliceSource::new(b"a = 1\n\xEF\xBB\xBFnode_id = 'n1'\n"),
+                "node_id",
+            ),
+            Err(ReadTomlFieldError::RsLsfFieldNotFound)
+        );
+    }
+
+    #[test]
+    fn rslsf_core_reports_overflowed_lines_that_could_hold_a_spaced_dotted_key() {
+        // The kept prefix ends before the `=`: a key written with blanks
+        // around the dot (or pushed back by indentation) must not be
+        // skipped as unrelated.
+        for toml_bytes in [
+            &b"server . node_id = \"a long value past the line buffer\"\n"[..],
+            b"\t\t\tserver.node_id = \"a long value past the line buffer\"\n",
+            b"server.node_id            = 1\n",
+        ] {
+            assert_eq!(
+                read_single_line_string_field_from_source_no_heap::<64, 16, 16, 4096>(
+                    &mut RsLsfSliceSource::new(toml_bytes),
+                    "server.node_id",
+                ),
+                Err(ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer)
+            );
+        }
+        // A prefix that already differs from the key is still skipped.
+        assert_eq!(
+            read_single_line_string_field_from_source_no_heap::<64, 16, 16, 4096>(
+                &mut RsLsfSliceSource::new(b"server.node_id_long = \"a long value\"\n"),
+                "server.node_id",
+            ),
+            Err(ReadTomlFieldError::RsLsfFieldNotFound)
+        );
+    }
+
+    #[test]
+    fn rslsf_core_budget_does_not_depend_on_read_sizes() {
+        // 20 bytes: the key's line ends at byte 9, inside a 12 byte budget
+        // that a whole 8 byte chunk would overrun at byte 16.
+        const BUDGET_TOML_BYTES: &[u8] = b"port = 1\nname = 'x'\n";
+        for bytes_per_read in [1, 3, 8] {
+            let mut trickle_source = TrickleSource {
+                remaining_bytes: BUDGET_TOML_BYTES,
+                bytes_per_read,
+            };
+            let (output_buffer, written_length) =
+                read_single_line_string_field_from_source_no_heap::<4, 8, 12, 12>(&mut trickle_source, "port")
+                    .expect("within the budget");
+            assert_eq!(&output_buffer[..written_length], b"1");
+
+            let mut trickle_source = TrickleSource {
+                remaining_bytes: BUDGET_TOML_BYTES,
+                bytes_per_read,
+            };
+            assert_eq!(
+                read_single_line_string_field_from_source_no_heap::<4, 8, 12, 12>(&mut trickle_source, "name"),
+                Err(ReadTomlFieldError::RsLsfSafetyBudgetExhausted)
+            );
+            // One byte past the budget was read to find that out, no more.
+            assert_eq!(trickle_source.remaining_bytes.len(), BUDGET_TOML_BYTES.len() - 13);
+        }
+    }
+}
@@ -0,0 +1,519 @@
+//! Property tests for the no-heap reader (test-only; heap use is fine here).
+//!
+//! Random TOML documents, keys and scan parameters are thrown at
+//! [`read_single_line_string_field_from_toml_no_heap`], and every answer is
+//! checked against the heap reader in `read_toml_field` as an oracle. Where
+//! the two may legitimately differ, the difference must be explained by a
+//! bound of the no-heap reader (buffer sizes, line length, byte budget).
+//!
+//! The generator is self-contained (SplitMix64, no crates, no network) and
+//! deterministic. A failure message names the case seed; reproduce it with
+//!
+//! ```text
+//! RSLSF_PROPERTY_SEED=<seed> RSLSF_PROPERTY_CASES=1 cargo test rslsf_property
+//! ```
+//!
+//! `RSLSF_PROPERTY_CASES` alone runs more (or fewer) cases per test.
+
+use super::*;
+use read_toml_field::read_toml_field::{ReadTomlError, TomlValue, read_toml_value};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+/// Cases per test when `RSLSF_PROPERTY_CASES` is not set.
+const DEFAULT_PROPERTY_CASES: u64 = 256;
+
+/// First case seed when `RSLSF_PROPERTY_SEED` is not set.
+const DEFAULT_PROPERTY_SEED: u64 = 0x5EED_0F15_F00D_0017;
+
+/// Keys are drawn from small pools so queries often hit, miss by a
+/// prefix (`name` / `name_long`), or name a key in another table.
+const KEY_POOL: [&str; 7] = ["a", "b", "name", "name_long", "port", "node_id", "x-1"];
+const TABLE_POOL: [&str; 3] = ["server", "network", "db"];
+
+/// Blanks between tokens.
+const BLANK_PIECES: [&str; 4] = ["", " ", "  ", "\t"];
+
+/// Text pieces for string values: plain text, bytes that look like TOML
+/// syntax, multi-byte UTF-8, and (basic strings only) escapes.
+const PLAIN_PIECES: [&str; 10] = ["a", "Z", "7", " ", "#", "=", "[x]", "é", "😀", "\t"];
+const BASIC_ONLY_PIECES: [&str; 9] = ["'", "\\\"", "\\\\", "\\t", "\\n", "\\b", "\\u00e9", "\\U0001F600", "\\u0041"];
+const LITERAL_ONLY_PIECES: [&str; 3] = ["\"", "\\", "\\q"];
+
+/// SplitMix64: tiny, seedable and good enough to drive a generator.
+struct RsLsfTestRng {
+    state: u64,
+}
+
+impl RsLsfTestRng {
+    fn new(seed: u64) -> Self {
+        RsLsfTestRng { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut mixed = self.state;
+        mixed = (mixed ^ (mixed >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        mixed = (mixed ^ (mixed >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        mixed ^ (mixed >> 31)
+    }
+
+    /// Uniform in `0..bound` (`bound > 0`; the modulo bias is irrelevant here).
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    fn chance(&mut self, percent: usize) -> bool {
+        self.below(100) < percent
+    }
+
+    fn pick<'item, Item>(&mut self, items: &'item [Item]) -> &'item Item {
+        &items[self.below(items.len())]
+    }
+
+    fn spaces(&mut self) -> &'static str {
+        self.pick::<&str>(&BLANK_PIECES)
+    }
+}
+
+/// One generated document, kept as lines so the no-heap reader and the
+/// oracle can each get the encoding they support.
+struct GeneratedToml {
+    /// Lines without terminators, as the oracle reads them.
+    lines: Vec<String>,
+    /// The no-heap copy starts with a UTF-8 BOM (the oracle's does not).
+    with_byte_order_mark: bool,
+    /// The no-heap copy uses CRLF line endings (the oracle's uses LF).
+    with_crlf: bool,
+    /// The no-heap copy's last line has no terminator.
+    without_final_newline: bool,
+    /// Full paths of every key written, e.g. `port` or `server.port`.
+    written_keys: Vec<String>,
+}
+
+impl GeneratedToml {
+    fn generate(rng: &mut RsLsfTestRng) -> Self {
+        let mut lines = Vec::new();
+        let mut written_keys = Vec::new();
+        let mut used_paths = HashSet::new();
+
+        let table_count = rng.below(3);
+        let mut section_index = 0;
+        while section_index <= table_count {
+            let table_name = if section_index == 0 {
+                None
+            } else {
+                // Distinct tables, so no table is defined twice.
+                let table_name = TABLE_POOL[(section_index - 1) % TABLE_POOL.len()];
+                lines.push(format!("{}[{}{}{}]{}", rng.spaces(), rng.spaces(), table_name, rng.spaces(), trailing_comment(rng)));
+                Some(table_name)
+            };
+
+            let line_count = rng.below(8);
+            for _ in 0..line_count {
+                match rng.below(10) {
+                    0 => lines.push(rng.spaces().to_string()),
+                    1 => lines.push(format!("{}# comment with key = \"value\"", rng.spaces())),
+                    _ => {
+                        let key = *rng.pick(&KEY_POOL);
+                        // A dotted key for another table, written at the top.
+                        let (written_key, full_path) = match table_name {
+                            None if rng.chance(25) => {
+                                let dotted_table = *rng.pick(&TABLE_POOL);
+                                let dot = *rng.pick(&[".", " . "]);
+                                (format!("{}{}{}", dotted_table, dot, key), format!("{}.{}", dotted_table, key))
+                            }
+                            None => (key.to_string(), key.to_string()),
+                            Some(table_name) => (key.to_string(), format!("{}.{}", table_name, key)),
+                        };
+                        // Tables are only opened after the top-level lines,
+                        // so skipping repeats is enough to avoid duplicates.
+                        if !used_paths.insert(full_path.clone()) {
+                            continue;
+                        }
+                        lines.push(format!(
+                            "{}{}{}={}{}{}",
+                            rng.spaces(),
+                            written_key,
+                            rng.spaces(),
+                            rng.spaces(),
+                            generate_value(rng),
+                            trailing_comment(rng),
+                        ));
+                        written_keys.push(full_path);
+                    }
+                }
+            }
+            section_index += 1;
+        }
+
+        GeneratedToml {
+            lines,
+            with_byte_order_mark: rng.chance(20),
+            with_crlf: rng.chance(30),
+            without_final_newline: rng.chance(30),
+            written_keys,
+        }
+    }
+
+    fn oracle_text(&self) -> String {
+        let mut oracle_text = self.lines.join("\n");
+        oracle_text.push('\n');
+        oracle_text
+    }
+
+    fn noheap_bytes(&self) -> Vec<u8> {
+        let mut noheap_bytes = Vec::new();
+        if self.with_byte_order_mark {
+            noheap_bytes.extend_from_slice(b"\xEF\xBB\xBF");
+        }
+        let line_terminator: &[u8] = if self.with_crlf { b"\r\n" } else { b"\n" };
+        for (line_index, line) in self.lines.iter().enumerate() {
+            noheap_bytes.extend_from_slice(line.as_bytes());
+            if line_index + 1 < self.lines.len() || !self.without_final_newline {
+                noheap_bytes.extend_from_slice(line_terminator);
+            }
+        }
+        noheap_bytes
+    }
+
+    /// The longest line the no-heap scanner has to hold (a BOM counts:
+    /// it is stripped only once the line is complete).
+    fn longest_noheap_line(&self) -> usize {
+        let bom_length = if self.with_byte_order_mark { 3 } else { 0 };
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(line_index, line)| if line_index == 0 { line.len() + bom_length } else { line.len() })
+            .max()
+            .unwrap_or(bom_length)
+    }
+
+    /// A key to look up: mostly one that was written, otherwise a near
+    /// miss, another table's key, a whole table, or an empty key.
+    fn pick_query(&self, rng: &mut RsLsfTestRng) -> String {
+        match rng.below(10) {
+            0..=5 if !self.written_keys.is_empty() => rng.pick(&self.written_keys).clone(),
+            6 => rng.pick(&KEY_POOL).to_string(),
+            7 => format!("{}.{}", rng.pick(&TABLE_POOL), rng.pick(&KEY_POOL)),
+            8 if rng.chance(20) => String::new(),
+            8 => rng.pick(&TABLE_POOL).to_string(),
+            _ => "nam".to_string(),
+        }
+    }
+}
+
+/// Nothing, or an inline comment (which may hold quotes and `=`).
+fn trailing_comment(rng: &mut RsLsfTestRng) -> String {
+    if rng.chance(30) {
+        format!("{}# note = \"x\" 'y'", rng.spaces())
+    } else {
+        String::new()
+    }
+}
+
+/// A value as written: a string of either kind, an integer, a boolean, an
+/// integer array, or (rarely) a broken string the oracle must reject.
+fn generate_value(rng: &mut RsLsfTestRng) -> String {
+    match rng.below(20) {
+        0..=6 => format!("\"{}\"", generate_string_body(rng, &BASIC_ONLY_PIECES)),
+        7..=9 => format!("'{}'", generate_string_body(rng, &LITERAL_ONLY_PIECES)),
+        10..=12 => {
+            let integer_value = match rng.below(3) {
+                0 => rng.below(10) as i64,
+                1 => rng.below(100_000) as i64 - 50_000,
+                _ => rng.next_u64() as i64,
+            };
+            integer_value.to_string()
+        }
+        13 | 14 => rng.pick(&["true", "false"]).to_string(),
+        15 | 16 => {
+            let element_count = rng.below(5);
+            let elements: Vec<String> = (0..element_count).map(|_| (rng.below(1000) as i64 - 500).to_string()).collect();
+            format!("[{}]", elements.join(", "))
+        }
+        17 => format!("\"{}\\q{}\"", generate_string_body(rng, &[]), generate_string_body(rng, &[])),
+        18 => format!("\"{}", generate_string_body(rng, &[])),
+        _ => format!("\"{}\" trailing", generate_string_body(rng, &[])),
+    }
+}
+
+/// String contents, usually short, sometimes longer than any line buffer.
+fn generate_string_body(rng: &mut RsLsfTestRng, extra_pieces: &[&str]) -> String {
+    let piece_count = if rng.chance(10) { 100 + rng.below(200) } else { rng.below(12) };
+    let mut string_body = String::new();
+    for _ in 0..piece_count {
+        if !extra_pieces.is_empty() && rng.chance(30) {
+            string_body.push_str(rng.pick::<&str>(extra_pieces));
+        } else {
+            string_body.push_str(rng.pick::<&str>(&PLAIN_PIECES));
+        }
+    }
+    string_body
+}
+
+/// A source over a slice that hands out a random number of bytes per read
+/// and records how many it handed out in total.
+struct CountingTrickleSource<'bytes, 'rng> {
+    remaining_bytes: &'bytes [u8],
+    rng: &'rng mut RsLsfTestRng,
+    bytes_handed_out: u64,
+}
+
+impl RsLsfByteSource for CountingTrickleSource<'_, '_> {
+    fn read_chunk(&mut self, chunk_buffer: &mut [u8]) -> Result<usize, ReadTomlFieldError> {
+        let copy_length = (1 + self.rng.below(chunk_buffer.len())).min(self.remaining_bytes.len());
+        chunk_buffer[..copy_length].copy_from_slice(&self.remaining_bytes[..copy_length]);
+        self.remaining_bytes = &self.remaining_bytes[copy_length..];
+        self.bytes_handed_out += copy_length as u64;
+        Ok(copy_length)
+    }
+}
+
+/// The files of one case, removed again when the case ends.
+struct PropertyCaseFiles {
+    noheap_path: PathBuf,
+    oracle_path: PathBuf,
+}
+
+impl PropertyCaseFiles {
+    fn write(label: &str, case_seed: u64, noheap_bytes: &[u8], oracle_text: &str) -> Self {
+        let temp_directory = std::env::temp_dir();
+        let file_stem = format!("rslsf_property_{}_{}_{:016x}", std::process::id(), label, case_seed);
+        let case_files = PropertyCaseFiles {
+            noheap_path: temp_directory.join(format!("{}_noheap.toml", file_stem)),
+            oracle_path: temp_directory.join(format!("{}_oracle.toml", file_stem)),
+        };
+        std::fs::write(&case_files.noheap_path, noheap_bytes).expect("test setup: write no-heap file");
+        std::fs::write(&case_files.oracle_path, oracle_text).expect("test setup: write oracle file");
+        case_files
+    }
+}
+
+impl Drop for PropertyCaseFiles {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.noheap_path);
+        let _ = std::fs::remove_file(&self.oracle_path);
+    }
+}
+
+/// Case seeds for one test: `RSLSF_PROPERTY_SEED` and
+/// `RSLSF_PROPERTY_CASES` if set, the defaults otherwise.
+fn property_case_seeds() -> impl Iterator<Item = u64> {
+    let first_seed = std::env::var("RSLSF_PROPERTY_SEED")
+        .ok()
+        .and_then(|seed_text| {
+            let seed_text = seed_text.trim();
+            match seed_text.strip_prefix("0x") {
+                Some(hex_digits) => u64::from_str_radix(hex_digits, 16).ok(),
+                None => seed_text.parse().ok(),
+            }
+        })
+        .unwrap_or(DEFAULT_PROPERTY_SEED);
+    let case_count = std::env::var("RSLSF_PROPERTY_CASES")
+        .ok()
+        .and_then(|count_text| count_text.trim().parse().ok())
+        .unwrap_or(DEFAULT_PROPERTY_CASES);
+    (0..case_count).map(move |case_index| first_seed.wrapping_add(case_index))
+}
+
+/// What the no-heap reader must answer, given the oracle's answer.
+fn check_against_oracle<const OUTPUT_BUFFER_BYTES: usize>(
+    noheap_result: &Result<([u8; OUTPUT_BUFFER_BYTES], usize), ReadTomlFieldError>,
+    oracle_result: &Result<TomlValue, ReadTomlError>,
+) -> Result<(), String> {
+    let expected_bytes: Option<Vec<u8>> = match oracle_result {
+        Ok(TomlValue::String(text)) => Some(text.as_bytes().to_vec()),
+        Ok(other_value) => Some(other_value.to_string().into_bytes()),
+        Err(_) => None,
+    };
+
+    match (noheap_result, oracle_result) {
+        (Ok((output_buffer, written_length)), Ok(_)) => {
+            let expected_bytes = expected_bytes.unwrap_or_default();
+            if output_buffer[..*written_length] == expected_bytes[..] {
+                Ok(())
+            } else {
+                Err(format!(
+                    "value differs: no-heap {:?}, oracle {:?}",
+                    String::from_utf8_lossy(&output_buffer[..*written_length]),
+                    String::from_utf8_lossy(&expected_bytes),
+                ))
+            }
+        }
+        (Err(ReadTomlFieldError::RsLsfValueExceedsOutputBuffer), Ok(_))
+            if expected_bytes.as_ref().is_some_and(|expected| expected.len() > OUTPUT_BUFFER_BYTES) =>
+        {
+            Ok(())
+        }
+        (Err(ReadTomlFieldError::RsLsfFieldNotFound), Err(ReadTomlError::NotFound { .. })) => Ok(()),
+        (
+            Err(ReadTomlFieldError::RsLsfInvalidEscapeSequence | ReadTomlFieldError::RsLsfMalformedString),
+            Err(ReadTomlError::MalformedLine { .. }),
+        ) => Ok(()),
+        _ => Err(format!("no-heap {:?}, oracle {:?}", noheap_result, oracle_result)),
+    }
+}
+
+/// Run one query with one set of scan parameters and check every
+/// property: agreement with the oracle (up to the reader's bounds), the
+/// same answer through a source with random short reads, and the budget.
+fn check_one_query<
+    const OUTPUT_BUFFER_BYTES: usize,
+    const READ_CHUNK_BYTES: usize,
+    const MAX_LINE_BYTES: usize,
+    const MAX_BYTES_SCANNED: u64,
+>(
+    rng: &mut RsLsfTestRng,
+    generated_toml: &GeneratedToml,
+    case_files: &PropertyCaseFiles,
+    query_key: &str,
+) -> Result<(), String> {
+    let noheap_bytes = generated_toml.noheap_bytes();
+    let noheap_path = case_files.noheap_path.to_str().expect("temp path is UTF-8");
+    let noheap_result = read_single_line_string_field_from_toml_no_heap::<
+        OUTPUT_BUFFER_BYTES,
+        READ_CHUNK_BYTES,
+        MAX_LINE_BYTES,
+        MAX_BYTES_SCANNED,
+    >(noheap_path, query_key);
+
+    // Property: any byte source gives the same answer, and the scan never
+    // takes more than one byte past its budget.
+    let mut trickle_source = CountingTrickleSource {
+        remaining_bytes: &noheap_bytes,
+        rng,
+        bytes_handed_out: 0,
+    };
+    let trickle_result = read_single_line_string_field_from_source_no_heap::<
+        OUTPUT_BUFFER_BYTES,
+        READ_CHUNK_BYTES,
+        MAX_LINE_BYTES,
+        MAX_BYTES_SCANNED,
+    >(&mut trickle_source, query_key);
+    if trickle_result != noheap_result {
+        return Err(format!("short reads changed the answer: {:?} vs {:?}", trickle_result, noheap_result));
+    }
+    if trickle_source.bytes_handed_out > MAX_BYTES_SCANNED + 1 {
+        return Err(format!("read {} bytes on a {} byte budget", trickle_source.bytes_handed_out, MAX_BYTES_SCANNED));
+    }
+
+    // Errors explained by the caller's bounds: each must really apply.
+    match noheap_result {
+        Err(ReadTomlFieldError::RsLsfEmptyKey) if query_key.is_empty() => return Ok(()),
+        Err(ReadTomlFieldError::RsLsfKeyTooLong) if query_key.len() >= MAX_LINE_BYTES => return Ok(()),
+        Err(ReadTomlFieldError::RsLsfSafetyBudgetExhausted) if noheap_bytes.len() as u64 > MAX_BYTES_SCANNED => {
+            return Ok(());
+        }
+        Err(ReadTomlFieldError::RsLsfMatchingLineExceedsScanBuffer)
+            if generated_toml.longest_noheap_line() > MAX_LINE_BYTES =>
+        {
+            return Ok(());
+        }
+        _ => {}
+    }
+    if query_key.is_empty() || query_key.len() >= MAX_LINE_BYTES {
+        return Err(format!("invalid key {:?} gave {:?}", query_key, noheap_result));
+    }
+
+    let oracle_path = case_files.oracle_path.to_str().expect("temp path is UTF-8");
+    let oracle_result = read_toml_value(oracle_path, query_key);
+    // A table has no single-line value: the oracle's answer for it is not
+    // comparable, and the no-heap reader only has to fail cleanly.
+    if TABLE_POOL.contains(&query_key) && oracle_result.is_err() {
+        return match noheap_result {
+            Err(ReadTomlFieldError::RsLsfFieldNotFound) => Ok(()),
+            _ => Err(format!("table {:?} gave {:?}", query_key, noheap_result)),
+        };
+    }
+    check_against_oracle(&noheap_result, &oracle_result)
+}
+
+/// Run one query with scan parameters picked at random from a spread of
+/// instantiations (tiny buffers and budgets up to the defaults).
+fn check_query_with_random_parameters(
+    rng: &mut RsLsfTestRng,
+    generated_toml: &GeneratedToml,
+    case_files: &PropertyCaseFiles,
+    query_key: &str,
+) -> Result<(), String> {
+    match rng.below(6) {
+        0 => check_one_query::<1, 1, 8, 64>(rng, generated_toml, case_files, query_key),
+        1 => check_one_query::<4, 3, 16, 256>(rng, generated_toml, case_files, query_key),
+        2 => check_one_query::<16, 7, 48, 4096>(rng, generated_toml, case_files, query_key),
+        3 => check_one_query::<64, 64, 128, 1024>(rng, generated_toml, case_files, query_key),
+        4 => check_one_query::<300, 32, 700, 8192>(rng, generated_toml, case_files, query_key),
+        _ => check_one_query::<256, RSLSF_READ_CHUNK_BYTES, RSLSF_MAX_LINE_BYTES, RSLSF_MAX_BYTES_SCANNED>(
+            rng,
+            generated_toml,
+            case_files,
+            query_key,
+        ),
+    }
+}
+
+#[test]
+fn rslsf_property_matches_heap_reader_oracle() {
+    for case_seed in property_case_seeds() {
+        let mut rng = RsLsfTestRng::new(case_seed);
+        let generated_toml = GeneratedToml::generate(&mut rng);
+        let case_files = PropertyCaseFiles::write(
+            "oracle",
+            case_seed,
+            &generated_toml.noheap_bytes(),
+            &generated_toml.oracle_text(),
+        );
+
+        for _ in 0..8 {
+            let query_key = generated_toml.pick_query(&mut rng);
+            if let Err(failure) = check_query_with_random_parameters(&mut rng, &generated_toml, &case_files, &query_key)
+            {
+                panic!(
+                    "case seed {:#x}, key {:?}: {}\nno-heap file bytes: {:?}",
+                    case_seed,
+                    query_key,
+                    failure,
+                    String::from_utf8_lossy(&generated_toml.noheap_bytes()),
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn rslsf_property_survives_arbitrary_bytes() {
+    // Bytes biased toward TOML syntax, invalid UTF-8 included. There is no
+    // oracle for garbage: the reader must only answer, consistently and
+    // within its budget, whatever the bytes are.
+    const SYNTAX_BYTES: &[u8] = b"=\"'#[]. \t\r\n\\uU0aZ_-";
+    for case_seed in property_case_seeds() {
+        let mut rng = RsLsfTestRng::new(case_seed);
+        let byte_count = rng.below(600);
+        let garbage_bytes: Vec<u8> = (0..byte_count)
+            .map(|_| if rng.chance(70) { *rng.pick(SYNTAX_BYTES) } else { rng.next_u64() as u8 })
+            .collect();
+        let case_files = PropertyCaseFiles::write("garbage", case_seed, &garbage_bytes, "");
+        let noheap_path = case_files.noheap_path.to_str().expect("temp path is UTF-8");
+
+        for query_key in ["a", "a.b", "\"", "=", "[a]"] {
+            let file_result = read_single_line_string_field_from_toml_no_heap::<8, 5, 32, 512>(noheap_path, query_key);
+            let mut trickle_source = CountingTrickleSource {
+                remaining_bytes: &garbage_bytes,
+                rng: &mut rng,
+                bytes_handed_out: 0,
+            };
+            let trickle_result =
+                read_single_line_string_field_from_source_no_heap::<8, 5, 32, 512>(&mut trickle_source, query_key);
+            assert_eq!(trickle_result, file_result, "case seed {:#x}, key {:?}", case_seed, query_key);
+            assert!(
+                trickle_source.bytes_handed_out <= 512 + 1,
+                "case seed {:#x}: read {} bytes on a 512 byte budget",
+                case_seed,
+                trickle_source.bytes_handed_out,
+            );
+            if let Ok((_, written_length)) = file_result {
+                assert!(written_length <= 8, "case seed {:#x}", case_seed);
+            }
+        }
+    }
+}
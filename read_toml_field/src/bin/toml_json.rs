@@ -0,0 +1,107 @@
+//! Converts a TOML file to JSON, or a JSON file to TOML, on stdout.
+//!
+//! ```text
+//! toml_json config.toml           # config.toml as JSON
+//! toml_json settings.json         # settings.json as TOML
+//! toml_json --check config.toml   # list what a round trip would lose
+//! ```
+//!
+//! A file ending in `.json` is read as JSON, anything else as TOML. Exits
+//! with 0 on success, 1 if the file cannot be converted without loss (or,
+//! with `--check`, if anything would be lost) and 2 if it cannot be read.
+
+use std::process::ExitCode;
+
+use read_toml_field::toml_diagnostic::render_read_error;
+use read_toml_field::toml_document::TomlDocument;
+use read_toml_field::toml_json::{
+    check_json_round_trip, check_toml_round_trip, json_to_toml, parse_json, toml_to_json, TomlJsonLoss,
+};
+
+const USAGE: &str = "usage: toml_json [--check] <file.toml | file.json>";
+
+fn main() -> ExitCode {
+    let mut check_only = false;
+    let mut path = None;
+    for argument in std::env::args().skip(1) {
+        match argument.as_str() {
+            "--check" => check_only = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ if path.is_none() && !argument.starts_with('-') => path = Some(argument),
+            _ => {
+                eprintln!("{}", USAGE);
+                return ExitCode::from(2);
+            }
+        }
+    }
+    let Some(path) = path else {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    };
+
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("toml_json: cannot read '{}': {}", path, e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let converted = if path.ends_with(".json") {
+        let json = match parse_json(&content) {
+            Ok(json) => json,
+            Err(e) => {
+                eprintln!("toml_json: {}: {}", path, e);
+                return ExitCode::from(2);
+            }
+        };
+        if check_only {
+            return report_round_trip(&path, &check_json_round_trip(&json));
+        }
+        json_to_toml(&json)
+    } else {
+        let document = match TomlDocument::parse_strict(&content, None) {
+            Ok(document) => document,
+            Err(errors) => {
+                for error in &errors {
+                    eprintln!("{}", render_read_error(&path, error));
+                }
+                return ExitCode::from(2);
+            }
+        };
+        if check_only {
+            return report_round_trip(&path, &check_toml_round_trip(&document));
+        }
+        toml_to_json(&document).map(|json| format!("{}\n", json))
+    };
+
+    match converted {
+        Ok(text) => {
+            print!("{}", text);
+            ExitCode::SUCCESS
+        }
+        Err(losses) => {
+            for loss in &losses {
+                eprintln!("toml_json: {}: {}", path, loss);
+            }
+            eprintln!("toml_json: {} cannot be converted without losing the values above", path);
+            ExitCode::from(1)
+        }
+    }
+}
+
+/// Prints what a round trip of `path` would lose; fails if anything.
+fn report_round_trip(path: &str, losses: &[TomlJsonLoss]) -> ExitCode {
+    if losses.is_empty() {
+        println!("{}: round trip keeps every value", path);
+        return ExitCode::SUCCESS;
+    }
+    for loss in losses {
+        println!("{}: {}", path, loss);
+    }
+    println!("{}: round trip loses or changes {} value(s)", path, losses.len());
+    ExitCode::from(1)
+}
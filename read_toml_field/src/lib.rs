@@ -0,0 +1,21 @@
+//! Vanilla-Rust TOML field readers.
+//!
+//! The reader lives in its own module file so it can still be copied
+//! into other projects as a single `mod read_toml_field;`, while the
+//! demo binary and tests use it through this library target.
+//! `toml_document` builds on it to parse a file once and query it many
+//! times, `toml_include` merges several files (or files that include
+//! each other) into one document, `toml_schema` checks a document against
+//! a list of field rules, and `toml_layers` lets command-line and
+//! environment overrides and program defaults stand in for its values.
+//! `toml_diagnostic` renders values and errors as caret diagnostics
+//! pointing at the file, line and column to fix. `toml_json` converts
+//! documents to JSON and back for the `toml_json` binary.
+
+pub mod read_toml_field;
+pub mod toml_diagnostic;
+pub mod toml_document;
+pub mod toml_include;
+pub mod toml_json;
+pub mod toml_layers;
+pub mod toml_schema;
@@ -1,31 +1,69 @@
 
 
-mod read_toml_field;  // This declares the module and tells Rust to look for handle_gpg.rs
-use crate::read_toml_field::{
+use read_toml_field::read_toml_field::{
     read_field_from_toml,
     read_basename_fields_from_toml,
     read_single_line_string_field_from_toml,
     read_multi_line_toml_string,
     read_integer_array,
-    read_singleline_string_from_clearsigntoml,
-    read_multiline_string_from_clearsigntoml,
-}; 
+    read_bool_field_from_toml,
+    read_i64_field_from_toml,
+    set_read_toml_log_hook,
+    ReadTomlError,
+};
+use read_toml_field::toml_diagnostic::render_read_error;
+use read_toml_field::toml_document::TomlDocument;
+use read_toml_field::toml_layers::{parse_set_arguments, TomlLayers};
+use read_toml_field::read_toml_field::TomlValue;
 
-fn main() -> Result<(), String> {
-    let value = read_field_from_toml("test.toml", "fieldname");
+fn main() -> Result<(), ReadTomlError> {
+    // Show the readers' diagnostics on stderr
+    set_read_toml_log_hook(Some(|message| eprintln!("read_toml_field: {}", message)));
+
+    let value = read_field_from_toml("test.toml", "fieldname")?;
     println!("Field value -> {}", value);
-    
+
     // Read all prompt fields
-    let prompt_values = read_basename_fields_from_toml("config.toml", "prompt");
-    println!("Prompts: {:?}", prompt_values);
+    let prompt_fields = read_basename_fields_from_toml("config.toml", "prompt")?;
+    for (suffix, value) in &prompt_fields {
+        println!("prompt_{} = {}", suffix, value);
+    }
 
     let single_line = read_single_line_string_field_from_toml("config.toml", "promptsdir_1")?;
     let multi_line = read_multi_line_toml_string("config.toml", "multi_line")?;
     let integer_array = read_integer_array("config.toml", "schedule_duration_start_end")?;
+    let server_name = read_single_line_string_field_from_toml("config.toml", "server.name")?;
+    let log_enabled = read_bool_field_from_toml("config.toml", "log")?;
     
     println!("Single line: {}", single_line);
     println!("Multi line: {}", multi_line);
     println!("Numbers: {:?}", integer_array);
-    
+    println!("Server name: {}", server_name);
+    println!("Log enabled: {}", log_enabled);
+
+    // Parse the file once and read several fields from memory
+    let document = TomlDocument::load("config.toml")?;
+    println!("Top-level keys: {:?}", document.keys());
+    println!("Client name: {}", document.get_string("client.name")?);
+
+    // Per-host overrides: --set server.port=9090, or APP_SERVER_PORT=9090
+    let (overrides, _) = parse_set_arguments(std::env::args().skip(1))?;
+    let layers = TomlLayers::new("config.toml", document)
+        .with_overrides(overrides)
+        .with_process_environment("APP")
+        .with_default("server.timeout", TomlValue::Integer(30));
+    for resolved in layers.report(&["server.name", "server.port", "server.timeout", "log"]) {
+        println!("{}", resolved?);
+    }
+
+    let server_port = read_i64_field_from_toml("config.toml", "server.port")?;
+    println!("Server port: {}", server_port);
+
+    // Deliberately wrong type (server.name is a string), to show how a
+    // diagnostic points at the value to fix
+    if let Err(e) = read_i64_field_from_toml("config.toml", "server.name") {
+        eprintln!("{}", render_read_error("config.toml", &e));
+    }
+
     Ok(())
 }
@@ -11,350 +11,1923 @@ use crate::read_toml_field::{
     read_single_line_string_field_from_toml,
     read_multi_line_toml_string,
     read_integer_array,
+    read_bool_field_from_toml,
+    set_read_toml_log_hook,
+    ReadTomlError,
     read_singleline_string_from_clearsigntoml,
     read_multiline_string_from_clearsigntoml,
 }; 
 
-fn main() -> Result<(), String> {
-    let value = read_field_from_toml("test.toml", "fieldname");
+fn main() -> Result<(), ReadTomlError> {
+    // Show the readers' diagnostics on stderr
+    set_read_toml_log_hook(Some(|message| eprintln!("read_toml_field: {}", message)));
+
+    let value = read_field_from_toml("test.toml", "fieldname")?;
     println!("Field value -> {}", value);
-    
+
     // Read all prompt fields
-    let prompt_values = read_basename_fields_from_toml("config.toml", "prompt");
-    println!("Prompts: {:?}", prompt_values);
+    let prompt_fields = read_basename_fields_from_toml("config.toml", "prompt")?;
+    for (suffix, value) in &prompt_fields {
+        println!("prompt_{} = {}", suffix, value);
+    }
 
     let single_line = read_single_line_string_field_from_toml("config.toml", "promptsdir_1")?;
     let multi_line = read_multi_line_toml_string("config.toml", "multi_line")?;
     let integer_array = read_integer_array("config.toml", "schedule_duration_start_end")?;
+    let server_name = read_single_line_string_field_from_toml("config.toml", "server.name")?;
+    let log_enabled = read_bool_field_from_toml("config.toml", "log")?;
     
     println!("Single line: {}", single_line);
     println!("Multi line: {}", multi_line);
     println!("Numbers: {:?}", integer_array);
+    println!("Server name: {}", server_name);
+    println!("Log enabled: {}", log_enabled);
     
     Ok(())
 }
 
 */
 
+use std::fmt;
 use std::fs::File;
-use std::io::{self, BufRead, Read};
+use std::io::Read;
 // use std::path::Path;
 use std::process::Command;
+use std::sync::Mutex;
+
+/// Function that receives the diagnostics of the readers, see
+/// [`set_read_toml_log_hook`].
+pub type ReadTomlLogHook = fn(&str);
+
+/// Where reader diagnostics go; `None` (the default) discards them.
+static READ_TOML_LOG_HOOK: Mutex<Option<ReadTomlLogHook>> = Mutex::new(None);
+
+/// Sets (or with `None`, removes) the function that receives diagnostic
+/// messages from the readers, such as lines that were skipped because
+/// they are not valid TOML. Nothing is printed unless a hook is set.
+///
+/// example use:
+///     set_read_toml_log_hook(Some(|message| eprintln!("read_toml: {}", message)));
+///
+pub fn set_read_toml_log_hook(hook: Option<ReadTomlLogHook>) {
+    if let Ok(mut current_hook) = READ_TOML_LOG_HOOK.lock() {
+        *current_hook = hook;
+    }
+}
+
+/// Sends a diagnostic message to the log hook, if one is set.
+pub(crate) fn log_read_toml(message: &str) {
+    if let Ok(current_hook) = READ_TOML_LOG_HOOK.lock()
+        && let Some(hook) = *current_hook
+    {
+        hook(message);
+    }
+}
+
+/// Error type for all readers in this module.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReadTomlError {
+    /// The file could not be opened or read
+    OpenFailed { path: String, message: String },
+    /// The requested field name is not a valid key path
+    InvalidFieldName { field: String, message: String },
+    /// The field does not exist in the file
+    NotFound { field: String },
+    /// The line holding the field is not valid TOML
+    MalformedLine { field: String, line: usize, column: usize, message: String },
+    /// The field exists but holds a different type than requested
+    TypeMismatch { field: String, expected: &'static str, found: &'static str },
+    /// The field is defined more than once
+    DuplicateKey { field: String, first_line: usize, duplicate_line: usize },
+    /// The clearsigned file could not be verified with GPG
+    VerificationFailed { path: String, message: String },
+    /// A line that is not a table header or a `key = value` entry
+    InvalidLine { line: usize, column: usize, message: String },
+    /// A table is defined twice, or a key is used both as a value and as a table
+    TableRedefined { table: String, first_line: usize, duplicate_line: usize },
+    /// A key that is not in the allow-list of a strict read
+    UnknownKey { field: String, line: usize },
+    /// A numbered field whose suffix does not fit a `1..N` sequence
+    NotInSequence { field: String, line: usize, message: String },
+    /// A file includes itself, directly or through other files
+    IncludeCycle { files: Vec<String> },
+}
+
+impl fmt::Display for ReadTomlError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadTomlError::OpenFailed { path, message } => {
+                write!(formatter, "Failed to open file '{}': {}", path, message)
+            }
+            ReadTomlError::InvalidFieldName { field, message } => {
+                write!(formatter, "Invalid field name '{}': {}", field, message)
+            }
+            ReadTomlError::NotFound { field } => write!(formatter, "Field '{}' not found", field),
+            ReadTomlError::MalformedLine { field, line, column, message } => {
+                write!(formatter, "Invalid value for field '{}' at line {}, column {}: {}", field, line, column, message)
+            }
+            ReadTomlError::TypeMismatch { field, expected, found } => {
+                write!(formatter, "Field '{}' is {}, expected {}", field, with_article(found), with_article(expected))
+            }
+            ReadTomlError::DuplicateKey { field, first_line, duplicate_line } => {
+                write!(formatter, "Field '{}' is defined on line {} and again on line {}", field, first_line, duplicate_line)
+            }
+            ReadTomlError::VerificationFailed { path, message } => {
+                write!(formatter, "GPG verification failed for file '{}': {}", path, message)
+            }
+            ReadTomlError::InvalidLine { line, column, message } => {
+                write!(formatter, "Invalid TOML at line {}, column {}: {}", line, column, message)
+            }
+            ReadTomlError::TableRedefined { table, first_line, duplicate_line } => {
+                write!(formatter, "Table '{}' is defined on line {} and again on line {}", table, first_line, duplicate_line)
+            }
+            ReadTomlError::UnknownKey { field, line } => {
+                write!(formatter, "Unknown key '{}' on line {}", field, line)
+            }
+            ReadTomlError::NotInSequence { field, line, message } => {
+                write!(formatter, "Field '{}' on line {} is out of sequence: {}", field, line, message)
+            }
+            ReadTomlError::IncludeCycle { files } => {
+                write!(formatter, "TOML files include each other: {}", files.join(" -> "))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReadTomlError {}
+
+/// A type name with its indefinite article: "an integer", "a string".
+pub(crate) fn with_article(type_name: &str) -> String {
+    match type_name.chars().next() {
+        Some('a' | 'e' | 'i' | 'o' | 'u') => format!("an {}", type_name),
+        _ => format!("a {}", type_name),
+    }
+}
+
+/// Where a value or an error sits in a TOML file: the file (when known),
+/// the 1-based line and column (in characters) of its start, and the
+/// byte range it covers. Displayed as `config.toml:42:17`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TomlSpan {
+    pub file: Option<String>,
+    pub line: usize,
+    pub column: usize,
+    pub byte_range: std::ops::Range<usize>,
+}
+
+impl TomlSpan {
+    /// The span of `byte_range` in `content`, the text of `file`.
+    pub fn new(file: Option<&str>, content: &str, byte_range: std::ops::Range<usize>) -> TomlSpan {
+        let (line, column) = line_and_column(content, byte_range.start);
+        TomlSpan { file: file.map(str::to_string), line, column, byte_range }
+    }
+
+    /// The span of the text on `line` (1-based), without its indentation
+    /// and line break, or `None` if `content` has fewer lines.
+    pub fn of_line(file: Option<&str>, content: &str, line: usize) -> Option<TomlSpan> {
+        let line_start = byte_offset_of_position(content, line, 1)?;
+        let line_text = content[line_start..].split('\n').next().unwrap_or("");
+        let indentation = line_text.len() - line_text.trim_start().len();
+        let text_end = line_start + line_text.trim_end().len();
+        let text_start = (line_start + indentation).min(text_end);
+        Some(TomlSpan::new(file, content, text_start..text_end))
+    }
+}
+
+impl fmt::Display for TomlSpan {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(formatter, "{}:{}:{}", file, self.line, self.column),
+            None => write!(formatter, "{}:{}", self.line, self.column),
+        }
+    }
+}
+
+impl ReadTomlError {
+    /// Where in `content` (the text of the file at `path`) this error
+    /// points, for a diagnostic such as `config.toml:42:17`.
+    ///
+    /// Errors with a line and column point at that character; errors with
+    /// only a line cover that line; a type mismatch covers the value of its
+    /// field. Errors that are not about a place in the file (`NotFound`,
+    /// `OpenFailed`, ...) have no span.
+    pub fn span(&self, path: &str, content: &str) -> Option<TomlSpan> {
+        match self {
+            ReadTomlError::MalformedLine { line, column, .. } | ReadTomlError::InvalidLine { line, column, .. } => {
+                let start = byte_offset_of_position(content, *line, *column)?;
+                let end = content[start..].chars().next().map_or(start, |character| start + character.len_utf8());
+                Some(TomlSpan::new(Some(path), content, start..end))
+            }
+            ReadTomlError::DuplicateKey { duplicate_line: line, .. }
+            | ReadTomlError::TableRedefined { duplicate_line: line, .. }
+            | ReadTomlError::UnknownKey { line, .. }
+            | ReadTomlError::NotInSequence { line, .. } => TomlSpan::of_line(Some(path), content, *line),
+            ReadTomlError::TypeMismatch { field, .. } => {
+                let field_path = parse_field_path(field).ok()?;
+                let entry = find_toml_entry(content, field, &field_path).ok()?;
+                Some(entry_span(path, content, &entry))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// The span of an entry's value as written, quotes and brackets included.
+fn entry_span(path: &str, content: &str, entry: &ScannedTomlEntry) -> TomlSpan {
+    TomlSpan::new(Some(path), content, entry.value_offset..entry.value_offset + entry.raw_value.len())
+}
+
+/// Byte offset of a 1-based line and column (in characters), the reverse
+/// of [`line_and_column`]. A column just past the end of the line is the
+/// offset of the line break.
+pub(crate) fn byte_offset_of_position(content: &str, line: usize, column: usize) -> Option<usize> {
+    let line_start = if line == 1 {
+        0
+    } else {
+        content.match_indices('\n').nth(line.checked_sub(2)?)?.0 + 1
+    };
+    let line_text = content[line_start..].split('\n').next().unwrap_or("");
+    let column_offset = match line_text.char_indices().nth(column.checked_sub(1)?) {
+        Some((offset, _)) => offset,
+        None if column == line_text.chars().count() + 1 => line_text.len(),
+        None => return None,
+    };
+    Some(line_start + column_offset)
+}
 
-/// The function reads a single line from a TOML file that starts with a specified field name
-/// and ends with a value. The function returns an empty string if the field is not found, and
-/// does not panic or unwrap in case of errors. The function uses only standard Rust libraries
-/// and does not introduce unnecessary dependencies.
+/// The function reads a single field from a TOML file and returns its value
+/// as text. String values have their quotes removed and escapes decoded;
+/// other values (numbers, booleans, arrays) are returned as written.
+/// The function does not panic or unwrap in case of errors, and uses only
+/// standard Rust libraries.
+///
+/// `field_name` may be a plain key (`fieldname`, root table only), a dotted
+/// path into a table (`server.port`), or an index into an array of tables
+/// (`products[1].name`); see [`parse_toml_lookup_path`].
 ///
 /// design:
-/// 0. start with an empty string to return by default
 /// 1. get file at path
 /// 2. open as text
-/// 3. iterate through rows
-/// 4. look for filed name as start of string the " = "
-/// 5. grab that whole row of text
-/// 6. remove "fieldname = " from the beginning
-/// 7. remove '" ' and trailing spaces from the end
-/// 8. return that string, if any
-/// by default, return an empty string, if anything goes wrong, 
-/// handle the error, and return an empty string
+/// 3. walk the `key = value` entries, tracking `[table]` headers
+/// 4. compare each entry's full path with the requested path
+/// 5. take the raw value text of the matching entry
+/// 6. remove the quotes and decode escapes of a string value
+/// 7. return that string
+///
+/// A missing field, an unreadable file and an empty value (`field = ""`,
+/// which returns `Ok("")`) can be told apart through [`ReadTomlError`].
 ///
 /// requires:
 /// use std::fs::File;
-/// use std::io::{self, BufRead};
+/// use std::io::Read;
 ///
 /// example use:
-///     let value = read_field_from_toml("test.toml", "fieldname");
+///     let value = read_field_from_toml("test.toml", "fieldname")?;
 ///
-pub fn read_field_from_toml(path: &str, field_name: &str) -> String {
-    // Validate input parameters
-    if path.is_empty() || field_name.is_empty() {
-        println!("Error: Empty path or field name provided");
-        return String::new();
-    }
-
+pub fn read_field_from_toml(path: &str, field_name: &str) -> Result<String, ReadTomlError> {
     // Verify file extension
     if !path.to_lowercase().ends_with(".toml") {
-        println!("Warning: File does not have .toml extension: {}", path);
+        log_read_toml(&format!("File does not have .toml extension: {}", path));
     }
 
-    // Debug print statement
-    println!("Attempting to open file at path: {}", path);
+    let field_path = parse_field_path(field_name)?;
+    let content = read_toml_file_to_string(path)?;
+    let entry = find_toml_entry(&content, field_name, &field_path)?;
 
-    // Open the file at the specified path
-    let file = match File::open(path) {
-        Ok(file) => file,
-        Err(e) => {
-            // More detailed error reporting
-            println!("Failed to open file at path: {}. Error: {}", path, e);
-            return String::new();
-        },
-    };
+    log_read_toml(&format!("Found field '{}' on line {}", field_name, entry.line_number));
 
-    // Debug print statement
-    println!("Successfully opened file at path: {}", path);
+    entry_string_text(&content, field_name, &entry)
+}
 
-    // Create a buffered reader to read the file line by line
-    let reader = io::BufReader::new(file);
+/// Reads all fields from a TOML file that share a common base name (prefix before underscore)
+/// and returns `(suffix, value)` pairs. Returns an empty vector if no matching fields are found.
+///
+/// The pairs are in natural order of their suffixes, so `prompt_2` comes
+/// before `prompt_10`. Values are parsed as TOML, so multi-line strings and
+/// arrays (also spread over several lines) are returned whole.
+/// Only keys of the root table are matched; `#` comments after a value are ignored.
+/// Lines that are not valid TOML are skipped and reported to the log hook,
+/// unless the line holds one of the matching fields.
+///
+/// # Arguments
+/// * `path` - Path to the TOML file
+/// * `base_name` - Base name to search for (e.g., "prompt" will match "prompt_1", "prompt_2", etc.)
+///
+/// # Returns
+/// * `Result<Vec<(String, TomlValue)>, ReadTomlError>` - Suffix and value of all fields matching the base name
+///
+/// # Example
+/// ```
+/// # use read_toml_field::read_toml_field::{read_basename_fields_from_toml, TomlValue};
+/// let fields = read_basename_fields_from_toml("config.toml", "prompt").unwrap();
+/// // For TOML content:
+/// // prompt_1 = "filename1.txt"
+/// // prompt_2 = "filename2.txt"
+/// // ...
+/// assert_eq!(fields[0], ("1".to_string(), TomlValue::String("filename1.txt".to_string())));
+/// ```
+pub fn read_basename_fields_from_toml(path: &str, base_name: &str) -> Result<Vec<(String, TomlValue)>, ReadTomlError> {
+    let fields = read_numbered_entries(path, base_name)?;
+    Ok(fields.into_iter().map(|(suffix, value, _)| (suffix, value)).collect())
+}
 
-    // Keep track of line numbers for better error reporting
-    let mut line_number = 0;
+/// The fields of [`read_basename_fields_from_toml`] with the line each
+/// one is defined on.
+fn read_numbered_entries(path: &str, base_name: &str) -> Result<Vec<(String, TomlValue, usize)>, ReadTomlError> {
+    if base_name.is_empty() {
+        return Err(ReadTomlError::InvalidFieldName {
+            field: base_name.to_string(),
+            message: "empty base name".to_string(),
+        });
+    }
 
-    // Iterate through each line in the file
-    for line_result in reader.lines() {
-        line_number += 1;
+    let content = read_toml_file_to_string(path)?;
+    let base_name_with_underscore = format!("{}_", base_name);
+    let mut fields: Vec<(String, TomlValue, usize)> = Vec::new();
 
-        // Handle line reading errors
-        let line = match line_result {
-            Ok(line) => line,
+    // Process each entry of the root table
+    let mut scanner = TomlEntryScanner::new(&content);
+    while let Some(entry_result) = scanner.next_entry() {
+        let entry = match entry_result {
+            Ok(entry) => entry,
             Err(e) => {
-                println!("Error reading line {}: {}", line_number, e);
+                if let Some([TomlPathSegment::Key(key)]) = e.full_path.as_deref()
+                    && key.starts_with(&base_name_with_underscore)
+                {
+                    return Err(ReadTomlError::MalformedLine {
+                        field: key.clone(),
+                        line: e.line,
+                        column: e.column,
+                        message: e.message,
+                    });
+                }
+                log_read_toml(&format!("Skipping malformed TOML: {}", e));
                 continue;
             }
         };
 
-        // Skip empty lines and comments
-        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+        // Check if the key starts with base_name_
+        let key = match entry.full_path.as_slice() {
+            [TomlPathSegment::Key(key)] => key,
+            _ => continue,
+        };
+        let Some(suffix) = key.strip_prefix(&base_name_with_underscore) else {
             continue;
+        };
+
+        if let Some((_, _, first_line)) = fields.iter().find(|(known_suffix, _, _)| known_suffix == suffix) {
+            return Err(ReadTomlError::DuplicateKey {
+                field: key.clone(),
+                first_line: *first_line,
+                duplicate_line: entry.line_number,
+            });
         }
 
-        // Debug print statement
-        println!("Processing line {}: {}", line_number, line);
+        let value = parse_toml_value(entry.raw_value).map_err(|(offset, message)| {
+            let (line, column) = line_and_column(&content, entry.value_offset + offset);
+            ReadTomlError::MalformedLine { field: key.clone(), line, column, message }
+        })?;
+        fields.push((suffix.to_string(), value, entry.line_number));
+    }
 
-        // Check if line starts with field name
-        if line.trim_start().starts_with(field_name) {
-            // Debug print statement
-            println!("Found field '{}' on line {}", field_name, line_number);
+    // Sort by suffix, comparing runs of digits as numbers
+    fields.sort_by(|(a, _, _), (b, _, _)| natural_order(a, b));
+    Ok(fields)
+}
 
-            // Split the line by '=' and handle malformed lines
-            let parts: Vec<&str> = line.splitn(2, '=').collect();
-            if parts.len() != 2 {
-                println!("Malformed TOML line {} - missing '=': {}", line_number, line);
-                continue;
+/// Reads the fields `base_name_1` to `base_name_N` from a TOML file and
+/// returns their values in that order, for lists such as `prompt_1`,
+/// `prompt_2`, ... that must not have holes.
+///
+/// A missing number is reported as [`ReadTomlError::NotFound`] for the
+/// first absent field; a suffix that is not a plain number from 1 up
+/// (`prompt_x`, `prompt_0`, `prompt_01`) is [`ReadTomlError::NotInSequence`].
+///
+/// # Example
+/// ```
+/// # use read_toml_field::read_toml_field::read_basename_sequence_from_toml;
+/// let prompts = read_basename_sequence_from_toml("config.toml", "prompt").unwrap();
+/// assert_eq!(prompts.len(), 3);
+/// ```
+pub fn read_basename_sequence_from_toml(path: &str, base_name: &str) -> Result<Vec<TomlValue>, ReadTomlError> {
+    let fields = read_numbered_entries(path, base_name)?;
+
+    let mut values = Vec::with_capacity(fields.len());
+    for (suffix, value, line) in fields {
+        let number = match suffix.parse::<usize>() {
+            Ok(number) if number > 0 && suffix.starts_with(|c: char| c.is_ascii_digit() && c != '0') => number,
+            _ => {
+                return Err(ReadTomlError::NotInSequence {
+                    field: format!("{}_{}", base_name, suffix),
+                    line,
+                    message: "the suffix is not a number from 1 up".to_string(),
+                });
+            }
+        };
+        // Natural order puts the numbers first and ascending, so the first
+        // number that is not the next one marks the gap
+        if number != values.len() + 1 {
+            return Err(ReadTomlError::NotFound { field: format!("{}_{}", base_name, values.len() + 1) });
+        }
+        values.push(value);
+    }
+
+    Ok(values)
+}
+
+/// Compares two strings with runs of ASCII digits compared by their
+/// numeric value, so `"2" < "10"` and `"a2" < "a10"`. Equal numbers with
+/// different leading zeros fall back to the plain text order.
+fn natural_order(a: &str, b: &str) -> std::cmp::Ordering {
+    let (mut a_rest, mut b_rest) = (a, b);
+    loop {
+        match (a_rest.chars().next(), b_rest.chars().next()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return std::cmp::Ordering::Less,
+            (Some(_), None) => return std::cmp::Ordering::Greater,
+            (Some(a_char), Some(b_char)) if a_char.is_ascii_digit() && b_char.is_ascii_digit() => {
+                let a_len = a_rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(a_rest.len());
+                let b_len = b_rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(b_rest.len());
+                let a_digits = a_rest[..a_len].trim_start_matches('0');
+                let b_digits = b_rest[..b_len].trim_start_matches('0');
+                let order = a_digits.len().cmp(&b_digits.len()).then_with(|| a_digits.cmp(b_digits));
+                if order != std::cmp::Ordering::Equal {
+                    return order;
+                }
+                a_rest = &a_rest[a_len..];
+                b_rest = &b_rest[b_len..];
+            }
+            (Some(a_char), Some(b_char)) => {
+                if a_char != b_char {
+                    return a_char.cmp(&b_char);
+                }
+                a_rest = &a_rest[a_char.len_utf8()..];
+                b_rest = &b_rest[b_char.len_utf8()..];
+            }
+        }
+    }
+}
+
+/// Reads a single-line string field from a TOML file.
+///
+/// `field_name` may be a dotted path such as `server.name`; see
+/// [`parse_toml_lookup_path`]. Basic strings (`"a \"b\""`) have their
+/// escapes decoded and literal strings (`'C:\path'`) are returned as
+/// written; unquoted values are returned as plain text.
+///
+/// # Arguments
+/// * `path` - Path to the TOML file
+/// * `field_name` - Name of the field to read
+///
+/// # Returns
+/// * `Result<String, ReadTomlError>` - The field value or the reason it could not be read
+pub fn read_single_line_string_field_from_toml(path: &str, field_name: &str) -> Result<String, ReadTomlError> {
+    let content = read_toml_file_to_string(path)?;
+    let field_path = parse_field_path(field_name)?;
+    let entry = find_toml_entry(&content, field_name, &field_path)?;
+
+    entry_string_text(&content, field_name, &entry)
+}
+
+/// Reads a multi-line string field (triple-quoted) from a TOML file.
+///
+/// Both `"""` basic strings (escapes and line-ending backslashes are
+/// decoded) and `'''` literal strings are accepted. Each line of the result
+/// is trimmed, so indented blocks such as GPG keys come back flush left;
+/// use [`read_toml_value`] for the exact string.
+///
+/// # Arguments
+/// * `path` - Path to the TOML file
+/// * `field_name` - Name of the field to read
+///
+/// # Returns
+/// * `Result<String, ReadTomlError>` - The concatenated multi-line value or the reason it could not be read
+pub fn read_multi_line_toml_string(path: &str, field_name: &str) -> Result<String, ReadTomlError> {
+    let content = read_toml_file_to_string(path)?;
+    let field_path = parse_field_path(field_name)?;
+    let entry = find_toml_entry(&content, field_name, &field_path)?;
+
+    // Decode the content between the triple quotes
+    check_multi_line_string(field_name, entry.raw_value)?;
+    let multi_line_content = entry_string_text(&content, field_name, &entry)?;
+
+    Ok(trim_multi_line_text(&multi_line_content))
+}
+
+/// Checks that a raw value is a `"""` or `'''` string.
+pub(crate) fn check_multi_line_string(field_name: &str, raw_value: &str) -> Result<(), ReadTomlError> {
+    if raw_value.starts_with("\"\"\"") || raw_value.starts_with("'''") {
+        return Ok(());
+    }
+    let found = match parse_toml_value(raw_value) {
+        Ok(TomlValue::String(_)) => "single-line string",
+        Ok(value) => value.type_name(),
+        Err(_) => "malformed value",
+    };
+    Err(ReadTomlError::TypeMismatch {
+        field: field_name.to_string(),
+        expected: "multi-line string",
+        found,
+    })
+}
+
+/// Trims each line and the whole text, as [`read_multi_line_toml_string`] does.
+pub(crate) fn trim_multi_line_text(text: &str) -> String {
+    text.lines()
+        .map(|line| line.trim())
+        .collect::<Vec<&str>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Reads an array of integers from a TOML file into a Vec<u64>.
+///
+/// The array may span several lines, end with a trailing comma and have
+/// `#` comments between its elements. Integers follow the TOML rules
+/// (`1_000`, `0xff`, ...) and must not be negative.
+///
+/// # Arguments
+/// * `path` - Path to the TOML file
+/// * `field_name` - Name of the field to read
+///
+/// # Returns
+/// * `Result<Vec<u64>, ReadTomlError>` - The vector of integers or the reason it could not be read
+pub fn read_integer_array(path: &str, field_name: &str) -> Result<Vec<u64>, ReadTomlError> {
+    unsigned_integers(field_name, read_i64_array_from_toml(path, field_name)?)
+}
+
+/// Converts the integers of an array to `u64`, rejecting negative ones.
+pub(crate) fn unsigned_integers(field_name: &str, numbers: Vec<i64>) -> Result<Vec<u64>, ReadTomlError> {
+    numbers
+        .into_iter()
+        .map(|number| {
+            u64::try_from(number).map_err(|_| ReadTomlError::TypeMismatch {
+                field: field_name.to_string(),
+                expected: "non-negative integer",
+                found: "negative integer",
+            })
+        })
+        .collect()
+}
+
+/// One step of a TOML lookup path: a key name, or an index into an
+/// array of tables.
+///
+/// `server.port` is `[Key("server"), Key("port")]`, and
+/// `products[1].name` is `[Key("products"), Index(1), Key("name")]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TomlPathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parses a lookup path such as `port`, `server.port`, `"a.b".c` or
+/// `products[1].name` into its segments.
+///
+/// A path with a single key only matches keys in the root table, so
+/// `name` and `server.name` are different fields. Indexes count the
+/// `[[products]]` headers of an array of tables, starting at 0.
+///
+/// # Arguments
+/// * `lookup_path` - Dotted key path, optionally with `[index]` steps
+///
+/// # Returns
+/// * `Result<Vec<TomlPathSegment>, String>` - The path segments or an error message
+pub fn parse_toml_lookup_path(lookup_path: &str) -> Result<Vec<TomlPathSegment>, String> {
+    let (segments, rest) = parse_dotted_key(lookup_path, true)?;
+    if !rest.trim().is_empty() {
+        return Err(format!("Unexpected text after key path: '{}'", rest.trim()));
+    }
+    Ok(segments)
+}
+
+/// Writes a path back as a lookup path string, the reverse of
+/// [`parse_toml_lookup_path`]: keys that are not bare are quoted, so
+/// `[Key("a.b"), Index(0), Key("c")]` becomes `"a.b"[0].c`.
+pub fn format_toml_path(path: &[TomlPathSegment]) -> String {
+    let mut formatted = String::new();
+    for segment in path {
+        match segment {
+            TomlPathSegment::Key(key) => {
+                if !formatted.is_empty() {
+                    formatted.push('.');
+                }
+                let is_bare = !key.is_empty()
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+                if is_bare {
+                    formatted.push_str(key);
+                } else {
+                    formatted.push('"');
+                    for character in key.chars() {
+                        match character {
+                            '"' => formatted.push_str("\\\""),
+                            '\\' => formatted.push_str("\\\\"),
+                            '\n' => formatted.push_str("\\n"),
+                            '\t' => formatted.push_str("\\t"),
+                            other => formatted.push(other),
+                        }
+                    }
+                    formatted.push('"');
+                }
+            }
+            TomlPathSegment::Index(index) => formatted.push_str(&format!("[{}]", index)),
+        }
+    }
+    formatted
+}
+
+/// Parses a field name with [`parse_toml_lookup_path`] for the readers.
+pub(crate) fn parse_field_path(field_name: &str) -> Result<Vec<TomlPathSegment>, ReadTomlError> {
+    parse_toml_lookup_path(field_name).map_err(|message| ReadTomlError::InvalidFieldName {
+        field: field_name.to_string(),
+        message,
+    })
+}
+
+/// Reads a whole TOML file into memory.
+pub(crate) fn read_toml_file_to_string(path: &str) -> Result<String, ReadTomlError> {
+    let open_failed = |e: std::io::Error| ReadTomlError::OpenFailed {
+        path: path.to_string(),
+        message: e.to_string(),
+    };
+
+    let mut file = File::open(path).map_err(open_failed)?;
+    let mut content = String::new();
+    file.read_to_string(&mut content).map_err(open_failed)?;
+
+    Ok(content)
+}
+
+/// Returns the entry of `content` whose full path is `field_path`.
+///
+/// Lines that cannot be read as TOML are skipped (and reported to the log
+/// hook), the same way the line readers always skipped lines they did not
+/// understand, unless the bad line is the requested field itself:
+/// `name = "x" oops` is an error for `name`, not a missing field. A field
+/// defined twice is an error, as in TOML.
+fn find_toml_entry<'a>(
+    content: &'a str,
+    field_name: &str,
+    field_path: &[TomlPathSegment],
+) -> Result<ScannedTomlEntry<'a>, ReadTomlError> {
+    let mut found_entry: Option<ScannedTomlEntry<'a>> = None;
+
+    let mut scanner = TomlEntryScanner::new(content);
+    while let Some(entry_result) = scanner.next_entry() {
+        match entry_result {
+            Ok(entry) if entry.full_path == field_path => {
+                if let Some(first_entry) = &found_entry {
+                    return Err(ReadTomlError::DuplicateKey {
+                        field: field_name.to_string(),
+                        first_line: first_entry.line_number,
+                        duplicate_line: entry.line_number,
+                    });
+                }
+                found_entry = Some(entry);
             }
+            Err(e) if e.full_path.as_deref() == Some(field_path) => {
+                return Err(ReadTomlError::MalformedLine {
+                    field: field_name.to_string(),
+                    line: e.line,
+                    column: e.column,
+                    message: e.message,
+                });
+            }
+            Err(e) => log_read_toml(&format!("Skipping malformed TOML: {}", e)),
+            Ok(_) => {}
+        }
+    }
 
-            let key = parts[0].trim();
-            let value = parts[1].trim();
+    found_entry.ok_or_else(|| ReadTomlError::NotFound { field: field_name.to_string() })
+}
 
-            // Verify exact field name match (avoiding partial matches)
-            if key != field_name {
+/// The text of an entry's value, decoded with [`string_value_text`].
+fn entry_string_text(content: &str, field_name: &str, entry: &ScannedTomlEntry) -> Result<String, ReadTomlError> {
+    string_value_text(entry.raw_value).map_err(|message| {
+        let (line, column) = line_and_column(content, entry.value_offset);
+        ReadTomlError::MalformedLine { field: field_name.to_string(), line, column, message }
+    })
+}
+
+/// 1-based line and column (counted in characters) of a byte offset.
+pub(crate) fn line_and_column(content: &str, byte_offset: usize) -> (usize, usize) {
+    let before = &content[..byte_offset];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+/// A line that [`TomlEntryScanner`] could not read.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TomlScanError {
+    /// Full path of the entry on the bad line, when its key could be read.
+    pub(crate) full_path: Option<Vec<TomlPathSegment>>,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for TomlScanError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// A `key = value` entry found by [`TomlEntryScanner`], with the value
+/// still as raw TOML text (quotes, brackets and all).
+pub(crate) struct ScannedTomlEntry<'a> {
+    /// Table path followed by the key path, e.g. `port` under `[server]`
+    /// is `server.port`.
+    pub(crate) full_path: Vec<TomlPathSegment>,
+    /// Number of segments at the end of `full_path` written in the key
+    /// itself (2 for `a.b = 1`), the rest come from the table header.
+    pub(crate) key_segment_count: usize,
+    /// Raw value text; spans several lines for arrays and `"""` strings.
+    pub(crate) raw_value: &'a str,
+    /// 1-based line on which the key appears.
+    pub(crate) line_number: usize,
+    /// Byte offset of `raw_value` in the scanned text.
+    pub(crate) value_offset: usize,
+}
+
+/// A table header or an entry, as returned by [`TomlEntryScanner::next_item`].
+pub(crate) enum ScannedTomlItem<'a> {
+    /// A `[table]` or `[[array-of-tables]]` header. For an array of tables
+    /// the path ends with the index of the element the header starts.
+    TableHeader {
+        table_path: Vec<TomlPathSegment>,
+        line_number: usize,
+    },
+    Entry(ScannedTomlEntry<'a>),
+}
+
+/// Walks the `key = value` entries of a TOML text in order, tracking the
+/// current `[table]` and `[[array-of-tables]]` header so each entry is
+/// reported with its full path.
+///
+/// Multi-line strings and arrays are skipped as a whole, so their inner
+/// lines are never mistaken for keys or headers.
+pub(crate) struct TomlEntryScanner<'a> {
+    content: &'a str,
+    /// Byte offset of the next unread line.
+    position: usize,
+    /// 1-based line number of `position`.
+    line_number: usize,
+    /// Path of the current table, or `None` after a header that could not
+    /// be read (entries below it are skipped until the next good header).
+    current_table: Option<Vec<TomlPathSegment>>,
+    /// Number of elements seen so far for each `[[array-of-tables]]`.
+    array_table_counts: Vec<(Vec<TomlPathSegment>, usize)>,
+}
+
+impl<'a> TomlEntryScanner<'a> {
+    pub(crate) fn new(content: &'a str) -> Self {
+        TomlEntryScanner {
+            content,
+            position: 0,
+            line_number: 1,
+            current_table: Some(Vec::new()),
+            array_table_counts: Vec::new(),
+        }
+    }
+
+    /// Returns the next entry, `Some(Err(..))` for a line that is not valid
+    /// TOML (the scanner has already moved past it), or `None` at the end.
+    pub(crate) fn next_entry(&mut self) -> Option<Result<ScannedTomlEntry<'a>, TomlScanError>> {
+        loop {
+            match self.next_item()? {
+                Ok(ScannedTomlItem::TableHeader { .. }) => continue,
+                Ok(ScannedTomlItem::Entry(entry)) => return Some(Ok(entry)),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Like [`TomlEntryScanner::next_entry`], but also returns the table
+    /// headers.
+    pub(crate) fn next_item(&mut self) -> Option<Result<ScannedTomlItem<'a>, TomlScanError>> {
+        while self.position < self.content.len() {
+            let line_start = self.position;
+            let line_end = self.end_of_line(line_start);
+            let trimmed = self.content[line_start..line_end].trim();
+
+            // Skip empty lines and comments
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                self.advance_past_line(line_end);
                 continue;
             }
 
-            // Handle empty values
-            if value.is_empty() {
-                println!("Warning: Empty value found for field '{}'", field_name);
-                return String::new();
+            if trimmed.starts_with('[') {
+                let header_start = line_end - self.content[line_start..line_end].trim_start().len();
+                let header_result = self.enter_table_header(trimmed);
+                if let Err(message) = header_result {
+                    let error = self.error_at(header_start, None, message);
+                    self.advance_past_line(line_end);
+                    self.current_table = None;
+                    return Some(Err(error));
+                }
+                let header = ScannedTomlItem::TableHeader {
+                    table_path: self.current_table.clone().unwrap_or_default(),
+                    line_number: self.line_number,
+                };
+                self.advance_past_line(line_end);
+                return Some(Ok(header));
+            }
+
+            match self.read_key_value(line_start, line_end) {
+                Ok(None) => continue,
+                Ok(Some(entry)) => return Some(Ok(ScannedTomlItem::Entry(entry))),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        None
+    }
+
+    /// Reads the `key = value` entry starting at `line_start`. Returns
+    /// `Ok(None)` for entries under an unreadable table header.
+    ///
+    /// After the value only whitespace and a `#` comment may follow on its
+    /// line; anything else is reported at the column where it starts.
+    fn read_key_value(&mut self, line_start: usize, line_end: usize) -> Result<Option<ScannedTomlEntry<'a>>, TomlScanError> {
+        let line_number = self.line_number;
+        let line = &self.content[line_start..line_end];
+        let key_start = line_end - line.trim_start().len();
+
+        let (key_path, after_key) = match parse_dotted_key(line, false) {
+            Ok(parsed) => parsed,
+            Err(message) => {
+                let error = self.error_at(key_start, None, message);
+                self.advance_past_line(line_end);
+                return Err(error);
+            }
+        };
+
+        let key_segment_count = key_path.len();
+        let full_path = self.current_table.as_ref().map(|current_table| {
+            let mut full_path = current_table.clone();
+            full_path.extend(key_path);
+            full_path
+        });
+
+        let after_equals = match after_key.strip_prefix('=') {
+            Some(after_equals) => after_equals.trim_start_matches([' ', '\t']),
+            None => {
+                let error = self.error_at(line_end - after_key.len(), full_path, "missing '=' after key".to_string());
+                self.advance_past_line(line_end);
+                return Err(error);
+            }
+        };
+
+        let value_start = line_end - after_equals.len();
+        let value_end = match find_toml_value_end(self.content, value_start) {
+            Ok(value_end) => value_end,
+            Err(message) => {
+                let error = self.error_at(value_start, full_path, message);
+                self.advance_past_line(line_end);
+                return Err(error);
             }
+        };
+
+        // Only whitespace and a comment may follow the value on its line
+        let value_line_end = self.end_of_line(value_end);
+        let after_value = &self.content[value_end..value_line_end];
+        let trailing = after_value.trim_start();
+        if !trailing.is_empty() && !trailing.starts_with('#') {
+            let trailing_start = value_line_end - trailing.len();
+            let message = format!("unexpected text after value: '{}'", trailing.trim_end());
+            let error = self.error_at(trailing_start, full_path, message);
+            self.advance_past_line(value_line_end);
+            return Err(error);
+        }
+
+        // Continue after the line on which the value ends
+        self.advance_past_line(value_line_end);
+
+        Ok(full_path.map(|full_path| ScannedTomlEntry {
+            full_path,
+            raw_value: &self.content[value_start..value_end],
+            key_segment_count,
+            line_number,
+            value_offset: value_start,
+        }))
+    }
+
+    fn error_at(&self, offset: usize, full_path: Option<Vec<TomlPathSegment>>, message: String) -> TomlScanError {
+        let (line, column) = line_and_column(self.content, offset);
+        TomlScanError { full_path, line, column, message }
+    }
+
+    /// Switches the current table for a `[a.b]` or `[[a.b]]` header line.
+    fn enter_table_header(&mut self, header_line: &str) -> Result<(), String> {
+        let is_array_table = header_line.starts_with("[[");
+        let (inner, closing) = if is_array_table {
+            (&header_line[2..], "]]")
+        } else {
+            (&header_line[1..], "]")
+        };
 
-            // Debug print statement
-            println!("Extracted value: {}", value);
+        let (header_keys, after_keys) = parse_dotted_key(inner, false)?;
+        let after_header = after_keys
+            .strip_prefix(closing)
+            .ok_or_else(|| format!("table header is missing '{}'", closing))?
+            .trim();
+        if !after_header.is_empty() && !after_header.starts_with('#') {
+            return Err(format!("unexpected text after table header: '{}'", after_header));
+        }
+
+        // Steps through an array of tables refer to its latest element,
+        // so `[products.details]` after `[[products]]` is `products[N].details`.
+        let header_key_count = header_keys.len();
+        let mut resolved_table = Vec::new();
+        for (key_index, key) in header_keys.into_iter().enumerate() {
+            resolved_table.push(key);
+            let is_last_key = key_index + 1 == header_key_count;
+            if is_last_key && is_array_table {
+                let element_index = self.push_array_table_element(&resolved_table);
+                resolved_table.push(TomlPathSegment::Index(element_index));
+            } else if let Some(element_count) = self.array_table_count(&resolved_table) {
+                resolved_table.push(TomlPathSegment::Index(element_count - 1));
+            }
+        }
+
+        self.current_table = Some(resolved_table);
+        Ok(())
+    }
+
+    fn array_table_count(&self, table_path: &[TomlPathSegment]) -> Option<usize> {
+        self.array_table_counts
+            .iter()
+            .find(|(known_path, _)| known_path == table_path)
+            .map(|(_, count)| *count)
+    }
+
+    /// Records a new `[[table]]` element and returns its index.
+    fn push_array_table_element(&mut self, table_path: &[TomlPathSegment]) -> usize {
+        for (known_path, count) in self.array_table_counts.iter_mut() {
+            if known_path == table_path {
+                *count += 1;
+                return *count - 1;
+            }
+        }
+        self.array_table_counts.push((table_path.to_vec(), 1));
+        0
+    }
+
+    /// Byte offset of the `\n` ending the line that contains `offset`, or
+    /// the end of the content.
+    fn end_of_line(&self, offset: usize) -> usize {
+        self.content[offset..]
+            .find('\n')
+            .map_or(self.content.len(), |newline| offset + newline)
+    }
+
+    /// Moves to the start of the line after `line_end`, counting lines.
+    fn advance_past_line(&mut self, line_end: usize) {
+        let next_position = (line_end + 1).min(self.content.len());
+        self.line_number += self.content[self.position..next_position].matches('\n').count();
+        self.position = next_position;
+    }
+}
+
+/// Parses a dotted key (`a`, `a.b`, `"quoted.key".c`) from the start of
+/// `text` and returns the segments and the unparsed rest of the text.
+/// With `allow_index`, `[N]` steps are accepted after any key.
+fn parse_dotted_key(text: &str, allow_index: bool) -> Result<(Vec<TomlPathSegment>, &str), String> {
+    let mut segments = Vec::new();
+    let mut rest = text.trim_start_matches([' ', '\t']);
+
+    loop {
+        let (key, after_key) = parse_single_key(rest)?;
+        segments.push(TomlPathSegment::Key(key));
+        rest = after_key;
+
+        if allow_index {
+            while let Some(after_bracket) = rest.strip_prefix('[') {
+                let close = after_bracket
+                    .find(']')
+                    .ok_or_else(|| "unclosed '[' in key path".to_string())?;
+                let index = after_bracket[..close]
+                    .trim()
+                    .parse::<usize>()
+                    .map_err(|_| format!("invalid index '{}' in key path", &after_bracket[..close]))?;
+                segments.push(TomlPathSegment::Index(index));
+                rest = &after_bracket[close + 1..];
+            }
+        }
+
+        let trimmed = rest.trim_start_matches([' ', '\t']);
+        match trimmed.strip_prefix('.') {
+            Some(after_dot) => rest = after_dot.trim_start_matches([' ', '\t']),
+            None => return Ok((segments, trimmed)),
+        }
+    }
+}
+
+/// Parses one bare (`a-b_c`), basic-quoted (`"a.b"`) or literal-quoted
+/// (`'a.b'`) key from the start of `text`.
+fn parse_single_key(text: &str) -> Result<(String, &str), String> {
+    if text.starts_with('"') || text.starts_with('\'') {
+        if text.starts_with("\"\"\"") || text.starts_with("'''") {
+            return Err("keys cannot be multi-line strings".to_string());
+        }
+        let key_end = find_toml_value_end(text, 0).map_err(|_| "unterminated quoted key".to_string())?;
+        let key = decode_toml_string(&text[..key_end])?;
+        return Ok((key, &text[key_end..]));
+    }
+
+    let key_end = text
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(text.len());
+    if key_end == 0 {
+        let found = text.lines().next().unwrap_or("").trim();
+        return Err(format!("expected a key name at '{}'", found));
+    }
+    Ok((text[..key_end].to_string(), &text[key_end..]))
+}
+
+/// Returns the byte offset just past the TOML value starting at
+/// `value_start`. Strings, arrays and inline tables may span lines;
+/// any other value runs to the end of the line or a `#` comment.
+fn find_toml_value_end(content: &str, value_start: usize) -> Result<usize, String> {
+    let rest = &content[value_start..];
+
+    if rest.starts_with("\"\"\"") {
+        return find_multi_line_string_end(content, value_start, '"');
+    }
+    if rest.starts_with("'''") {
+        return find_multi_line_string_end(content, value_start, '\'');
+    }
+
+    if let Some(after_quote) = rest.strip_prefix('"') {
+        let mut characters = after_quote.char_indices();
+        while let Some((index, character)) = characters.next() {
+            match character {
+                '"' => return Ok(value_start + 1 + index + 1),
+                '\\' => {
+                    characters.next();
+                }
+                '\n' => break,
+                _ => {}
+            }
+        }
+        return Err("unterminated string".to_string());
+    }
+
+    if let Some(after_quote) = rest.strip_prefix('\'') {
+        return match after_quote.find(['\'', '\n']) {
+            Some(close) if after_quote[close..].starts_with('\'') => Ok(value_start + 1 + close + 1),
+            _ => Err("unterminated literal string".to_string()),
+        };
+    }
+
+    if rest.starts_with('[') || rest.starts_with('{') {
+        return find_bracketed_value_end(content, value_start);
+    }
+
+    // Bare value (number, boolean, datetime): up to a comment or line end
+    let bare_end = rest.find(['#', '\n']).unwrap_or(rest.len());
+    let bare_value = rest[..bare_end].trim_end();
+    if bare_value.is_empty() {
+        return Err("missing value".to_string());
+    }
+    Ok(value_start + bare_value.len())
+}
+
+/// Finds the end of the `"""` or `'''` string whose opening quotes are at
+/// `value_start`: the byte offset just past the closing delimiter,
+/// including up to two extra quotes directly before it (`""""` ends in `"`).
+pub fn find_multi_line_string_end(content: &str, value_start: usize, quote: char) -> Result<usize, String> {
+    let delimiter: String = std::iter::repeat_n(quote, 3).collect();
+    let body_start = value_start + 3;
+    let mut characters = content[body_start..].char_indices();
+
+    while let Some((index, character)) = characters.next() {
+        if character == '\\' && quote == '"' {
+            characters.next();
+            continue;
+        }
+        if content[body_start + index..].starts_with(&delimiter) {
+            let mut close = body_start + index;
+            let mut extra_quotes = 0;
+            while extra_quotes < 2 && content[close + 3..].starts_with(quote) {
+                close += 1;
+                extra_quotes += 1;
+            }
+            return Ok(close + 3);
+        }
+    }
+    Err(format!("closing {} not found", delimiter))
+}
+
+/// Finds the matching `]` or `}` for the array or inline table starting at
+/// `value_start`, skipping over strings and `#` comments inside it.
+fn find_bracketed_value_end(content: &str, value_start: usize) -> Result<usize, String> {
+    let mut depth = 0usize;
+    let mut position = value_start;
+
+    while let Some(character) = content[position..].chars().next() {
+        match character {
+            '[' | '{' => {
+                depth += 1;
+                position += 1;
+            }
+            ']' | '}' => {
+                depth -= 1;
+                position += 1;
+                if depth == 0 {
+                    return Ok(position);
+                }
+            }
+            '"' | '\'' => position = find_toml_value_end(content, position)?,
+            '#' => {
+                position = content[position..]
+                    .find('\n')
+                    .map_or(content.len(), |newline| position + newline);
+            }
+            _ => position += character.len_utf8(),
+        }
+    }
+    Err("closing bracket not found".to_string())
+}
+
+/// A TOML value converted to its Rust type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TomlValue {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    Datetime(TomlDatetime),
+    Array(Vec<TomlValue>),
+}
+
+impl TomlValue {
+    /// Short TOML name of the value's type, used in error messages.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            TomlValue::String(_) => "string",
+            TomlValue::Integer(_) => "integer",
+            TomlValue::Float(_) => "float",
+            TomlValue::Boolean(_) => "boolean",
+            TomlValue::Datetime(_) => "datetime",
+            TomlValue::Array(_) => "array",
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            TomlValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            TomlValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            TomlValue::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            TomlValue::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_datetime(&self) -> Option<&TomlDatetime> {
+        match self {
+            TomlValue::Datetime(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[TomlValue]> {
+        match self {
+            TomlValue::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the value as TOML: strings as basic strings with escapes,
+/// floats always with a `.`, `inf` or `nan`, and arrays on one line.
+impl fmt::Display for TomlValue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TomlValue::String(text) => write!(formatter, "\"{}\"", escape_basic_string(text)),
+            TomlValue::Integer(number) => write!(formatter, "{}", number),
+            TomlValue::Float(number) => {
+                if number.is_nan() {
+                    write!(formatter, "nan")
+                } else if number.is_infinite() {
+                    write!(formatter, "{}", if *number > 0.0 { "inf" } else { "-inf" })
+                } else {
+                    let text = number.to_string();
+                    if text.contains(['.', 'e', 'E']) {
+                        write!(formatter, "{}", text)
+                    } else {
+                        write!(formatter, "{}.0", text)
+                    }
+                }
+            }
+            TomlValue::Boolean(flag) => write!(formatter, "{}", flag),
+            TomlValue::Datetime(datetime) => write!(formatter, "{}", datetime),
+            TomlValue::Array(elements) => {
+                write!(formatter, "[")?;
+                for (index, element) in elements.iter().enumerate() {
+                    if index > 0 {
+                        write!(formatter, ", ")?;
+                    }
+                    write!(formatter, "{}", element)?;
+                }
+                write!(formatter, "]")
+            }
+        }
+    }
+}
+
+/// Escapes text for the inside of a `"basic"` TOML string.
+pub fn escape_basic_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\u{0008}' => escaped.push_str("\\b"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\u{000C}' => escaped.push_str("\\f"),
+            '\r' => escaped.push_str("\\r"),
+            control if control.is_control() => escaped.push_str(&format!("\\u{:04X}", control as u32)),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+/// An RFC 3339 date-time as TOML allows it: an offset date-time, a local
+/// date-time (no offset), a local date (no time) or a local time (no date).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TomlDatetime {
+    pub date: Option<TomlDate>,
+    pub time: Option<TomlTime>,
+    pub offset: Option<TomlOffset>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TomlDate {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TomlTime {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    pub nanosecond: u32,
+}
+
+/// Offset of an offset date-time: `Z`, or signed minutes east of UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TomlOffset {
+    Utc,
+    Minutes(i16),
+}
+
+impl fmt::Display for TomlDatetime {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(date) = &self.date {
+            write!(formatter, "{:04}-{:02}-{:02}", date.year, date.month, date.day)?;
+            if self.time.is_some() {
+                write!(formatter, "T")?;
+            }
+        }
+        if let Some(time) = &self.time {
+            write!(formatter, "{:02}:{:02}:{:02}", time.hour, time.minute, time.second)?;
+            if time.nanosecond > 0 {
+                let fraction = format!("{:09}", time.nanosecond);
+                write!(formatter, ".{}", fraction.trim_end_matches('0'))?;
+            }
+        }
+        match self.offset {
+            Some(TomlOffset::Utc) => write!(formatter, "Z"),
+            Some(TomlOffset::Minutes(minutes)) => {
+                let sign = if minutes < 0 { '-' } else { '+' };
+                let minutes = minutes.unsigned_abs();
+                write!(formatter, "{}{:02}:{:02}", sign, minutes / 60, minutes % 60)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Reads any field from a TOML file as a typed [`TomlValue`].
+///
+/// # Arguments
+/// * `path` - Path to the TOML file
+/// * `field_name` - Name or dotted path of the field to read
+///
+/// # Returns
+/// * `Result<TomlValue, ReadTomlError>` - The parsed value or the reason it could not be read
+///
+/// # Example
+/// ```
+/// # use read_toml_field::read_toml_field::{read_toml_value, TomlValue};
+/// let value = read_toml_value("config.toml", "log");
+/// assert_eq!(value, Ok(TomlValue::Boolean(true)));
+/// ```
+pub fn read_toml_value(path: &str, field_name: &str) -> Result<TomlValue, ReadTomlError> {
+    let content = read_toml_file_to_string(path)?;
+    let field_path = parse_field_path(field_name)?;
+    let entry = find_toml_entry(&content, field_name, &field_path)?;
+
+    parse_toml_value(entry.raw_value).map_err(|(offset, message)| {
+        let (line, column) = line_and_column(&content, entry.value_offset + offset);
+        ReadTomlError::MalformedLine { field: field_name.to_string(), line, column, message }
+    })
+}
+
+/// Reads any field from a TOML file as a typed [`TomlValue`], together with
+/// the [`TomlSpan`] of its value as written, for pointing an operator at
+/// `config.toml:42:17` when the value is not acceptable.
+///
+/// # Example
+/// ```
+/// # use read_toml_field::read_toml_field::{read_toml_value_with_span, TomlValue};
+/// let (value, span) = read_toml_value_with_span("config.toml", "log")?;
+/// assert_eq!(value, TomlValue::Boolean(true));
+/// assert_eq!(span.to_string(), "config.toml:3:5");
+/// # Ok::<(), read_toml_field::read_toml_field::ReadTomlError>(())
+/// ```
+pub fn read_toml_value_with_span(path: &str, field_name: &str) -> Result<(TomlValue, TomlSpan), ReadTomlError> {
+    let content = read_toml_file_to_string(path)?;
+    let field_path = parse_field_path(field_name)?;
+    let entry = find_toml_entry(&content, field_name, &field_path)?;
+
+    let value = parse_toml_value(entry.raw_value).map_err(|(offset, message)| {
+        let (line, column) = line_and_column(&content, entry.value_offset + offset);
+        ReadTomlError::MalformedLine { field: field_name.to_string(), line, column, message }
+    })?;
+    Ok((value, entry_span(path, &content, &entry)))
+}
+
+/// Reads a boolean (`true` / `false`) field from a TOML file.
+pub fn read_bool_field_from_toml(path: &str, field_name: &str) -> Result<bool, ReadTomlError> {
+    let value = read_toml_value(path, field_name)?;
+    value.as_bool().ok_or_else(|| type_mismatch(field_name, "boolean", &value))
+}
+
+/// Reads a signed integer field from a TOML file. Decimal, `0x` hex,
+/// `0o` octal and `0b` binary forms are accepted, with `_` separators.
+pub fn read_i64_field_from_toml(path: &str, field_name: &str) -> Result<i64, ReadTomlError> {
+    let value = read_toml_value(path, field_name)?;
+    value.as_i64().ok_or_else(|| type_mismatch(field_name, "integer", &value))
+}
+
+/// Reads a float field from a TOML file, including `inf`, `-inf` and `nan`.
+/// Integer values are a type mismatch, as TOML keeps the two types apart.
+pub fn read_float_field_from_toml(path: &str, field_name: &str) -> Result<f64, ReadTomlError> {
+    let value = read_toml_value(path, field_name)?;
+    value.as_f64().ok_or_else(|| type_mismatch(field_name, "float", &value))
+}
+
+/// Reads an RFC 3339 date-time, local date-time, local date or local time
+/// field from a TOML file.
+pub fn read_datetime_field_from_toml(path: &str, field_name: &str) -> Result<TomlDatetime, ReadTomlError> {
+    let value = read_toml_value(path, field_name)?;
+    value.as_datetime().copied().ok_or_else(|| type_mismatch(field_name, "datetime", &value))
+}
+
+/// Reads an array field from a TOML file. Elements may be of mixed types.
+pub fn read_array_field_from_toml(path: &str, field_name: &str) -> Result<Vec<TomlValue>, ReadTomlError> {
+    match read_toml_value(path, field_name)? {
+        TomlValue::Array(values) => Ok(values),
+        other => Err(type_mismatch(field_name, "array", &other)),
+    }
+}
+
+/// Reads an array of strings from a TOML file. Any non-string element is
+/// a type mismatch.
+pub fn read_string_array_from_toml(path: &str, field_name: &str) -> Result<Vec<String>, ReadTomlError> {
+    read_array_field_from_toml(path, field_name)?
+        .into_iter()
+        .map(|element| match element {
+            TomlValue::String(text) => Ok(text),
+            other => Err(type_mismatch(field_name, "string", &other)),
+        })
+        .collect()
+}
+
+/// Reads an array of integers from a TOML file. Any non-integer element
+/// is a type mismatch.
+pub fn read_i64_array_from_toml(path: &str, field_name: &str) -> Result<Vec<i64>, ReadTomlError> {
+    let elements = read_array_field_from_toml(path, field_name)?;
+    integer_elements(field_name, &elements)
+}
+
+/// Reads an array of integer arrays, such as `[[1, 2], [3, 4]]`, from a
+/// TOML file. The inner arrays may have different lengths.
+///
+/// # Example
+/// ```
+/// # use read_toml_field::read_toml_field::read_nested_i64_array_from_toml;
+/// // For TOML content:
+/// // matrix = [
+/// //     [1, 2],
+/// //     [3, 4], # trailing commas and comments are fine
+/// // ]
+/// let matrix = read_nested_i64_array_from_toml("config.toml", "matrix");
+/// // Returns: Ok(vec![vec![1, 2], vec![3, 4]])
+/// ```
+pub fn read_nested_i64_array_from_toml(path: &str, field_name: &str) -> Result<Vec<Vec<i64>>, ReadTomlError> {
+    read_array_field_from_toml(path, field_name)?
+        .iter()
+        .map(|element| match element {
+            TomlValue::Array(inner) => integer_elements(field_name, inner),
+            other => Err(type_mismatch(field_name, "array", other)),
+        })
+        .collect()
+}
+
+pub(crate) fn integer_elements(field_name: &str, elements: &[TomlValue]) -> Result<Vec<i64>, ReadTomlError> {
+    elements
+        .iter()
+        .map(|element| element.as_i64().ok_or_else(|| type_mismatch(field_name, "integer", element)))
+        .collect()
+}
+
+pub(crate) fn type_mismatch(field_name: &str, expected: &'static str, found: &TomlValue) -> ReadTomlError {
+    ReadTomlError::TypeMismatch {
+        field: field_name.to_string(),
+        expected,
+        found: found.type_name(),
+    }
+}
+
+/// Parses the raw text of one TOML value, as found by [`TomlEntryScanner`].
+pub(crate) fn parse_toml_value(raw_value: &str) -> Result<TomlValue, (usize, String)> {
+    let mut parser = TomlValueParser { text: raw_value, position: 0, error_position: 0 };
+    let value = parser.parse_value().map_err(|message| (parser.error_position, message))?;
+    let rest = &raw_value[parser.position..];
+    let trimmed_rest = rest.trim_start();
+    if !trimmed_rest.is_empty() {
+        let rest_offset = raw_value.len() - trimmed_rest.len();
+        return Err((rest_offset, format!("unexpected text after value: '{}'", first_line(trimmed_rest))));
+    }
+    Ok(value)
+}
+
+/// Recursive-descent parser over the raw text of a single value.
+struct TomlValueParser<'a> {
+    text: &'a str,
+    position: usize,
+    /// Start of the element being parsed, reported as the error position.
+    error_position: usize,
+}
+
+impl TomlValueParser<'_> {
+    fn rest(&self) -> &str {
+        &self.text[self.position..]
+    }
+
+    fn parse_value(&mut self) -> Result<TomlValue, String> {
+        self.error_position = self.position;
+        let rest = self.rest();
+        if rest.starts_with('[') {
+            return self.parse_array();
+        }
+        if rest.starts_with('"') || rest.starts_with('\'') {
+            let value_end = find_toml_value_end(self.text, self.position)?;
+            let quoted = &self.text[self.position..value_end];
+            self.position = value_end;
+            return decode_toml_string(quoted).map(TomlValue::String);
+        }
+        if rest.starts_with('{') {
+            return Err("inline tables are not supported".to_string());
+        }
+
+        let token = self.take_bare_token();
+        parse_bare_value(token)
+    }
+
+    fn parse_array(&mut self) -> Result<TomlValue, String> {
+        // Skip '['
+        self.position += 1;
+        let mut elements = Vec::new();
+
+        loop {
+            self.skip_array_whitespace();
+            if self.rest().starts_with(']') {
+                self.position += 1;
+                return Ok(TomlValue::Array(elements));
+            }
+            if self.rest().is_empty() {
+                self.error_position = self.position;
+                return Err("closing ']' not found".to_string());
+            }
+
+            elements.push(self.parse_value()?);
+
+            self.skip_array_whitespace();
+            if self.rest().starts_with(',') {
+                self.position += 1;
+            } else if !self.rest().starts_with(']') {
+                self.error_position = self.position;
+                return Err(format!("expected ',' or ']' in array, found '{}'", first_line(self.rest())));
+            }
+        }
+    }
+
+    /// Skips whitespace, newlines and `#` comments between array elements.
+    fn skip_array_whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            let skipped = rest.len() - trimmed.len();
+            let comment_length = if trimmed.starts_with('#') {
+                trimmed.find('\n').unwrap_or(trimmed.len())
+            } else {
+                0
+            };
+            self.position += skipped + comment_length;
+            if comment_length == 0 {
+                return;
+            }
+        }
+    }
+
+    /// Takes a number, boolean or datetime token. A date followed by a
+    /// space and a time (`1979-05-27 07:32:00`) is taken as one token.
+    fn take_bare_token(&mut self) -> &str {
+        let rest = self.rest();
+        let is_token_end = |c: char| c.is_whitespace() || matches!(c, ',' | ']' | '}' | '#');
+        let mut token_end = rest.find(is_token_end).unwrap_or(rest.len());
+
+        let after_token = &rest[token_end..];
+        if is_local_date(&rest[..token_end]) && after_token.starts_with(' ') {
+            let time_part = &after_token[1..];
+            let time_end = time_part.find(is_token_end).unwrap_or(time_part.len());
+            if is_time_prefix(&time_part[..time_end]) {
+                token_end += 1 + time_end;
+            }
+        }
+
+        let token_start = self.position;
+        self.position += token_end;
+        &self.text[token_start..self.position]
+    }
+}
+
+/// Returns the text of a string value with its quotes removed and escapes
+/// decoded. Values that are not strings (numbers, booleans, ...) are
+/// returned as written, which is what the line readers always did.
+pub fn string_value_text(raw_value: &str) -> Result<String, String> {
+    let raw_value = raw_value.trim();
+    if raw_value.starts_with('"') || raw_value.starts_with('\'') {
+        decode_toml_string(raw_value)
+    } else {
+        Ok(raw_value.to_string())
+    }
+}
+
+/// Decodes a complete TOML string token, quotes included, following the
+/// TOML 1.0 rules for each of the four string kinds:
+///
+/// * `"basic"` - escapes such as `\"`, `\\`, `\n`, `\t` and `\uXXXX` are decoded
+/// * `'literal'` - taken exactly as written, so `'C:\path'` keeps its backslash
+/// * `"""multi-line basic"""` - escapes decoded, a newline right after the
+///   opening quotes is dropped, and a `\` at the end of a line removes the
+///   line break and the leading whitespace of the next line
+/// * `'''multi-line literal'''` - taken as written, minus a newline right
+///   after the opening quotes
+pub fn decode_toml_string(quoted: &str) -> Result<String, String> {
+    for (delimiter, is_basic) in [("\"\"\"", true), ("'''", false)] {
+        if quoted.len() >= 6 && quoted.starts_with(delimiter) && quoted.ends_with(delimiter) {
+            let body = &quoted[3..quoted.len() - 3];
+            let body = body
+                .strip_prefix("\r\n")
+                .or_else(|| body.strip_prefix('\n'))
+                .unwrap_or(body);
+            return if is_basic {
+                decode_basic_string_body(body, true)
+            } else {
+                Ok(body.to_string())
+            };
+        }
+    }
+
+    if quoted.len() >= 2 && quoted.starts_with('"') && quoted.ends_with('"') {
+        return decode_basic_string_body(&quoted[1..quoted.len() - 1], false);
+    }
+    if quoted.len() >= 2 && quoted.starts_with('\'') && quoted.ends_with('\'') {
+        let body = &quoted[1..quoted.len() - 1];
+        if body.contains('\'') || body.contains('\n') {
+            return Err("literal strings cannot contain ' or line breaks".to_string());
+        }
+        return Ok(body.to_string());
+    }
+
+    Err(format!("not a quoted string: {}", first_line(quoted)))
+}
+
+/// Decodes the escape sequences of a basic string body (quotes removed).
+fn decode_basic_string_body(body: &str, is_multi_line: bool) -> Result<String, String> {
+    let mut decoded = String::with_capacity(body.len());
+    let mut characters = body.chars().peekable();
+
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            if character == '"' && !is_multi_line {
+                return Err("unescaped '\"' inside string".to_string());
+            }
+            decoded.push(character);
+            continue;
+        }
+
+        let escape = characters
+            .next()
+            .ok_or_else(|| "string ends with a lone '\\'".to_string())?;
+        match escape {
+            'b' => decoded.push('\u{0008}'),
+            't' => decoded.push('\t'),
+            'n' => decoded.push('\n'),
+            'f' => decoded.push('\u{000C}'),
+            'r' => decoded.push('\r'),
+            '"' => decoded.push('"'),
+            '\\' => decoded.push('\\'),
+            'u' | 'U' => {
+                let digit_count = if escape == 'u' { 4 } else { 8 };
+                let hex_digits: String = characters.by_ref().take(digit_count).collect();
+                if hex_digits.len() != digit_count || !hex_digits.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(format!("invalid \\{} escape: expected {} hex digits", escape, digit_count));
+                }
+                let code_point = u32::from_str_radix(&hex_digits, 16)
+                    .map_err(|_| format!("invalid \\{}{} escape", escape, hex_digits))?;
+                let unicode_character = char::from_u32(code_point)
+                    .ok_or_else(|| format!("\\{}{} is not a Unicode scalar value", escape, hex_digits))?;
+                decoded.push(unicode_character);
+            }
+            ' ' | '\t' | '\r' | '\n' if is_multi_line => {
+                // Line-ending backslash: only whitespace may follow it on
+                // its line, then all whitespace up to the next text is removed.
+                let mut crossed_line_break = escape == '\n';
+                while let Some(&next) = characters.peek() {
+                    match next {
+                        '\n' => crossed_line_break = true,
+                        ' ' | '\t' | '\r' => {}
+                        _ => break,
+                    }
+                    characters.next();
+                }
+                if !crossed_line_break {
+                    return Err("'\\' followed by whitespace must end the line".to_string());
+                }
+            }
+            other => return Err(format!("invalid escape sequence '\\{}'", other)),
+        }
+    }
+    Ok(decoded)
+}
+
+/// Parses an unquoted value: boolean, datetime, float or integer.
+fn parse_bare_value(token: &str) -> Result<TomlValue, String> {
+    match token {
+        "" => return Err("missing value".to_string()),
+        "true" => return Ok(TomlValue::Boolean(true)),
+        "false" => return Ok(TomlValue::Boolean(false)),
+        _ => {}
+    }
+
+    if is_local_date(token) || is_time_prefix(token) {
+        return parse_toml_datetime(token).map(TomlValue::Datetime);
+    }
+
+    let unsigned = token.trim_start_matches(['+', '-']);
+    let is_prefixed_integer = unsigned.starts_with("0x") || unsigned.starts_with("0o") || unsigned.starts_with("0b");
+    if unsigned == "inf" || unsigned == "nan" || (!is_prefixed_integer && unsigned.contains(['.', 'e', 'E'])) {
+        return parse_toml_float(token).map(TomlValue::Float);
+    }
+
+    parse_toml_integer(token).map(TomlValue::Integer)
+}
+
+/// Parses a TOML integer: decimal with optional sign, or unsigned `0x`,
+/// `0o` and `0b` forms. `_` is allowed only between two digits.
+fn parse_toml_integer(token: &str) -> Result<i64, String> {
+    let invalid = || format!("invalid integer '{}'", token);
+
+    for (prefix, radix) in [("0x", 16), ("0o", 8), ("0b", 2)] {
+        if let Some(digits) = token.strip_prefix(prefix) {
+            let digits = remove_digit_separators(digits, radix).ok_or_else(invalid)?;
+            return i64::from_str_radix(&digits, radix).map_err(|_| invalid());
+        }
+    }
+
+    let (sign, unsigned) = match token.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", token.strip_prefix('+').unwrap_or(token)),
+    };
+    if unsigned.len() > 1 && unsigned.starts_with('0') {
+        return Err(format!("leading zeros are not allowed in '{}'", token));
+    }
+    let digits = remove_digit_separators(unsigned, 10).ok_or_else(invalid)?;
+    format!("{}{}", sign, digits).parse::<i64>().map_err(|_| invalid())
+}
+
+/// Parses a TOML float, including `inf` and `nan` with an optional sign.
+fn parse_toml_float(token: &str) -> Result<f64, String> {
+    let invalid = || format!("invalid float '{}'", token);
+
+    let (is_negative, unsigned) = match token.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, token.strip_prefix('+').unwrap_or(token)),
+    };
+    let magnitude = match unsigned {
+        "inf" => f64::INFINITY,
+        "nan" => f64::NAN,
+        _ => {
+            let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+                Some(split) => (&unsigned[..split], Some(&unsigned[split + 1..])),
+                None => (unsigned, None),
+            };
+            let (integer_part, fraction_part) = match mantissa.split_once('.') {
+                Some((integer_part, fraction_part)) => (integer_part, Some(fraction_part)),
+                None => (mantissa, None),
+            };
+            if integer_part.len() > 1 && integer_part.starts_with('0') {
+                return Err(format!("leading zeros are not allowed in '{}'", token));
+            }
 
-            // Clean up the value: remove quotes and trim spaces
-            let cleaned_value = value.trim().trim_matches('"').trim();
-            
-            // Verify the cleaned value isn't empty
-            if cleaned_value.is_empty() {
-                println!("Warning: Value became empty after cleaning for field '{}'", field_name);
-                return String::new();
+            let mut normalized = remove_digit_separators(integer_part, 10).ok_or_else(invalid)?;
+            if let Some(fraction_part) = fraction_part {
+                normalized.push('.');
+                normalized.push_str(&remove_digit_separators(fraction_part, 10).ok_or_else(invalid)?);
             }
+            if let Some(exponent) = exponent {
+                let (exponent_sign, exponent_digits) = match exponent.strip_prefix(['+', '-']) {
+                    Some(digits) => (&exponent[..1], digits),
+                    None => ("", exponent),
+                };
+                normalized.push('e');
+                normalized.push_str(exponent_sign);
+                normalized.push_str(&remove_digit_separators(exponent_digits, 10).ok_or_else(invalid)?);
+            }
+            normalized.parse::<f64>().map_err(|_| invalid())?
+        }
+    };
+    Ok(if is_negative { -magnitude } else { magnitude })
+}
 
-            return cleaned_value.to_string();
+/// Removes `_` separators from a run of digits, or returns `None` if the
+/// run is empty, has a non-digit, or has a `_` not between two digits.
+fn remove_digit_separators(digits: &str, radix: u32) -> Option<String> {
+    if digits.is_empty() || digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return None;
+    }
+    let mut cleaned = String::with_capacity(digits.len());
+    for character in digits.chars() {
+        if character == '_' {
+            continue;
         }
+        if !character.is_digit(radix) {
+            return None;
+        }
+        cleaned.push(character);
     }
+    Some(cleaned)
+}
 
-    // If we get here, the field wasn't found
-    println!("Field '{}' not found in file", field_name);
-    String::new()
+/// True if `token` starts with a `YYYY-MM-DD` date.
+fn is_local_date(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    bytes.len() >= 10
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[5..7].iter().all(u8::is_ascii_digit)
+        && bytes[7] == b'-'
+        && bytes[8..10].iter().all(u8::is_ascii_digit)
 }
 
-/// Reads all fields from a TOML file that share a common base name (prefix before underscore)
-/// and returns a vector of their values. Returns an empty vector if no matching fields are found
-/// or if any errors occur.
-///
-/// # Arguments
-/// * `path` - Path to the TOML file
-/// * `base_name` - Base name to search for (e.g., "prompt" will match "prompt_1", "prompt_2", etc.)
-///
-/// # Returns
-/// * `Vec<String>` - Vector containing all values for fields matching the base name
-///
-/// # Example
-/// ```
-/// let values = read_basename_fields_from_toml("config.toml", "prompt");
-/// // For TOML content:
-/// // prompt_1 = "value1"
-/// // prompt_2 = "value2"
-/// // Returns: vec!["value1", "value2"]
-/// ```
-pub fn read_basename_fields_from_toml(path: &str, base_name: &str) -> Vec<String> {
-    let mut values = Vec::new();
-
-    // Validate input parameters
-    if path.is_empty() || base_name.is_empty() {
-        println!("Error: Empty path or base name provided");
-        return values;
-    }
-
-    // Open and read the file
-    let file = match File::open(path) {
-        Ok(file) => file,
-        Err(e) => {
-            println!("Failed to open file at path: {}. Error: {}", path, e);
-            return values;
-        },
-    };
+/// True if `token` starts with an `HH:MM` time.
+fn is_time_prefix(token: &str) -> bool {
+    let bytes = token.as_bytes();
+    bytes.len() >= 5
+        && bytes[..2].iter().all(u8::is_ascii_digit)
+        && bytes[2] == b':'
+        && bytes[3..5].iter().all(u8::is_ascii_digit)
+}
 
-    let reader = io::BufReader::new(file);
-    let base_name_with_underscore = format!("{}_", base_name);
+/// Parses an RFC 3339 offset date-time, local date-time, local date or
+/// local time, validating every field's range.
+fn parse_toml_datetime(token: &str) -> Result<TomlDatetime, String> {
+    let invalid = |reason: &str| format!("invalid datetime '{}': {}", token, reason);
 
-    // Process each line
-    for (line_number, line_result) in reader.lines().enumerate() {
-        let line = match line_result {
-            Ok(line) => line,
-            Err(e) => {
-                println!("Error reading line {}: {}", line_number + 1, e);
-                continue;
-            }
-        };
+    let mut rest = token;
+    let mut datetime = TomlDatetime { date: None, time: None, offset: None };
 
-        // Skip empty lines and comments
-        let trimmed_line = line.trim();
-        if trimmed_line.is_empty() || trimmed_line.starts_with('#') {
-            continue;
+    if is_local_date(rest) {
+        let year = rest[0..4].parse::<u16>().map_err(|_| invalid("bad year"))?;
+        let month = rest[5..7].parse::<u8>().map_err(|_| invalid("bad month"))?;
+        let day = rest[8..10].parse::<u8>().map_err(|_| invalid("bad day"))?;
+        if !(1..=12).contains(&month) {
+            return Err(invalid("month out of range"));
         }
+        if day == 0 || day > days_in_month(year, month) {
+            return Err(invalid("day out of range"));
+        }
+        datetime.date = Some(TomlDate { year, month, day });
+        rest = &rest[10..];
 
-        // Check if line starts with base_name_
-        if trimmed_line.starts_with(&base_name_with_underscore) {
-            // Split the line by '=' and handle malformed lines
-            let parts: Vec<&str> = trimmed_line.splitn(2, '=').collect();
-            if parts.len() != 2 {
-                println!("Malformed TOML line {} - missing '=': {}", line_number + 1, line);
-                continue;
-            }
-
-            let value = parts[1].trim();
-            
-            // Clean up the value: remove quotes and trim spaces
-            let cleaned_value = value.trim().trim_matches('"').trim();
-            
-            if !cleaned_value.is_empty() {
-                values.push(cleaned_value.to_string());
-            }
+        match rest.chars().next() {
+            None => return Ok(datetime),
+            Some('T' | 't' | ' ') => rest = &rest[1..],
+            Some(_) => return Err(invalid("expected 'T' between date and time")),
         }
     }
 
-    // Sort values to ensure consistent ordering
-    values.sort();
-    values
-}
-
-/// Reads a single-line string field from a TOML file.
-/// 
-/// # Arguments
-/// * `path` - Path to the TOML file
-/// * `field_name` - Name of the field to read
-/// 
-/// # Returns
-/// * `Result<String, String>` - The field value or an error message
-pub fn read_single_line_string_field_from_toml(path: &str, field_name: &str) -> Result<String, String> {
-    let file = File::open(path)
-        .map_err(|e| format!("Failed to open file: {}", e))?;
-    
-    let reader = io::BufReader::new(file);
-    
-    for line in reader.lines() {
-        let line = line.map_err(|e| format!("Failed to read line: {}", e))?;
-        let trimmed = line.trim();
-        
-        if trimmed.starts_with(&format!("{} = ", field_name)) {
-            return Ok(trimmed
-                .splitn(2, '=')
-                .nth(1)
-                .unwrap_or("")
-                .trim()
-                .trim_matches('"')
-                .to_string());
+    let bytes = rest.as_bytes();
+    let is_time_layout = bytes.len() >= 8
+        && bytes[2] == b':'
+        && bytes[5] == b':'
+        && [0, 1, 3, 4, 6, 7].iter().all(|&index| bytes[index].is_ascii_digit());
+    if !is_time_layout {
+        return Err(invalid("expected time as HH:MM:SS"));
+    }
+    let parse_two_digits = |text: &str| -> Result<u8, String> {
+        if text.bytes().all(|b| b.is_ascii_digit()) {
+            text.parse::<u8>().map_err(|_| invalid("bad number"))
+        } else {
+            Err(invalid("bad number"))
         }
+    };
+    let hour = parse_two_digits(&rest[0..2])?;
+    let minute = parse_two_digits(&rest[3..5])?;
+    let second = parse_two_digits(&rest[6..8])?;
+    if hour > 23 || minute > 59 || second > 60 {
+        return Err(invalid("time out of range"));
     }
-    
-    Err(format!("Field '{}' not found", field_name))
-}
-
-/// Reads a multi-line string field (triple-quoted) from a TOML file.
-/// 
-/// # Arguments
-/// * `path` - Path to the TOML file
-/// * `field_name` - Name of the field to read
-/// 
-/// # Returns
-/// * `Result<String, String>` - The concatenated multi-line value or an error message
-pub fn read_multi_line_toml_string(path: &str, field_name: &str) -> Result<String, String> {
-    let mut file = File::open(path)
-        .map_err(|e| format!("Failed to open file: {}", e))?;
-    
-    let mut content = String::new();
-    file.read_to_string(&mut content)
-        .map_err(|e| format!("Failed to read file: {}", e))?;
-
-    // Find the start of the field
-    let field_start = format!("{} = \"\"\"", field_name);
-    let start_pos = content.find(&field_start)
-        .ok_or_else(|| format!("Multi-line field '{}' not found", field_name))?;
+    rest = &rest[8..];
 
-    // Find the end of the field (next """)
-    let content_after_start = &content[start_pos + field_start.len()..];
-    let end_pos = content_after_start.find("\"\"\"")
-        .ok_or_else(|| format!("Closing triple quotes not found for field '{}'", field_name))?;
+    let mut nanosecond = 0u32;
+    if let Some(after_dot) = rest.strip_prefix('.') {
+        let fraction_length = after_dot.find(|c: char| !c.is_ascii_digit()).unwrap_or(after_dot.len());
+        if fraction_length == 0 {
+            return Err(invalid("missing fractional seconds"));
+        }
+        // Keep nanosecond precision; extra digits are truncated
+        let fraction: String = after_dot[..fraction_length].chars().chain(std::iter::repeat('0')).take(9).collect();
+        nanosecond = fraction.parse::<u32>().map_err(|_| invalid("bad fractional seconds"))?;
+        rest = &after_dot[fraction_length..];
+    }
+    datetime.time = Some(TomlTime { hour, minute, second, nanosecond });
 
-    // Extract the content between the triple quotes
-    let multi_line_content = &content_after_start[..end_pos];
+    if rest.is_empty() {
+        return Ok(datetime);
+    }
+    if datetime.date.is_none() {
+        return Err(invalid("a local time cannot have an offset"));
+    }
+    if rest == "Z" || rest == "z" {
+        datetime.offset = Some(TomlOffset::Utc);
+        return Ok(datetime);
+    }
 
-    // Clean up the content
-    Ok(multi_line_content
-        .lines()
-        .map(|line| line.trim())
-        .collect::<Vec<&str>>()
-        .join("\n")
-        .trim()
-        .to_string())
+    let offset_bytes = rest.as_bytes();
+    if offset_bytes.len() != 6 || !matches!(offset_bytes[0], b'+' | b'-') || offset_bytes[3] != b':' {
+        return Err(invalid("expected offset as Z or +HH:MM"));
+    }
+    let offset_hours = parse_two_digits(&rest[1..3])?;
+    let offset_minutes = parse_two_digits(&rest[4..6])?;
+    if offset_hours > 23 || offset_minutes > 59 {
+        return Err(invalid("offset out of range"));
+    }
+    let total_minutes = i16::from(offset_hours) * 60 + i16::from(offset_minutes);
+    datetime.offset = Some(TomlOffset::Minutes(if offset_bytes[0] == b'-' { -total_minutes } else { total_minutes }));
+    Ok(datetime)
 }
 
-/// Reads an array of integers from a TOML file into a Vec<u64>.
-/// 
-/// # Arguments
-/// * `path` - Path to the TOML file
-/// * `field_name` - Name of the field to read
-/// 
-/// # Returns
-/// * `Result<Vec<u64>, String>` - The vector of integers or an error message
-pub fn read_integer_array(path: &str, field_name: &str) -> Result<Vec<u64>, String> {
-    let file = File::open(path)
-        .map_err(|e| format!("Failed to open file: {}", e))?;
-    
-    let reader = io::BufReader::new(file);
-    
-    for line in reader.lines() {
-        let line = line.map_err(|e| format!("Failed to read line: {}", e))?;
-        let trimmed = line.trim();
-        
-        if trimmed.starts_with(&format!("{} = [", field_name)) {
-            let array_part = trimmed
-                .splitn(2, '=')
-                .nth(1)
-                .ok_or("Invalid array format")?
-                .trim()
-                .trim_matches(|c| c == '[' || c == ']');
-                
-            return array_part
-                .split(',')
-                .map(|s| s.trim().parse::<u64>()
-                    .map_err(|e| format!("Invalid integer: {}", e)))
-                .collect::<Result<Vec<u64>, String>>();
-        }
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
     }
-    
-    Err(format!("Array field '{}' not found", field_name))
+}
+
+/// First line of `text`, trimmed, for error messages.
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or("").trim()
 }
 
 /// Extracts a GPG key from a TOML file.
@@ -365,8 +1938,8 @@ pub fn read_integer_array(path: &str, field_name: &str) -> Result<Vec<u64>, Stri
 /// * `key_field` - Name of the field containing the GPG key
 ///
 /// # Returns
-/// * `Result<String, String>` - The GPG key or an error message
-fn extract_gpg_key_from_clearsigntoml(path: &str, key_field: &str) -> Result<String, String> {
+/// * `Result<String, ReadTomlError>` - The GPG key or the reason it could not be read
+fn extract_gpg_key_from_clearsigntoml(path: &str, key_field: &str) -> Result<String, ReadTomlError> {
     read_multi_line_toml_string(path, key_field)
 }
 
@@ -445,16 +2018,20 @@ fn verify_clearsign(path: &str, key: &str) -> Result<bool, String> {
 /// * `field_name` - Name of the field to read
 ///
 /// # Returns
-/// * `Result<String, String>` - The field value or an error message
-pub fn read_singleline_string_from_clearsigntoml(path_to_clearsigntoml_with_gpgkey: &str, field_name: &str) -> Result<String, String> {
+/// * `Result<String, ReadTomlError>` - The field value or the reason it could not be read
+pub fn read_singleline_string_from_clearsigntoml(path_to_clearsigntoml_with_gpgkey: &str, field_name: &str) -> Result<String, ReadTomlError> {
     // Extract GPG key from the file
     let key = extract_gpg_key_from_clearsigntoml(path_to_clearsigntoml_with_gpgkey, "gpg_key_public")?;
 
     // Verify the file and only proceed if verification succeeds
-    let verification_result = verify_clearsign(path_to_clearsigntoml_with_gpgkey, &key)?;
+    let verification_result = verify_clearsign(path_to_clearsigntoml_with_gpgkey, &key)
+        .map_err(|message| ReadTomlError::VerificationFailed { path: path_to_clearsigntoml_with_gpgkey.to_string(), message })?;
 
     if !verification_result {
-        return Err(format!("GPG verification failed for file: {}", path_to_clearsigntoml_with_gpgkey));
+        return Err(ReadTomlError::VerificationFailed {
+            path: path_to_clearsigntoml_with_gpgkey.to_string(),
+            message: "the signature could not be verified".to_string(),
+        });
     }
 
     // Only read the field if verification succeeded
@@ -468,16 +2045,20 @@ pub fn read_singleline_string_from_clearsigntoml(path_to_clearsigntoml_with_gpgk
 /// * `field_name` - Name of the field to read
 ///
 /// # Returns
-/// * `Result<String, String>` - The field value or an error message
-pub fn read_multiline_string_from_clearsigntoml(path: &str, field_name: &str) -> Result<String, String> {
+/// * `Result<String, ReadTomlError>` - The field value or the reason it could not be read
+pub fn read_multiline_string_from_clearsigntoml(path: &str, field_name: &str) -> Result<String, ReadTomlError> {
     // Extract GPG key from the file
     let key = extract_gpg_key_from_clearsigntoml(path, "gpg_key_public")?;
     
     // Verify the file and only proceed if verification succeeds
-    let verification_result = verify_clearsign(path, &key)?;
+    let verification_result = verify_clearsign(path, &key)
+        .map_err(|message| ReadTomlError::VerificationFailed { path: path.to_string(), message })?;
     
     if !verification_result {
-        return Err(format!("GPG verification failed for file: {}", path));
+        return Err(ReadTomlError::VerificationFailed {
+            path: path.to_string(),
+            message: "the signature could not be verified".to_string(),
+        });
     }
     
     // Only read the field if verification succeeded
@@ -491,16 +2072,20 @@ pub fn read_multiline_string_from_clearsigntoml(path: &str, field_name: &str) ->
 /// * `field_name` - Name of the field to read
 ///
 /// # Returns
-/// * `Result<Vec<u64>, String>` - The integer array or an error message
-pub fn read_integerarray_clearsigntoml(path: &str, field_name: &str) -> Result<Vec<u64>, String> {
+/// * `Result<Vec<u64>, ReadTomlError>` - The integer array or the reason it could not be read
+pub fn read_integerarray_clearsigntoml(path: &str, field_name: &str) -> Result<Vec<u64>, ReadTomlError> {
     // Extract GPG key from the file
     let key = extract_gpg_key_from_clearsigntoml(path, "gpg_key_public")?;
     
     // Verify the file and only proceed if verification succeeds
-    let verification_result = verify_clearsign(path, &key)?;
+    let verification_result = verify_clearsign(path, &key)
+        .map_err(|message| ReadTomlError::VerificationFailed { path: path.to_string(), message })?;
     
     if !verification_result {
-        return Err(format!("GPG verification failed for file: {}", path));
+        return Err(ReadTomlError::VerificationFailed {
+            path: path.to_string(),
+            message: "the signature could not be verified".to_string(),
+        });
     }
     
     // Only read the field if verification succeeded
@@ -520,7 +2105,7 @@ mod tests {
     #[test]
     fn test_clearsign_reading() {
         // This test should be run only if GPG is available
-        if !Command::new("gpg").arg("--version").status().map_or(false, |s| s.success()) {
+        if !Command::new("gpg").arg("--version").status().is_ok_and(|s| s.success()) {
             println!("Skipping GPG test because GPG is not available");
             return;
         }
@@ -575,13 +2160,96 @@ mod tests {
         // Clean up
         let _ = remove_file(test_file);
 
-        assert_eq!(values, vec!["value1", "value2", "value3"]);
+        assert_eq!(
+            values.unwrap(),
+            vec![
+                ("1".to_string(), TomlValue::String("value1".to_string())),
+                ("2".to_string(), TomlValue::String("value2".to_string())),
+                ("3".to_string(), TomlValue::String("value3".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_basename_fields_natural_order_and_values() {
+        let test_content = r#"
+prompt_10 = "ten"
+prompt_2 = '''
+two
+lines'''
+prompt_1 = [
+    1, # one
+    2,
+]
+prompt_extra = true
+other_1 = "other"
+"#;
+        let test_file = "test_basename_order.toml";
+        write(test_file, test_content).unwrap();
+
+        let fields = read_basename_fields_from_toml(test_file, "prompt");
+        let sequence = read_basename_sequence_from_toml(test_file, "prompt");
+
+        let _ = remove_file(test_file);
+
+        let fields = fields.unwrap();
+        let suffixes: Vec<&str> = fields.iter().map(|(suffix, _)| suffix.as_str()).collect();
+        assert_eq!(suffixes, vec!["1", "2", "10", "extra"]);
+        assert_eq!(fields[0].1, TomlValue::Array(vec![TomlValue::Integer(1), TomlValue::Integer(2)]));
+        assert_eq!(fields[1].1, TomlValue::String("two\nlines".to_string()));
+        assert_eq!(fields[3].1, TomlValue::Boolean(true));
+        assert_eq!(sequence, Err(ReadTomlError::NotFound { field: "prompt_3".to_string() }));
+
+        assert_eq!(natural_order("a2b", "a10b"), std::cmp::Ordering::Less);
+        assert_eq!(natural_order("9", "09"), std::cmp::Ordering::Greater);
+    }
+
+    #[test]
+    fn test_read_basename_sequence() {
+        let test_file = "test_basename_sequence.toml";
+
+        write(test_file, "prompt_2 = \"b\"\nprompt_1 = \"a\"\n[table]\nprompt_3 = \"c\"\n").unwrap();
+        let contiguous = read_basename_sequence_from_toml(test_file, "prompt");
+        write(test_file, "prompt_1 = \"a\"\nprompt_01 = \"b\"\n").unwrap();
+        let leading_zero = read_basename_sequence_from_toml(test_file, "prompt");
+        write(test_file, "prompt_1 = \"a\"\nprompt_x = \"b\"\n").unwrap();
+        let not_a_number = read_basename_sequence_from_toml(test_file, "prompt");
+        write(test_file, "prompt_1 = \"a\"\nprompt_1 = \"b\"\n").unwrap();
+        let duplicate = read_basename_fields_from_toml(test_file, "prompt");
+        write(test_file, "prompt_1 = \"a\" oops\nother = 1 oops\n").unwrap();
+        let malformed = read_basename_fields_from_toml(test_file, "prompt");
+        write(test_file, "other = 1\n").unwrap();
+        let empty = read_basename_sequence_from_toml(test_file, "prompt");
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(
+            contiguous,
+            Ok(vec![TomlValue::String("a".to_string()), TomlValue::String("b".to_string())])
+        );
+        assert_eq!(
+            leading_zero,
+            Err(ReadTomlError::NotInSequence {
+                field: "prompt_01".to_string(),
+                line: 2,
+                message: "the suffix is not a number from 1 up".to_string(),
+            })
+        );
+        assert!(matches!(not_a_number, Err(ReadTomlError::NotInSequence { field, line: 2, .. }) if field == "prompt_x"));
+        assert_eq!(
+            duplicate,
+            Err(ReadTomlError::DuplicateKey { field: "prompt_1".to_string(), first_line: 1, duplicate_line: 2 })
+        );
+        assert!(matches!(malformed, Err(ReadTomlError::MalformedLine { field, line: 1, .. }) if field == "prompt_1"));
+        assert_eq!(empty, Ok(vec![]));
     }
 
     #[test]
     fn test_empty_input() {
         let values = read_basename_fields_from_toml("", "prompt");
-        assert!(values.is_empty());
+        assert!(matches!(values, Err(ReadTomlError::OpenFailed { .. })));
+        let values = read_basename_fields_from_toml("config.toml", "");
+        assert!(matches!(values, Err(ReadTomlError::InvalidFieldName { .. })));
     }
     
     #[test]
@@ -607,7 +2275,7 @@ description = """
 This is a
 multi-line
 string
-"""\
+"""
 "#;
         let test_file = "test_multi.toml";
         write(test_file, test_content).unwrap();
@@ -620,7 +2288,43 @@ string
         
         std::fs::remove_file(test_file).unwrap();
     }
-    
+
+    #[test]
+    fn test_text_after_multi_line_string() {
+        let test_content = "\
+commented = \"\"\"
+kept\"\"\"  # note
+garbage = '''
+kept''' oops
+continued = \"\"\"
+kept\"\"\"\\
+";
+        let test_file = "test_multi_trailing.toml";
+        write(test_file, test_content).unwrap();
+
+        assert_eq!(read_multi_line_toml_string(test_file, "commented"), Ok("kept".to_string()));
+        assert_eq!(
+            read_multi_line_toml_string(test_file, "garbage"),
+            Err(ReadTomlError::MalformedLine {
+                field: "garbage".to_string(),
+                line: 4,
+                column: 9,
+                message: "unexpected text after value: 'oops'".to_string(),
+            })
+        );
+        assert_eq!(
+            read_multi_line_toml_string(test_file, "continued"),
+            Err(ReadTomlError::MalformedLine {
+                field: "continued".to_string(),
+                line: 6,
+                column: 8,
+                message: "unexpected text after value: '\\'".to_string(),
+            })
+        );
+
+        std::fs::remove_file(test_file).unwrap();
+    }
+
     #[test]
     fn test_integer_array() {
         let test_content = r#"
@@ -634,4 +2338,558 @@ string
         
         std::fs::remove_file(test_file).unwrap();
     }
+
+    #[test]
+    fn test_section_aware_lookup() {
+        let test_content = r#"
+            name = "root"
+
+            [server]
+            name = "server-name"
+            port = "8080"
+
+            [client]
+            name = "client-name"
+        "#;
+        let test_file = "test_sections.toml";
+        write(test_file, test_content).unwrap();
+
+        let root_name = read_single_line_string_field_from_toml(test_file, "name");
+        let server_name = read_single_line_string_field_from_toml(test_file, "server.name");
+        let client_name = read_single_line_string_field_from_toml(test_file, "client.name");
+        let client_port = read_single_line_string_field_from_toml(test_file, "client.port");
+        let bare_port = read_single_line_string_field_from_toml(test_file, "port");
+        let field_value = read_field_from_toml(test_file, "server.port");
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(root_name.unwrap(), "root");
+        assert_eq!(server_name.unwrap(), "server-name");
+        assert_eq!(client_name.unwrap(), "client-name");
+        assert!(client_port.is_err());
+        assert!(bare_port.is_err());
+        assert_eq!(field_value.unwrap(), "8080");
+    }
+
+    #[test]
+    fn test_dotted_keys_and_nested_tables() {
+        let test_content = r#"
+server.port = "80"
+
+[a.b]
+c = "nested"
+
+[a]
+b.d = "dotted in table"
+"quoted.key" = "quoted"
+"#;
+        let test_file = "test_dotted_keys.toml";
+        write(test_file, test_content).unwrap();
+
+        let server_port = read_single_line_string_field_from_toml(test_file, "server.port");
+        let nested = read_single_line_string_field_from_toml(test_file, "a.b.c");
+        let dotted = read_single_line_string_field_from_toml(test_file, "a.b.d");
+        let quoted = read_single_line_string_field_from_toml(test_file, "a.\"quoted.key\"");
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(server_port.unwrap(), "80");
+        assert_eq!(nested.unwrap(), "nested");
+        assert_eq!(dotted.unwrap(), "dotted in table");
+        assert_eq!(quoted.unwrap(), "quoted");
+    }
+
+    #[test]
+    fn test_array_of_tables_lookup() {
+        let test_content = r#"
+[[products]]
+name = "hammer"
+
+[[products]]
+name = "nail"
+sizes = [1, 2,
+         3]
+
+[products.details]
+note = "multi-line values are skipped whole"
+description = """
+[not_a_table]
+name = "not a key"
+"""
+
+[[products]]
+name = "screw"
+"#;
+        let test_file = "test_array_of_tables.toml";
+        write(test_file, test_content).unwrap();
+
+        let first = read_single_line_string_field_from_toml(test_file, "products[0].name");
+        let second = read_single_line_string_field_from_toml(test_file, "products[1].name");
+        let third = read_single_line_string_field_from_toml(test_file, "products[2].name");
+        let details = read_single_line_string_field_from_toml(test_file, "products[1].details.note");
+        let sizes = read_integer_array(test_file, "products[1].sizes");
+        let fake_key = read_single_line_string_field_from_toml(test_file, "not_a_table.name");
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(first.unwrap(), "hammer");
+        assert_eq!(second.unwrap(), "nail");
+        assert_eq!(third.unwrap(), "screw");
+        assert_eq!(details.unwrap(), "multi-line values are skipped whole");
+        assert_eq!(sizes.unwrap(), vec![1, 2, 3]);
+        assert!(fake_key.is_err());
+    }
+
+    #[test]
+    fn test_parse_toml_lookup_path() {
+        assert_eq!(
+            parse_toml_lookup_path("products[1].name").unwrap(),
+            vec![
+                TomlPathSegment::Key("products".to_string()),
+                TomlPathSegment::Index(1),
+                TomlPathSegment::Key("name".to_string()),
+            ]
+        );
+        assert_eq!(
+            parse_toml_lookup_path("'a.b' . c").unwrap(),
+            vec![
+                TomlPathSegment::Key("a.b".to_string()),
+                TomlPathSegment::Key("c".to_string()),
+            ]
+        );
+        assert!(parse_toml_lookup_path("").is_err());
+        assert!(parse_toml_lookup_path("a.").is_err());
+        assert!(parse_toml_lookup_path("a[x]").is_err());
+
+        for lookup_path in ["port", "server.port", "\"a.b\".c", "products[1].name", "\"say \\\"hi\\\"\""] {
+            let segments = parse_toml_lookup_path(lookup_path).unwrap();
+            assert_eq!(format_toml_path(&segments), lookup_path);
+        }
+    }
+
+    #[test]
+    fn test_typed_scalar_readers() {
+        let test_content = r#"
+enabled = true
+disabled = false
+decimal = -1_000
+hex = 0xDEAD_beef
+octal = 0o755
+binary = 0b1101
+ratio = 6.626e-34
+big = 1_000.5
+positive_infinity = +inf
+not_a_number = -nan
+quoted_number = "42"
+"#;
+        let test_file = "test_typed_scalars.toml";
+        write(test_file, test_content).unwrap();
+
+        let enabled = read_bool_field_from_toml(test_file, "enabled");
+        let disabled = read_bool_field_from_toml(test_file, "disabled");
+        let decimal = read_i64_field_from_toml(test_file, "decimal");
+        let hex = read_i64_field_from_toml(test_file, "hex");
+        let octal = read_i64_field_from_toml(test_file, "octal");
+        let binary = read_i64_field_from_toml(test_file, "binary");
+        let ratio = read_float_field_from_toml(test_file, "ratio");
+        let big = read_float_field_from_toml(test_file, "big");
+        let positive_infinity = read_float_field_from_toml(test_file, "positive_infinity");
+        let not_a_number = read_float_field_from_toml(test_file, "not_a_number");
+        let quoted_number = read_i64_field_from_toml(test_file, "quoted_number");
+        let integer_as_float = read_float_field_from_toml(test_file, "decimal");
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(enabled, Ok(true));
+        assert_eq!(disabled, Ok(false));
+        assert_eq!(decimal, Ok(-1000));
+        assert_eq!(hex, Ok(0xDEAD_BEEF));
+        assert_eq!(octal, Ok(0o755));
+        assert_eq!(binary, Ok(13));
+        assert_eq!(ratio, Ok(6.626e-34));
+        assert_eq!(big, Ok(1000.5));
+        assert_eq!(positive_infinity, Ok(f64::INFINITY));
+        assert!(not_a_number.unwrap().is_nan());
+        assert_eq!(
+            quoted_number,
+            Err(ReadTomlError::TypeMismatch {
+                field: "quoted_number".to_string(),
+                expected: "integer",
+                found: "string",
+            })
+        );
+        assert!(matches!(integer_as_float, Err(ReadTomlError::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_invalid_numbers_are_rejected() {
+        for bad_value in ["01", "1__0", "_1", "1_", "0x", "+0xFF", "9223372036854775808", "1.", ".5", "1e", "1.2.3", "truthy"] {
+            assert!(parse_toml_value(bad_value).is_err(), "{} should be rejected", bad_value);
+        }
+        assert_eq!(parse_toml_value("9223372036854775807"), Ok(TomlValue::Integer(i64::MAX)));
+        assert_eq!(parse_toml_value("-9223372036854775808"), Ok(TomlValue::Integer(i64::MIN)));
+    }
+
+    #[test]
+    fn test_datetime_reader() {
+        let test_content = r#"
+offset_datetime = 1979-05-27T07:32:00.999999-07:00
+utc_datetime = 1979-05-27 07:32:00Z
+local_datetime = 1979-05-27T00:32:00
+local_date = 2024-02-29
+local_time = 07:32:00.5
+bad_date = 2023-02-29
+"#;
+        let test_file = "test_datetimes.toml";
+        write(test_file, test_content).unwrap();
+
+        let offset_datetime = read_datetime_field_from_toml(test_file, "offset_datetime");
+        let utc_datetime = read_datetime_field_from_toml(test_file, "utc_datetime");
+        let local_datetime = read_datetime_field_from_toml(test_file, "local_datetime");
+        let local_date = read_datetime_field_from_toml(test_file, "local_date");
+        let local_time = read_datetime_field_from_toml(test_file, "local_time");
+        let bad_date = read_datetime_field_from_toml(test_file, "bad_date");
+
+        let _ = remove_file(test_file);
+
+        let offset_datetime = offset_datetime.unwrap();
+        assert_eq!(offset_datetime.offset, Some(TomlOffset::Minutes(-420)));
+        assert_eq!(offset_datetime.time.unwrap().nanosecond, 999_999_000);
+        assert_eq!(offset_datetime.to_string(), "1979-05-27T07:32:00.999999-07:00");
+        assert_eq!(utc_datetime.unwrap().to_string(), "1979-05-27T07:32:00Z");
+        assert_eq!(local_datetime.unwrap().offset, None);
+        assert_eq!(
+            local_date.unwrap(),
+            TomlDatetime {
+                date: Some(TomlDate { year: 2024, month: 2, day: 29 }),
+                time: None,
+                offset: None,
+            }
+        );
+        assert_eq!(local_time.unwrap().to_string(), "07:32:00.5");
+        assert!(matches!(bad_date, Err(ReadTomlError::MalformedLine { line: 7, .. })));
+    }
+
+    #[test]
+    fn test_string_and_mixed_arrays() {
+        let test_content = r#"
+names = ["alpha", 'beta', "gamma"]
+mixed = [1, "two", 3.0, true, 1979-05-27, [4, 5]]
+not_strings = ["a", 1]
+"#;
+        let test_file = "test_typed_arrays.toml";
+        write(test_file, test_content).unwrap();
+
+        let names = read_string_array_from_toml(test_file, "names");
+        let mixed = read_array_field_from_toml(test_file, "mixed");
+        let not_strings = read_string_array_from_toml(test_file, "not_strings");
+        let missing = read_array_field_from_toml(test_file, "missing");
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(names.unwrap(), vec!["alpha", "beta", "gamma"]);
+        let mixed = mixed.unwrap();
+        assert_eq!(mixed.len(), 6);
+        assert_eq!(mixed[0], TomlValue::Integer(1));
+        assert_eq!(mixed[1].as_str(), Some("two"));
+        assert_eq!(mixed[2], TomlValue::Float(3.0));
+        assert_eq!(mixed[3], TomlValue::Boolean(true));
+        assert_eq!(mixed[4].type_name(), "datetime");
+        assert_eq!(
+            mixed[5],
+            TomlValue::Array(vec![TomlValue::Integer(4), TomlValue::Integer(5)])
+        );
+        assert!(matches!(not_strings, Err(ReadTomlError::TypeMismatch { found: "integer", .. })));
+        assert_eq!(missing, Err(ReadTomlError::NotFound { field: "missing".to_string() }));
+    }
+
+    #[test]
+    fn test_string_escapes_and_literal_strings() {
+        let test_content = r#"
+quoted = "a \"quoted\" word"
+escapes = "tab\there\nnew line \\ \u00E9 \U0001F600"
+windows_path = 'C:\path\to\file'
+regex = '<\i\c*\s*>'
+bad_escape = "bad \q escape"
+"#;
+        let test_file = "test_string_escapes.toml";
+        write(test_file, test_content).unwrap();
+
+        let quoted = read_single_line_string_field_from_toml(test_file, "quoted");
+        let quoted_field = read_field_from_toml(test_file, "quoted");
+        let escapes = read_single_line_string_field_from_toml(test_file, "escapes");
+        let windows_path = read_single_line_string_field_from_toml(test_file, "windows_path");
+        let windows_path_field = read_field_from_toml(test_file, "windows_path");
+        let regex = read_toml_value(test_file, "regex");
+        let bad_escape = read_single_line_string_field_from_toml(test_file, "bad_escape");
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(quoted.unwrap(), "a \"quoted\" word");
+        assert_eq!(quoted_field.unwrap(), "a \"quoted\" word");
+        assert_eq!(escapes.unwrap(), "tab\there\nnew line \\ \u{e9} \u{1F600}");
+        assert_eq!(windows_path.unwrap(), "C:\\path\\to\\file");
+        assert_eq!(windows_path_field.unwrap(), "C:\\path\\to\\file");
+        assert_eq!(regex, Ok(TomlValue::String("<\\i\\c*\\s*>".to_string())));
+        assert!(bad_escape.unwrap_err().to_string().contains("invalid escape"));
+    }
+
+    #[test]
+    fn test_multi_line_string_kinds() {
+        let test_content = "\
+folded = \"\"\"
+The quick brown \\
+    fox jumps over \\
+
+    the lazy dog.\"\"\"
+literal = '''
+C:\\no\\escapes\\here
+  indented line
+'''
+quotes_inside = \"\"\"Here are two quotation marks: \"\". Simple enough.\"\"\"
+ends_with_quote = \"\"\"She said \"hi\"\"\"\"
+";
+        let test_file = "test_multi_line_kinds.toml";
+        write(test_file, test_content).unwrap();
+
+        let folded = read_toml_value(test_file, "folded");
+        let literal = read_toml_value(test_file, "literal");
+        let literal_trimmed = read_multi_line_toml_string(test_file, "literal");
+        let quotes_inside = read_toml_value(test_file, "quotes_inside");
+        let ends_with_quote = read_toml_value(test_file, "ends_with_quote");
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(folded.unwrap().as_str(), Some("The quick brown fox jumps over the lazy dog."));
+        assert_eq!(literal.unwrap().as_str(), Some("C:\\no\\escapes\\here\n  indented line\n"));
+        assert_eq!(literal_trimmed.unwrap(), "C:\\no\\escapes\\here\nindented line");
+        assert_eq!(
+            quotes_inside.unwrap().as_str(),
+            Some("Here are two quotation marks: \"\". Simple enough.")
+        );
+        assert_eq!(ends_with_quote.unwrap().as_str(), Some("She said \"hi\""));
+    }
+
+    #[test]
+    fn test_decode_toml_string_errors() {
+        assert!(decode_toml_string("\"\\u12\"").is_err());
+        assert!(decode_toml_string("\"\\uD800\"").is_err());
+        assert!(decode_toml_string("\"\"\"a \\  b\"\"\"").is_err());
+        assert!(decode_toml_string("plain").is_err());
+        assert_eq!(decode_toml_string("''"), Ok(String::new()));
+        assert_eq!(decode_toml_string("\"\"\"\n\"\"\""), Ok(String::new()));
+    }
+
+    #[test]
+    fn test_inline_comments_outside_strings() {
+        let test_content = r#"
+name = "value" # the name
+hash = "not # a comment"   # but this is
+literal = 'C:\temp' # path
+ids = [1, 2] # ids
+port = 8080 # bare value
+prompt_1 = "first" # one
+prompt_2 = "second"
+notes = """
+keep # this
+""" # after the closing quotes
+"#;
+        let test_file = "test_inline_comments.toml";
+        write(test_file, test_content).unwrap();
+
+        assert_eq!(read_single_line_string_field_from_toml(test_file, "name"), Ok("value".to_string()));
+        assert_eq!(read_single_line_string_field_from_toml(test_file, "hash"), Ok("not # a comment".to_string()));
+        assert_eq!(read_single_line_string_field_from_toml(test_file, "literal"), Ok("C:\\temp".to_string()));
+        assert_eq!(read_single_line_string_field_from_toml(test_file, "port"), Ok("8080".to_string()));
+        assert_eq!(read_field_from_toml(test_file, "hash"), Ok("not # a comment".to_string()));
+        assert_eq!(read_integer_array(test_file, "ids"), Ok(vec![1, 2]));
+        assert_eq!(read_i64_field_from_toml(test_file, "port"), Ok(8080));
+        assert_eq!(read_basename_sequence_from_toml(test_file, "prompt").unwrap(), vec![
+            TomlValue::String("first".to_string()),
+            TomlValue::String("second".to_string()),
+        ]);
+        assert_eq!(read_multi_line_toml_string(test_file, "notes"), Ok("keep # this".to_string()));
+
+        std::fs::remove_file(test_file).unwrap();
+    }
+
+    #[test]
+    fn test_text_after_closing_quote_is_rejected() {
+        let test_content = "good = \"fine\"\nname = \"value\" garbage\n[server]\nlist = [1, 2] 3\n";
+        let test_file = "test_trailing_garbage.toml";
+        write(test_file, test_content).unwrap();
+
+        assert_eq!(
+            read_single_line_string_field_from_toml(test_file, "name"),
+            Err(ReadTomlError::MalformedLine {
+                field: "name".to_string(),
+                line: 2,
+                column: 16,
+                message: "unexpected text after value: 'garbage'".to_string(),
+            })
+        );
+        assert!(read_field_from_toml(test_file, "name").is_err());
+        assert!(matches!(
+            read_toml_value(test_file, "name"),
+            Err(ReadTomlError::MalformedLine { line: 2, column: 16, .. })
+        ));
+        assert_eq!(
+            read_integer_array(test_file, "server.list"),
+            Err(ReadTomlError::MalformedLine {
+                field: "server.list".to_string(),
+                line: 4,
+                column: 15,
+                message: "unexpected text after value: '3'".to_string(),
+            })
+        );
+
+        // Other fields are still readable around the bad lines
+        assert_eq!(read_single_line_string_field_from_toml(test_file, "good"), Ok("fine".to_string()));
+
+        std::fs::remove_file(test_file).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_value_column() {
+        let test_content = "count = 12abc\nlist = [1, 2, x]\n";
+        let test_file = "test_value_column.toml";
+        write(test_file, test_content).unwrap();
+
+        assert!(matches!(
+            read_toml_value(test_file, "count"),
+            Err(ReadTomlError::MalformedLine { line: 1, column: 9, .. })
+        ));
+        assert!(matches!(
+            read_toml_value(test_file, "list"),
+            Err(ReadTomlError::MalformedLine { line: 2, column: 15, .. })
+        ));
+
+        std::fs::remove_file(test_file).unwrap();
+    }
+
+    #[test]
+    fn test_multi_line_and_nested_integer_arrays() {
+        let test_content = r#"
+numbers = [
+    1,
+    2, # second
+    # a whole-line comment
+    3,
+]
+matrix = [
+    [1, 2],
+    [3, 4, 5], # rows may differ in length
+    [],
+]
+names = [
+    "alpha",
+    'beta', # literal
+]
+negative = [-1, 2]
+mixed = [[1, 2], 3]
+"#;
+        let test_file = "test_multi_line_arrays.toml";
+        write(test_file, test_content).unwrap();
+
+        assert_eq!(read_integer_array(test_file, "numbers"), Ok(vec![1, 2, 3]));
+        assert_eq!(read_i64_array_from_toml(test_file, "numbers"), Ok(vec![1, 2, 3]));
+        assert_eq!(
+            read_nested_i64_array_from_toml(test_file, "matrix"),
+            Ok(vec![vec![1, 2], vec![3, 4, 5], vec![]])
+        );
+        assert_eq!(
+            read_string_array_from_toml(test_file, "names"),
+            Ok(vec!["alpha".to_string(), "beta".to_string()])
+        );
+
+        assert_eq!(read_i64_array_from_toml(test_file, "negative"), Ok(vec![-1, 2]));
+        assert!(read_integer_array(test_file, "negative").is_err());
+        assert!(read_integer_array(test_file, "matrix").is_err());
+        let mixed = read_nested_i64_array_from_toml(test_file, "mixed");
+        assert!(matches!(mixed, Err(ReadTomlError::TypeMismatch { expected: "array", found: "integer", .. })));
+        assert_eq!(mixed.unwrap_err().to_string(), "Field 'mixed' is an integer, expected an array");
+        assert_eq!(
+            read_integer_array(test_file, "missing"),
+            Err(ReadTomlError::NotFound { field: "missing".to_string() })
+        );
+
+        std::fs::remove_file(test_file).unwrap();
+    }
+
+    #[test]
+    fn test_read_toml_error_kinds() {
+        let test_content = "empty = \"\"\nname = \"first\"\nname = \"second\"\ncount = 3\n";
+        let test_file = "test_error_kinds.toml";
+        write(test_file, test_content).unwrap();
+
+        assert_eq!(read_field_from_toml(test_file, "empty"), Ok(String::new()));
+        assert_eq!(
+            read_field_from_toml(test_file, "missing"),
+            Err(ReadTomlError::NotFound { field: "missing".to_string() })
+        );
+        assert_eq!(
+            read_field_from_toml(test_file, "name"),
+            Err(ReadTomlError::DuplicateKey { field: "name".to_string(), first_line: 2, duplicate_line: 3 })
+        );
+        assert!(matches!(
+            read_multi_line_toml_string(test_file, "count"),
+            Err(ReadTomlError::TypeMismatch { expected: "multi-line string", found: "integer", .. })
+        ));
+        assert!(matches!(
+            read_field_from_toml(test_file, "a..b"),
+            Err(ReadTomlError::InvalidFieldName { .. })
+        ));
+        assert!(matches!(
+            read_field_from_toml("missing_file.toml", "name"),
+            Err(ReadTomlError::OpenFailed { .. })
+        ));
+
+        let _ = remove_file(test_file);
+    }
+
+    static LOGGED_MESSAGES: std::sync::Mutex<Vec<String>> = std::sync::Mutex::new(Vec::new());
+
+    #[test]
+    fn test_log_hook_receives_skipped_lines() {
+        let test_content = "this line is not toml\nname = \"value\"\n";
+        let test_file = "test_log_hook.toml";
+        write(test_file, test_content).unwrap();
+
+        set_read_toml_log_hook(Some(|message| LOGGED_MESSAGES.lock().unwrap().push(message.to_string())));
+        let name = read_field_from_toml(test_file, "name");
+        set_read_toml_log_hook(None);
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(name, Ok("value".to_string()));
+        let logged_messages = LOGGED_MESSAGES.lock().unwrap();
+        assert!(logged_messages.iter().any(|message| message.starts_with("Skipping malformed TOML: line 1")));
+    }
+
+    #[test]
+    fn test_toml_value_display_round_trips() {
+        let raw_values = [
+            "\"tab\\there \\\"quoted\\\" \\\\ \\u0001\"",
+            "-42",
+            "1.0",
+            "6.02e23",
+            "-inf",
+            "nan",
+            "true",
+            "1979-05-27T07:32:00Z",
+            "[1, \"two\", [3.5]]",
+        ];
+        for raw_value in raw_values {
+            let value = parse_toml_value(raw_value).unwrap();
+            let written = value.to_string();
+            let reparsed = parse_toml_value(&written).unwrap();
+            if let TomlValue::Float(number) = value {
+                assert!(number.is_nan() || reparsed == value, "{}", raw_value);
+            } else {
+                assert_eq!(reparsed, value, "{} was written as {}", raw_value, written);
+            }
+        }
+        assert_eq!(TomlValue::Float(3.0).to_string(), "3.0");
+        assert_eq!(TomlValue::String("a\nb".to_string()).to_string(), "\"a\\nb\"");
+    }
 }
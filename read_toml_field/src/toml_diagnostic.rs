@@ -0,0 +1,163 @@
+//! Caret-style diagnostics for TOML values and errors.
+//!
+//! Turns a [`TomlSpan`] (or a [`ReadTomlError`] that points into a file)
+//! into the kind of message a compiler prints, so a binary can show the
+//! operator exactly which value to fix:
+//!
+//! ```text
+//! error: Field 'port' is a string, expected an integer
+//!  --> config.toml:42:8
+//!    |
+//! 42 | port = "8080"
+//!    |        ^^^^^^
+//! ```
+
+use crate::read_toml_field::{read_toml_file_to_string, ReadTomlError, TomlSpan};
+
+/// Renders `message` with the line of `content` that `span` starts on and
+/// a `^` under each character of the span on that line (at least one).
+/// `content` must be the text the span was taken from.
+///
+/// # Example
+/// ```
+/// # use read_toml_field::read_toml_field::TomlSpan;
+/// # use read_toml_field::toml_diagnostic::render_diagnostic;
+/// let content = "name = \"a\"\nport = \"8080\"\n";
+/// let span = TomlSpan::new(Some("config.toml"), content, 18..24);
+/// assert_eq!(
+///     render_diagnostic(content, &span, "port must be an integer"),
+///     "error: port must be an integer\n --> config.toml:2:8\n  |\n2 | port = \"8080\"\n  |        ^^^^^^"
+/// );
+/// ```
+pub fn render_diagnostic(content: &str, span: &TomlSpan, message: &str) -> String {
+    let start = span.byte_range.start.min(content.len());
+    let line_start = content[..start].rfind('\n').map_or(0, |newline| newline + 1);
+    let line_end = content[start..].find('\n').map_or(content.len(), |newline| start + newline);
+    let line_text = content[line_start..line_end].trim_end_matches('\r');
+
+    // Keep tabs in the padding so the carets line up under the text
+    let padding: String = content[line_start..start]
+        .chars()
+        .map(|character| if character == '\t' { '\t' } else { ' ' })
+        .collect();
+    let underlined_end = span.byte_range.end.clamp(start, line_start + line_text.len());
+    let caret_count = content[start..underlined_end].chars().count().max(1);
+
+    let line_number = span.line.to_string();
+    let gutter = " ".repeat(line_number.len());
+    format!(
+        "error: {}\n{}--> {}\n{} |\n{} | {}\n{} | {}{}",
+        message,
+        gutter,
+        span,
+        gutter,
+        line_number,
+        line_text,
+        gutter,
+        padding,
+        "^".repeat(caret_count)
+    )
+}
+
+/// Renders `message` at `span`, reading the text from `span.file`. Without
+/// a file, or if the file cannot be read, only the message and the
+/// position are shown.
+pub fn render_span_message(span: &TomlSpan, message: &str) -> String {
+    match span.file.as_deref().map(read_toml_file_to_string) {
+        Some(Ok(content)) => render_diagnostic(&content, span, message),
+        _ => format!("error: {}\n --> {}", message, span),
+    }
+}
+
+/// Renders an error returned for the file at `path`: with the offending
+/// line and carets when the error points into the file (see
+/// [`ReadTomlError::span`]), as a plain `error: ...` line otherwise.
+///
+/// # Example
+/// ```
+/// # use read_toml_field::read_toml_field::read_i64_field_from_toml;
+/// # use read_toml_field::toml_diagnostic::render_read_error;
+/// let error = read_i64_field_from_toml("config.toml", "server.name").unwrap_err();
+/// let diagnostic = render_read_error("config.toml", &error);
+/// assert!(diagnostic.contains(" --> config.toml:"));
+/// assert!(diagnostic.ends_with("^^^^^^^^^^^^^^^"));
+/// ```
+pub fn render_read_error(path: &str, error: &ReadTomlError) -> String {
+    let Ok(content) = read_toml_file_to_string(path) else {
+        return format!("error: {}", error);
+    };
+    match error.span(path, &content) {
+        Some(span) => render_diagnostic(&content, &span, &error.to_string()),
+        None => format!("error: {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_toml_field::{read_i64_field_from_toml, read_toml_value_with_span};
+    use crate::toml_document::TomlDocument;
+    use std::fs::{remove_file, write};
+
+    #[test]
+    fn test_render_read_errors() {
+        let test_file = "test_diagnostic_errors.toml";
+        write(
+            test_file,
+            "name = \"a\"\n\tport = \"80\"\nlist = [1, 2 3]\nname = \"b\"\n[server]\nhost = 'x'\n[server]\n",
+        )
+        .unwrap();
+
+        let type_mismatch = render_read_error(test_file, &read_i64_field_from_toml(test_file, "port").unwrap_err());
+        let malformed = render_read_error(test_file, &read_i64_field_from_toml(test_file, "list").unwrap_err());
+        let duplicate = render_read_error(test_file, &read_i64_field_from_toml(test_file, "name").unwrap_err());
+        let redefined = render_read_error(
+            test_file,
+            &TomlDocument::load_strict(test_file, None).unwrap_err().pop().unwrap(),
+        );
+        let not_found = render_read_error(test_file, &read_i64_field_from_toml(test_file, "missing").unwrap_err());
+        let (_, span) = read_toml_value_with_span(test_file, "server.host").unwrap();
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(
+            type_mismatch,
+            format!(
+                "error: Field 'port' is a string, expected an integer\n --> {}:2:9\n  |\n2 | \tport = \"80\"\n  | \t       ^^^^",
+                test_file
+            )
+        );
+        assert_eq!(
+            malformed,
+            format!(
+                "error: Invalid value for field 'list' at line 3, column 14: expected ',' or ']' in array, found '3]'\n --> {}:3:14\n  |\n3 | list = [1, 2 3]\n  |              ^",
+                test_file
+            )
+        );
+        assert!(duplicate.ends_with("4 | name = \"b\"\n  | ^^^^^^^^^^"));
+        assert!(redefined.ends_with("7 | [server]\n  | ^^^^^^^^"));
+        assert_eq!(not_found, "error: Field 'missing' not found");
+        assert_eq!(span, TomlSpan { file: Some(test_file.to_string()), line: 6, column: 8, byte_range: 67..70 });
+    }
+
+    #[test]
+    fn test_render_span_of_document_entry() {
+        let test_file = "test_diagnostic_entry.toml";
+        write(test_file, "[server]\nports = [\n    80,\n]\n").unwrap();
+
+        let document = TomlDocument::load(test_file).unwrap();
+        let span = document.entry("server.ports").unwrap().span();
+        let rendered = render_span_message(&span, "too few ports");
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(span.to_string(), format!("{}:2:9", test_file));
+        assert_eq!(span.byte_range, 17..28);
+        // Only the first line of a multi-line value is shown
+        assert!(rendered.ends_with("2 | ports = [\n  |         ^"));
+
+        let parsed = TomlDocument::parse("a = 1\n");
+        let span = parsed.entry("a").unwrap().span();
+        assert_eq!(render_span_message(&span, "bad"), "error: bad\n --> 1:5");
+    }
+}
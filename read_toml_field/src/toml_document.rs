@@ -0,0 +1,916 @@
+//! Parse a TOML file once, then read many fields from memory.
+//!
+//! The `read_*` functions in [`crate::read_toml_field`] open and scan the
+//! file on every call. A [`TomlDocument`] does that once and keeps the
+//! tables and values in memory, with the same getters and errors.
+//!
+//! Like the readers, this module uses only the standard library. Copy it
+//! next to `read_toml_field.rs` to use it in another project.
+
+use crate::read_toml_field::{
+    check_multi_line_string,
+    format_toml_path,
+    integer_elements,
+    line_and_column,
+    log_read_toml,
+    parse_field_path,
+    parse_toml_value,
+    read_toml_file_to_string,
+    string_value_text,
+    trim_multi_line_text,
+    type_mismatch,
+    unsigned_integers,
+    ReadTomlError,
+    ScannedTomlEntry,
+    ScannedTomlItem,
+    TomlDatetime,
+    TomlEntryScanner,
+    TomlPathSegment,
+    TomlScanError,
+    TomlSpan,
+    TomlValue,
+};
+
+/// A TOML file parsed once into a tree of tables.
+///
+/// The getters take the same field names as the `read_*` functions
+/// (`port`, `server.port`, `products[1].name`) and return the same values
+/// and [`ReadTomlError`]s. Lines that are not valid TOML are skipped and
+/// reported to the log hook, as the readers do, and reading a field that
+/// sits on such a line, or that is defined twice, is an error.
+///
+/// # Example
+/// ```
+/// # use read_toml_field::toml_document::TomlDocument;
+/// let document = TomlDocument::load("config.toml")?;
+///
+/// let server_name = document.get_string("server.name")?;
+/// let log_enabled = document.get_bool("log")?;
+/// assert!(document.contains("server.port"));
+/// assert!(document.table("server").is_some_and(|server| server.keys().contains(&"name")));
+/// # Ok::<(), read_toml_field::read_toml_field::ReadTomlError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TomlDocument {
+    root: TomlTable,
+    /// Fields that cannot be read, by full path.
+    problems: Vec<(Vec<TomlPathSegment>, TomlFieldProblem)>,
+}
+
+/// Why a field of a [`TomlDocument`] cannot be read.
+#[derive(Debug, Clone, PartialEq)]
+enum TomlFieldProblem {
+    Malformed(TomlScanError),
+    Duplicate { first_line: usize, duplicate_line: usize },
+}
+
+/// A table of a [`TomlDocument`], with its keys in file order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TomlTable {
+    entries: Vec<(String, TomlItem)>,
+    definition: TableDefinition,
+}
+
+/// How a table came to exist, for the strict-mode redefinition checks.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum TableDefinition {
+    /// Only as the parent of another table, e.g. `a` for `[a.b]`
+    #[default]
+    Implicit,
+    /// By a `[table]` or `[[table]]` header on this line
+    Header(usize),
+    /// By dotted keys such as `a.b = 1` on this line
+    DottedKeys(usize),
+}
+
+/// What a key of a [`TomlTable`] holds.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TomlItem {
+    Value(TomlEntry),
+    Table(TomlTable),
+    ArrayOfTables(Vec<TomlTable>),
+}
+
+/// A value of a [`TomlDocument`], together with its text as written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlEntry {
+    /// The parsed value, or why the raw text is not a valid TOML value
+    value: Result<TomlValue, TomlScanError>,
+    raw_value: String,
+    line_number: usize,
+    /// Line and column where the value starts
+    value_position: (usize, usize),
+    /// Bytes of the value as written in its file
+    value_range: std::ops::Range<usize>,
+    /// File the value was read from, if it came from a file
+    file: Option<String>,
+}
+
+impl TomlEntry {
+    /// The parsed value, or `None` if the text is not a valid TOML value.
+    pub fn value(&self) -> Option<&TomlValue> {
+        self.value.as_ref().ok()
+    }
+
+    /// The value as written in the file (quotes, brackets and all).
+    pub fn raw_value(&self) -> &str {
+        &self.raw_value
+    }
+
+    /// 1-based line on which the key appears.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Path of the file the value was read from, as given to
+    /// [`TomlDocument::load`]; `None` for a parsed string.
+    pub fn file(&self) -> Option<&str> {
+        self.file.as_deref()
+    }
+
+    /// Where the value as written sits in its file, see [`TomlSpan`].
+    pub fn span(&self) -> TomlSpan {
+        TomlSpan {
+            file: self.file.clone(),
+            line: self.value_position.0,
+            column: self.value_position.1,
+            byte_range: self.value_range.clone(),
+        }
+    }
+
+    /// `file:line` of the key, or `line N` without a file, for messages.
+    pub fn location(&self) -> String {
+        match &self.file {
+            Some(file) => format!("{}:{}", file, self.line_number),
+            None => format!("line {}", self.line_number),
+        }
+    }
+}
+
+impl TomlTable {
+    /// Keys of this table, in the order they first appear in the file.
+    pub fn keys(&self) -> Vec<&str> {
+        self.entries.iter().map(|(key, _)| key.as_str()).collect()
+    }
+
+    /// The value, table or array of tables stored under `key`.
+    pub fn get(&self, key: &str) -> Option<&TomlItem> {
+        self.entries
+            .iter()
+            .find(|(known_key, _)| known_key == key)
+            .map(|(_, item)| item)
+    }
+
+    /// The sub-table for `key`, creating it (or, with `index`, the array
+    /// of tables element) if needed. A key that already holds a value or
+    /// the other kind of table is an error carrying the line where it was
+    /// first defined.
+    fn child_table_mut(&mut self, key: &str, index: Option<usize>) -> Result<&mut TomlTable, usize> {
+        let position = match self.entries.iter().position(|(known_key, _)| known_key == key) {
+            Some(position) => position,
+            None => {
+                let item = match index {
+                    Some(_) => TomlItem::ArrayOfTables(Vec::new()),
+                    None => TomlItem::Table(TomlTable::default()),
+                };
+                self.entries.push((key.to_string(), item));
+                self.entries.len() - 1
+            }
+        };
+
+        match (&mut self.entries[position].1, index) {
+            (TomlItem::Table(table), None) => Ok(table),
+            (TomlItem::ArrayOfTables(tables), Some(index)) => {
+                if index == tables.len() {
+                    tables.push(TomlTable::default());
+                }
+                let first_line = tables.first().map_or(0, TomlTable::definition_line);
+                tables.get_mut(index).ok_or(first_line)
+            }
+            (TomlItem::Value(entry), _) => Err(entry.line_number),
+            (TomlItem::Table(table), Some(_)) => Err(table.definition_line()),
+            (TomlItem::ArrayOfTables(tables), None) => {
+                Err(tables.first().map_or(0, TomlTable::definition_line))
+            }
+        }
+    }
+
+    /// Merges `other` into this table: sub-tables present in both are
+    /// merged key by key, anything else in `other` replaces what this
+    /// table holds under the same key.
+    fn merge(&mut self, other: TomlTable) {
+        for (key, item) in other.entries {
+            match self.entries.iter_mut().find(|(known_key, _)| *known_key == key) {
+                Some((_, TomlItem::Table(table))) if matches!(item, TomlItem::Table(_)) => {
+                    if let TomlItem::Table(other_table) = item {
+                        table.merge(other_table);
+                    }
+                }
+                Some((_, existing_item)) => *existing_item = item,
+                None => self.entries.push((key, item)),
+            }
+        }
+    }
+
+    /// Records `file` as the source of every value in this table.
+    fn set_file(&mut self, file: &str) {
+        for (_, item) in &mut self.entries {
+            match item {
+                TomlItem::Value(entry) => entry.file = Some(file.to_string()),
+                TomlItem::Table(table) => table.set_file(file),
+                TomlItem::ArrayOfTables(tables) => tables.iter_mut().for_each(|table| table.set_file(file)),
+            }
+        }
+    }
+
+    fn definition_line(&self) -> usize {
+        match self.definition {
+            TableDefinition::Implicit => 0,
+            TableDefinition::Header(line) | TableDefinition::DottedKeys(line) => line,
+        }
+    }
+}
+
+/// Where a lookup path leads inside a [`TomlDocument`].
+enum TomlNode<'a> {
+    Table(&'a TomlTable),
+    ArrayOfTables(&'a [TomlTable]),
+    Value(&'a TomlEntry),
+}
+
+impl TomlDocument {
+    /// Reads and parses a TOML file.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the TOML file
+    ///
+    /// # Returns
+    /// * `Result<TomlDocument, ReadTomlError>` - The document, or `OpenFailed`
+    pub fn load(path: &str) -> Result<TomlDocument, ReadTomlError> {
+        let content = read_toml_file_to_string(path)?;
+        let (mut document, problems) = TomlDocument::parse_with_problems(&content, None);
+        for problem in &problems {
+            log_read_toml(&format!("Invalid TOML in '{}': {}", path, problem));
+        }
+        document.root.set_file(path);
+        Ok(document)
+    }
+
+    /// Parses TOML text. Problems are sent to the log hook and kept with
+    /// the fields they affect, which report them when read.
+    pub fn parse(content: &str) -> TomlDocument {
+        let (document, problems) = TomlDocument::parse_with_problems(content, None);
+        for problem in &problems {
+            log_read_toml(&format!("Invalid TOML: {}", problem));
+        }
+        document
+    }
+
+    /// Reads and parses a TOML file in strict mode, see
+    /// [`TomlDocument::parse_strict`].
+    pub fn load_strict(path: &str, allowed_keys: Option<&[&str]>) -> Result<TomlDocument, Vec<ReadTomlError>> {
+        let content = read_toml_file_to_string(path).map_err(|e| vec![e])?;
+        TomlDocument::parse_strict(&content, allowed_keys)
+    }
+
+    /// Parses TOML text in strict mode: any line that is not valid TOML, a
+    /// key defined twice, a table defined twice (or a key used both as a
+    /// value and as a table), and, with `allowed_keys`, any key not in that
+    /// list is an error. All problems are returned at once, in line order.
+    ///
+    /// `allowed_keys` holds full dotted keys such as `server.port`. Keys in
+    /// an array of tables are listed without an index (`products.name`
+    /// allows `name` in every `[[products]]`).
+    ///
+    /// # Example
+    /// ```
+    /// # use read_toml_field::toml_document::TomlDocument;
+    /// # use read_toml_field::read_toml_field::ReadTomlError;
+    /// let content = "name = \"a\"\nname = \"b\"\ncolour = \"red\"\n";
+    /// let problems = TomlDocument::parse_strict(content, Some(&["name"])).unwrap_err();
+    /// assert_eq!(problems, vec![
+    ///     ReadTomlError::DuplicateKey { field: "name".to_string(), first_line: 1, duplicate_line: 2 },
+    ///     ReadTomlError::UnknownKey { field: "colour".to_string(), line: 3 },
+    /// ]);
+    /// ```
+    pub fn parse_strict(content: &str, allowed_keys: Option<&[&str]>) -> Result<TomlDocument, Vec<ReadTomlError>> {
+        let (document, problems) = TomlDocument::parse_with_problems(content, allowed_keys);
+        if problems.is_empty() {
+            Ok(document)
+        } else {
+            Err(problems)
+        }
+    }
+
+    /// Builds the document in one pass over `content`, collecting every
+    /// problem that strict mode rejects.
+    fn parse_with_problems(content: &str, allowed_keys: Option<&[&str]>) -> (TomlDocument, Vec<ReadTomlError>) {
+        let mut document = TomlDocument::default();
+        let mut problems = Vec::new();
+
+        // Allowed keys are compared without array indexes
+        let allowed_keys: Option<Vec<String>> = allowed_keys.map(|allowed_keys| {
+            allowed_keys
+                .iter()
+                .filter_map(|allowed_key| match parse_field_path(allowed_key) {
+                    Ok(allowed_path) => Some(format_toml_path(&without_indexes(&allowed_path))),
+                    Err(e) => {
+                        problems.push(e);
+                        None
+                    }
+                })
+                .collect()
+        });
+
+        let mut scanner = TomlEntryScanner::new(content);
+        while let Some(item_result) = scanner.next_item() {
+            match item_result {
+                Ok(ScannedTomlItem::TableHeader { table_path, line_number }) => {
+                    if let Err(problem) = document.define_table(&table_path, line_number) {
+                        problems.push(problem);
+                    }
+                }
+                Ok(ScannedTomlItem::Entry(entry)) => {
+                    if let Some(allowed_keys) = &allowed_keys {
+                        let field = format_toml_path(&without_indexes(&entry.full_path));
+                        if !allowed_keys.contains(&field) {
+                            problems.push(ReadTomlError::UnknownKey { field, line: entry.line_number });
+                        }
+                    }
+                    if let Err(problem) = document.insert_entry(content, entry) {
+                        problems.push(problem);
+                    }
+                }
+                Err(e) => match e.full_path.clone() {
+                    Some(full_path) => {
+                        problems.push(ReadTomlError::MalformedLine {
+                            field: format_toml_path(&full_path),
+                            line: e.line,
+                            column: e.column,
+                            message: e.message.clone(),
+                        });
+                        document.problems.push((full_path, TomlFieldProblem::Malformed(e)));
+                    }
+                    None => problems.push(ReadTomlError::InvalidLine {
+                        line: e.line,
+                        column: e.column,
+                        message: e.message,
+                    }),
+                },
+            }
+        }
+
+        (document, problems)
+    }
+
+    /// Creates the table of a `[table]` or `[[table]]` header, which must
+    /// not have been defined before by another header or by dotted keys.
+    /// The path of a `[[table]]` header ends with the index of a new
+    /// element, so only a repeated `[table]` can be a redefinition.
+    fn define_table(&mut self, table_path: &[TomlPathSegment], line_number: usize) -> Result<(), ReadTomlError> {
+        let table_redefined = |table: &[TomlPathSegment], first_line: usize| ReadTomlError::TableRedefined {
+            table: format_toml_path(table),
+            first_line,
+            duplicate_line: line_number,
+        };
+
+        let table = table_at_mut(&mut self.root, table_path)
+            .map_err(|(depth, first_line)| table_redefined(&table_path[..depth], first_line))?;
+
+        match table.definition {
+            TableDefinition::Implicit => {
+                table.definition = TableDefinition::Header(line_number);
+                Ok(())
+            }
+            TableDefinition::Header(first_line) | TableDefinition::DottedKeys(first_line) => {
+                Err(table_redefined(table_path, first_line))
+            }
+        }
+    }
+
+    /// Adds an entry to its table, creating the tables of a dotted key.
+    fn insert_entry(&mut self, content: &str, entry: ScannedTomlEntry) -> Result<(), ReadTomlError> {
+        let value_position = line_and_column(content, entry.value_offset);
+        let value = parse_toml_value(entry.raw_value).map_err(|(offset, message)| {
+            let (line, column) = line_and_column(content, entry.value_offset + offset);
+            TomlScanError { full_path: Some(entry.full_path.clone()), line, column, message }
+        });
+        let toml_entry = TomlEntry {
+            value,
+            raw_value: entry.raw_value.to_string(),
+            line_number: entry.line_number,
+            value_position,
+            value_range: entry.value_offset..entry.value_offset + entry.raw_value.len(),
+            file: None,
+        };
+
+        let full_path = &entry.full_path;
+        let table_redefined = |table: &[TomlPathSegment], first_line: usize| ReadTomlError::TableRedefined {
+            table: format_toml_path(table),
+            first_line,
+            duplicate_line: entry.line_number,
+        };
+
+        // The header's table, then one table per dotted key part
+        let (key, parent_path) = match full_path.split_last() {
+            Some((TomlPathSegment::Key(key), parent_path)) => (key, parent_path),
+            _ => return Ok(()),
+        };
+        let header_depth = full_path.len() - entry.key_segment_count;
+        let mut table = table_at_mut(&mut self.root, &full_path[..header_depth])
+            .map_err(|(depth, first_line)| table_redefined(&full_path[..depth], first_line))?;
+        for (depth, segment) in parent_path.iter().enumerate().skip(header_depth) {
+            let TomlPathSegment::Key(dotted_key) = segment else {
+                return Ok(());
+            };
+            table = table
+                .child_table_mut(dotted_key, None)
+                .map_err(|first_line| table_redefined(&full_path[..=depth], first_line))?;
+            match table.definition {
+                TableDefinition::Implicit => table.definition = TableDefinition::DottedKeys(entry.line_number),
+                TableDefinition::DottedKeys(_) => {}
+                TableDefinition::Header(first_line) => {
+                    return Err(table_redefined(&full_path[..=depth], first_line));
+                }
+            }
+        }
+
+        match table.get(key) {
+            None => {
+                table.entries.push((key.clone(), TomlItem::Value(toml_entry)));
+                Ok(())
+            }
+            Some(TomlItem::Value(first_entry)) => {
+                let first_line = first_entry.line_number;
+                self.problems.push((
+                    full_path.clone(),
+                    TomlFieldProblem::Duplicate { first_line, duplicate_line: entry.line_number },
+                ));
+                Err(ReadTomlError::DuplicateKey {
+                    field: format_toml_path(full_path),
+                    first_line,
+                    duplicate_line: entry.line_number,
+                })
+            }
+            Some(TomlItem::Table(existing_table)) => Err(table_redefined(full_path, existing_table.definition_line())),
+            Some(TomlItem::ArrayOfTables(tables)) => {
+                Err(table_redefined(full_path, tables.first().map_or(0, TomlTable::definition_line)))
+            }
+        }
+    }
+
+    /// Merges `other` on top of this document, as when a `local.toml`
+    /// overrides a `base.toml`: tables are merged key by key and any
+    /// value, array or array of tables in `other` replaces the one here.
+    /// A field of this document that could not be read stops being an
+    /// error once `other` gives it a value.
+    ///
+    /// # Example
+    /// ```
+    /// # use read_toml_field::toml_document::TomlDocument;
+    /// let mut document = TomlDocument::parse("[server]\nname = \"base\"\nport = 80\n");
+    /// document.merge(TomlDocument::parse("[server]\nport = 8080\n"));
+    /// assert_eq!(document.get_string("server.name"), Ok("base".to_string()));
+    /// assert_eq!(document.get_i64("server.port"), Ok(8080));
+    /// ```
+    pub fn merge(&mut self, other: TomlDocument) {
+        self.problems.retain(|(path, _)| !matches!(other.node(path), Some(TomlNode::Value(_))));
+        self.root.merge(other.root);
+        self.problems.extend(other.problems);
+    }
+
+    /// Removes a key of the root table, returning what it held.
+    pub(crate) fn remove_root_key(&mut self, key: &str) -> Option<TomlItem> {
+        let position = self.root.entries.iter().position(|(known_key, _)| known_key == key)?;
+        Some(self.root.entries.remove(position).1)
+    }
+
+    /// Follows a parsed lookup path from the root table.
+    fn node(&self, field_path: &[TomlPathSegment]) -> Option<TomlNode<'_>> {
+        let mut node = TomlNode::Table(&self.root);
+        for segment in field_path {
+            node = match (node, segment) {
+                (TomlNode::Table(table), TomlPathSegment::Key(key)) => match table.get(key)? {
+                    TomlItem::Value(entry) => TomlNode::Value(entry),
+                    TomlItem::Table(table) => TomlNode::Table(table),
+                    TomlItem::ArrayOfTables(tables) => TomlNode::ArrayOfTables(tables),
+                },
+                (TomlNode::ArrayOfTables(tables), TomlPathSegment::Index(index)) => {
+                    TomlNode::Table(tables.get(*index)?)
+                }
+                _ => return None,
+            };
+        }
+        Some(node)
+    }
+
+    /// The entry for `field_name`, with its raw text and line number, or
+    /// the same error the readers return.
+    pub fn entry(&self, field_name: &str) -> Result<&TomlEntry, ReadTomlError> {
+        let field_path = parse_field_path(field_name)?;
+
+        if let Some((_, problem)) = self.problems.iter().find(|(path, _)| *path == field_path) {
+            return Err(match problem {
+                TomlFieldProblem::Malformed(e) => ReadTomlError::MalformedLine {
+                    field: field_name.to_string(),
+                    line: e.line,
+                    column: e.column,
+                    message: e.message.clone(),
+                },
+                TomlFieldProblem::Duplicate { first_line, duplicate_line } => ReadTomlError::DuplicateKey {
+                    field: field_name.to_string(),
+                    first_line: *first_line,
+                    duplicate_line: *duplicate_line,
+                },
+            });
+        }
+
+        match self.node(&field_path) {
+            Some(TomlNode::Value(entry)) => Ok(entry),
+            Some(_) => Err(ReadTomlError::TypeMismatch {
+                field: field_name.to_string(),
+                expected: "value",
+                found: "table",
+            }),
+            None => Err(ReadTomlError::NotFound { field: field_name.to_string() }),
+        }
+    }
+
+    /// Fields that cannot be read, in line order, each with the error
+    /// [`TomlDocument::entry`] returns for it.
+    pub(crate) fn problem_fields(&self) -> Vec<(String, ReadTomlError)> {
+        self.problems
+            .iter()
+            .map(|(path, _)| {
+                let field_name = format_toml_path(path);
+                let error = self.entry(&field_name).err().unwrap_or(ReadTomlError::NotFound {
+                    field: field_name.clone(),
+                });
+                (field_name, error)
+            })
+            .collect()
+    }
+
+    /// Keys of the root table, in file order.
+    pub fn keys(&self) -> Vec<&str> {
+        self.root.keys()
+    }
+
+    /// Whether `field_name` names a value or a table in the document.
+    pub fn contains(&self, field_name: &str) -> bool {
+        parse_field_path(field_name).is_ok_and(|field_path| self.node(&field_path).is_some())
+    }
+
+    /// The table at `table_name` (`server`, `products[0]`), if there is one.
+    /// The document itself is the table at an empty path, see
+    /// [`TomlDocument::root`].
+    pub fn table(&self, table_name: &str) -> Option<&TomlTable> {
+        let table_path = parse_field_path(table_name).ok()?;
+        match self.node(&table_path)? {
+            TomlNode::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+
+    /// The root table.
+    pub fn root(&self) -> &TomlTable {
+        &self.root
+    }
+
+    /// Any field as a typed [`TomlValue`], like [`crate::read_toml_field::read_toml_value`].
+    pub fn get(&self, field_name: &str) -> Result<&TomlValue, ReadTomlError> {
+        let entry = self.entry(field_name)?;
+        entry.value.as_ref().map_err(|e| ReadTomlError::MalformedLine {
+            field: field_name.to_string(),
+            line: e.line,
+            column: e.column,
+            message: e.message.clone(),
+        })
+    }
+
+    /// A field as text, like [`crate::read_toml_field::read_single_line_string_field_from_toml`].
+    pub fn get_string(&self, field_name: &str) -> Result<String, ReadTomlError> {
+        let entry = self.entry(field_name)?;
+        string_value_text(&entry.raw_value).map_err(|message| ReadTomlError::MalformedLine {
+            field: field_name.to_string(),
+            line: entry.value_position.0,
+            column: entry.value_position.1,
+            message,
+        })
+    }
+
+    /// A triple-quoted string field, like [`crate::read_toml_field::read_multi_line_toml_string`].
+    pub fn get_multi_line_string(&self, field_name: &str) -> Result<String, ReadTomlError> {
+        let entry = self.entry(field_name)?;
+        check_multi_line_string(field_name, &entry.raw_value)?;
+        Ok(trim_multi_line_text(&self.get_string(field_name)?))
+    }
+
+    /// A non-negative integer array, like [`crate::read_toml_field::read_integer_array`].
+    pub fn get_integer_array(&self, field_name: &str) -> Result<Vec<u64>, ReadTomlError> {
+        unsigned_integers(field_name, self.get_i64_array(field_name)?)
+    }
+
+    pub fn get_bool(&self, field_name: &str) -> Result<bool, ReadTomlError> {
+        let value = self.get(field_name)?;
+        value.as_bool().ok_or_else(|| type_mismatch(field_name, "boolean", value))
+    }
+
+    pub fn get_i64(&self, field_name: &str) -> Result<i64, ReadTomlError> {
+        let value = self.get(field_name)?;
+        value.as_i64().ok_or_else(|| type_mismatch(field_name, "integer", value))
+    }
+
+    pub fn get_f64(&self, field_name: &str) -> Result<f64, ReadTomlError> {
+        let value = self.get(field_name)?;
+        value.as_f64().ok_or_else(|| type_mismatch(field_name, "float", value))
+    }
+
+    pub fn get_datetime(&self, field_name: &str) -> Result<TomlDatetime, ReadTomlError> {
+        let value = self.get(field_name)?;
+        value.as_datetime().copied().ok_or_else(|| type_mismatch(field_name, "datetime", value))
+    }
+
+    pub fn get_array(&self, field_name: &str) -> Result<&[TomlValue], ReadTomlError> {
+        let value = self.get(field_name)?;
+        value.as_array().ok_or_else(|| type_mismatch(field_name, "array", value))
+    }
+
+    pub fn get_string_array(&self, field_name: &str) -> Result<Vec<String>, ReadTomlError> {
+        self.get_array(field_name)?
+            .iter()
+            .map(|element| match element {
+                TomlValue::String(text) => Ok(text.clone()),
+                other => Err(type_mismatch(field_name, "string", other)),
+            })
+            .collect()
+    }
+
+    pub fn get_i64_array(&self, field_name: &str) -> Result<Vec<i64>, ReadTomlError> {
+        integer_elements(field_name, self.get_array(field_name)?)
+    }
+
+    pub fn get_nested_i64_array(&self, field_name: &str) -> Result<Vec<Vec<i64>>, ReadTomlError> {
+        self.get_array(field_name)?
+            .iter()
+            .map(|element| match element {
+                TomlValue::Array(inner) => integer_elements(field_name, inner),
+                other => Err(type_mismatch(field_name, "array", other)),
+            })
+            .collect()
+    }
+}
+
+/// The table at `table_path` below `root`, created if needed. On a
+/// conflict, returns how many segments of the path were walked (the
+/// conflicting key included) and the line of the earlier definition.
+fn table_at_mut<'a>(
+    root: &'a mut TomlTable,
+    table_path: &[TomlPathSegment],
+) -> Result<&'a mut TomlTable, (usize, usize)> {
+    let mut table = root;
+    let mut depth = 0;
+    while depth < table_path.len() {
+        let TomlPathSegment::Key(key) = &table_path[depth] else {
+            return Err((depth + 1, 0));
+        };
+        depth += 1;
+        let key_depth = depth;
+        let index = match table_path.get(depth) {
+            Some(TomlPathSegment::Index(index)) => {
+                depth += 1;
+                Some(*index)
+            }
+            _ => None,
+        };
+        table = table.child_table_mut(key, index).map_err(|first_line| (key_depth, first_line))?;
+    }
+    Ok(table)
+}
+
+/// A path with its array indexes removed, for the allow-list comparison.
+fn without_indexes(path: &[TomlPathSegment]) -> Vec<TomlPathSegment> {
+    path.iter()
+        .filter(|segment| matches!(segment, TomlPathSegment::Key(_)))
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::read_toml_field::{read_integer_array, read_multi_line_toml_string, read_single_line_string_field_from_toml};
+    use std::fs::{remove_file, write};
+
+    const TEST_CONTENT: &str = r#"
+title = "document"
+count = 3
+numbers = [
+    1, 2, # comment
+    3,
+]
+notes = """
+    first
+    second
+"""
+
+[server]
+name = "server-name"
+port = 8080
+
+[server.limits]
+connections = 10
+
+[[products]]
+name = "hammer"
+
+[[products]]
+name = "nail"
+
+[empty]
+"#;
+
+    #[test]
+    fn test_document_matches_readers() {
+        let test_file = "test_document_readers.toml";
+        write(test_file, TEST_CONTENT).unwrap();
+
+        let document = TomlDocument::load(test_file).unwrap();
+        for field in ["title", "count", "server.name", "server.port", "products[1].name", "missing"] {
+            assert_eq!(
+                document.get_string(field),
+                read_single_line_string_field_from_toml(test_file, field),
+                "{}",
+                field
+            );
+        }
+        assert_eq!(document.get_multi_line_string("notes"), read_multi_line_toml_string(test_file, "notes"));
+        assert_eq!(document.get_integer_array("numbers"), read_integer_array(test_file, "numbers"));
+
+        let _ = remove_file(test_file);
+    }
+
+    #[test]
+    fn test_document_tables_and_keys() {
+        let document = TomlDocument::parse(TEST_CONTENT);
+
+        assert_eq!(document.keys(), vec!["title", "count", "numbers", "notes", "server", "products", "empty"]);
+        assert_eq!(document.get_i64("server.port"), Ok(8080));
+        assert_eq!(document.get_i64("server.limits.connections"), Ok(10));
+        assert_eq!(document.get_string("products[0].name"), Ok("hammer".to_string()));
+
+        assert!(document.contains("server"));
+        assert!(document.contains("server.limits.connections"));
+        assert!(document.contains("empty"));
+        assert!(!document.contains("server.missing"));
+        assert!(!document.contains("products[2]"));
+
+        let server = document.table("server").unwrap();
+        assert_eq!(server.keys(), vec!["name", "port", "limits"]);
+        assert!(matches!(server.get("limits"), Some(TomlItem::Table(_))));
+        assert_eq!(document.table("products[1]").map(|product| product.keys()), Some(vec!["name"]));
+        assert!(document.table("title").is_none());
+
+        assert!(matches!(
+            document.get("server"),
+            Err(ReadTomlError::TypeMismatch { expected: "value", found: "table", .. })
+        ));
+    }
+
+    #[test]
+    fn test_document_problems_are_reported_per_field() {
+        let document = TomlDocument::parse("name = \"a\"\nname = \"b\"\nbad = \"x\" junk\ncount = 12abc\nok = true\n");
+
+        assert_eq!(
+            document.get_string("name"),
+            Err(ReadTomlError::DuplicateKey { field: "name".to_string(), first_line: 1, duplicate_line: 2 })
+        );
+        assert!(matches!(document.get("bad"), Err(ReadTomlError::MalformedLine { line: 3, column: 11, .. })));
+        assert!(matches!(document.get_i64("count"), Err(ReadTomlError::MalformedLine { line: 4, column: 9, .. })));
+        assert_eq!(document.get_string("count"), Ok("12abc".to_string()));
+        assert_eq!(document.get_bool("ok"), Ok(true));
+        assert!(matches!(document.get_bool("count"), Err(ReadTomlError::MalformedLine { .. })));
+    }
+
+    #[test]
+    fn test_strict_mode_reports_all_problems() {
+        let content = r#"name = "a"
+name = "b"
+owner.name = "x"
+= 5
+
+[server]
+port = 80
+
+[server]
+host = "h"
+
+[owner]
+email = "e"
+
+[name]
+"#;
+        let problems = TomlDocument::parse_strict(content, None).unwrap_err();
+        assert_eq!(
+            problems,
+            vec![
+                ReadTomlError::DuplicateKey { field: "name".to_string(), first_line: 1, duplicate_line: 2 },
+                ReadTomlError::InvalidLine {
+                    line: 4,
+                    column: 1,
+                    message: "expected a key name at '= 5'".to_string(),
+                },
+                ReadTomlError::TableRedefined { table: "server".to_string(), first_line: 6, duplicate_line: 9 },
+                ReadTomlError::TableRedefined { table: "owner".to_string(), first_line: 3, duplicate_line: 12 },
+                ReadTomlError::TableRedefined { table: "name".to_string(), first_line: 1, duplicate_line: 15 },
+            ]
+        );
+
+        // Outside strict mode the same text still loads
+        let document = TomlDocument::parse(content);
+        assert_eq!(document.get_i64("server.port"), Ok(80));
+    }
+
+    #[test]
+    fn test_strict_mode_dotted_keys_and_array_tables() {
+        let valid = r#"
+fruit.apple.color = "red"
+fruit.apple.size = 3
+
+[[products]]
+name = "hammer"
+
+[products.details]
+weight = 2
+
+[[products]]
+name = "nail"
+
+[products.details]
+weight = 1
+
+[server.limits]
+connections = 10
+
+[server]
+port = 80
+"#;
+        let document = TomlDocument::parse_strict(valid, None).unwrap();
+        assert_eq!(document.get_i64("products[1].details.weight"), Ok(1));
+        assert_eq!(document.get_i64("fruit.apple.size"), Ok(3));
+
+        let header_after_dotted_keys = "[fruit]\napple.color = \"red\"\n\n[fruit.apple]\nsize = 3\n";
+        assert_eq!(
+            TomlDocument::parse_strict(header_after_dotted_keys, None).unwrap_err()[0],
+            ReadTomlError::TableRedefined { table: "fruit.apple".to_string(), first_line: 2, duplicate_line: 4 }
+        );
+
+        let dotted_keys_after_header = "[fruit.apple]\nsize = 3\n\n[fruit]\napple.color = \"red\"\n";
+        assert_eq!(
+            TomlDocument::parse_strict(dotted_keys_after_header, None).unwrap_err(),
+            vec![ReadTomlError::TableRedefined { table: "fruit.apple".to_string(), first_line: 1, duplicate_line: 5 }]
+        );
+
+        let value_then_array_table = "products = 1\n[[products]]\n";
+        assert_eq!(
+            TomlDocument::parse_strict(value_then_array_table, None).unwrap_err(),
+            vec![ReadTomlError::TableRedefined { table: "products".to_string(), first_line: 1, duplicate_line: 2 }]
+        );
+    }
+
+    #[test]
+    fn test_strict_mode_allow_list() {
+        let content = "name = \"a\"\ncolour = \"red\"\n[[products]]\nname = \"hammer\"\nprice = 3\n";
+        let allowed_keys = ["name", "products.name"];
+
+        assert_eq!(
+            TomlDocument::parse_strict(content, Some(&allowed_keys)).unwrap_err(),
+            vec![
+                ReadTomlError::UnknownKey { field: "colour".to_string(), line: 2 },
+                ReadTomlError::UnknownKey { field: "products.price".to_string(), line: 5 },
+            ]
+        );
+        assert!(TomlDocument::parse_strict(content, Some(&["name", "colour", "products.name", "products.price"])).is_ok());
+        assert!(matches!(
+            TomlDocument::parse_strict(content, Some(&["a..b"])).unwrap_err()[0],
+            ReadTomlError::InvalidFieldName { .. }
+        ));
+    }
+
+    #[test]
+    fn test_load_strict() {
+        let test_file = "test_document_strict.toml";
+        write(test_file, "port = 80\nport = 81\n").unwrap();
+
+        let problems = TomlDocument::load_strict(test_file, Some(&["port"]));
+        let missing = TomlDocument::load_strict("missing_strict.toml", None);
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(
+            problems.unwrap_err(),
+            vec![ReadTomlError::DuplicateKey { field: "port".to_string(), first_line: 1, duplicate_line: 2 }]
+        );
+        assert!(matches!(missing.unwrap_err()[..], [ReadTomlError::OpenFailed { .. }]));
+    }
+}
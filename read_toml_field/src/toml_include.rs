@@ -0,0 +1,219 @@
+//! Configuration split over several TOML files.
+//!
+//! A program may read a shared `base.toml` and a per-host `local.toml`, or
+//! a single file that starts with `include = ["base.toml"]`. Either way the
+//! files are merged into one [`TomlDocument`], later files overriding
+//! earlier ones, and every value remembers the file and line it came from
+//! (see [`crate::toml_document::TomlEntry::location`]).
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::read_toml_field::{log_read_toml, ReadTomlError};
+use crate::toml_document::TomlDocument;
+
+/// Root key listing the files a TOML file builds on.
+pub const INCLUDE_KEY: &str = "include";
+
+/// Loads TOML files in order and merges them into one document, each file
+/// overriding the ones before it (see [`TomlDocument::merge`]).
+///
+/// A file may start with `include = ["other.toml", ...]`, a path relative
+/// to the including file. The included files are merged first, in order,
+/// and the including file on top of them, so it can override what it
+/// includes. The `include` key itself is not part of the result. A file
+/// that includes itself, directly or through other files, is an
+/// [`ReadTomlError::IncludeCycle`]; including the same file twice from
+/// different places is allowed.
+///
+/// # Arguments
+/// * `paths` - TOML files, lowest priority first
+///
+/// # Returns
+/// * `Result<TomlDocument, ReadTomlError>` - The merged document, or the first file that failed
+///
+/// # Example
+/// ```
+/// # use read_toml_field::toml_include::load_toml_files;
+/// let document = load_toml_files(&["config.toml", "test.toml"])?;
+/// assert_eq!(document.get_string("fieldname")?, "hello world");
+/// assert_eq!(document.entry("log")?.location(), "config.toml:3");
+/// # Ok::<(), read_toml_field::read_toml_field::ReadTomlError>(())
+/// ```
+pub fn load_toml_files(paths: &[&str]) -> Result<TomlDocument, ReadTomlError> {
+    let mut document = TomlDocument::default();
+    for path in paths {
+        document.merge(load_with_includes(Path::new(path), &mut Vec::new())?);
+    }
+    Ok(document)
+}
+
+/// Loads one TOML file together with the files it includes; see
+/// [`load_toml_files`].
+pub fn load_toml_with_includes(path: &str) -> Result<TomlDocument, ReadTomlError> {
+    load_toml_files(&[path])
+}
+
+/// Loads `path` and, first, its includes. `include_chain` holds the
+/// canonical paths of the files currently being loaded, outermost first.
+fn load_with_includes(path: &Path, include_chain: &mut Vec<PathBuf>) -> Result<TomlDocument, ReadTomlError> {
+    let display_path = path.display().to_string();
+    let canonical_path = fs::canonicalize(path).map_err(|e| ReadTomlError::OpenFailed {
+        path: display_path.clone(),
+        message: e.to_string(),
+    })?;
+
+    if let Some(cycle_start) = include_chain.iter().position(|known_path| *known_path == canonical_path) {
+        let mut files: Vec<String> = include_chain[cycle_start..]
+            .iter()
+            .map(|known_path| known_path.display().to_string())
+            .collect();
+        files.push(canonical_path.display().to_string());
+        return Err(ReadTomlError::IncludeCycle { files });
+    }
+
+    let mut document = TomlDocument::load(&display_path)?;
+    let include_location = match document.entry(INCLUDE_KEY) {
+        Ok(include_entry) => include_entry.location(),
+        Err(ReadTomlError::NotFound { .. }) => return Ok(document),
+        Err(e) => return Err(e),
+    };
+    let includes = document.get_string_array(INCLUDE_KEY)?;
+    document.remove_root_key(INCLUDE_KEY);
+
+    include_chain.push(canonical_path);
+    let base_directory = path.parent().unwrap_or(Path::new(""));
+    let mut merged = TomlDocument::default();
+    for include in includes {
+        let include_path = base_directory.join(&include);
+        log_read_toml(&format!("Including '{}' from {}", include_path.display(), include_location));
+        let included = load_with_includes(&include_path, include_chain).map_err(|e| match e {
+            ReadTomlError::OpenFailed { path, message } => ReadTomlError::OpenFailed {
+                path,
+                message: format!("{} (included from {})", message, include_location),
+            },
+            other => other,
+        })?;
+        merged.merge(included);
+    }
+    include_chain.pop();
+
+    merged.merge(document);
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{create_dir_all, remove_dir_all, write};
+
+    #[test]
+    fn test_load_toml_files_merges_in_order() {
+        let directory = "test_include_merge";
+        create_dir_all(directory).unwrap();
+        write(
+            format!("{}/base.toml", directory),
+            "name = \"base\"\nport = 80 oops\ntags = [\"a\", \"b\"]\n\n[server]\nhost = \"base-host\"\ntimeout = 5\n\n[[products]]\nname = \"hammer\"\n",
+        )
+        .unwrap();
+        write(
+            format!("{}/local.toml", directory),
+            "port = 8080\ntags = [\"c\"]\n\n[server]\nhost = \"local-host\"\n\n[[products]]\nname = \"nail\"\n",
+        )
+        .unwrap();
+
+        let base_path = format!("{}/base.toml", directory);
+        let local_path = format!("{}/local.toml", directory);
+        let document = load_toml_files(&[&base_path, &local_path]);
+        let base_only = load_toml_files(&[&base_path]);
+
+        let _ = remove_dir_all(directory);
+
+        let document = document.unwrap();
+        assert_eq!(document.get_string("name"), Ok("base".to_string()));
+        assert_eq!(document.get_i64("port"), Ok(8080));
+        assert_eq!(document.get_string_array("tags"), Ok(vec!["c".to_string()]));
+        assert_eq!(document.get_string("server.host"), Ok("local-host".to_string()));
+        assert_eq!(document.get_i64("server.timeout"), Ok(5));
+        assert_eq!(document.get_string("products[0].name"), Ok("nail".to_string()));
+        assert!(!document.contains("products[1]"));
+        assert_eq!(document.keys(), vec!["name", "port", "tags", "server", "products"]);
+
+        assert_eq!(document.entry("server.timeout").unwrap().location(), format!("{}:7", base_path));
+        assert_eq!(document.entry("server.host").unwrap().location(), format!("{}:5", local_path));
+        assert_eq!(document.entry("port").unwrap().file(), Some(local_path.as_str()));
+
+        assert!(matches!(base_only.unwrap().get("port"), Err(ReadTomlError::MalformedLine { .. })));
+    }
+
+    #[test]
+    fn test_include_directive() {
+        let directory = "test_include_directive";
+        create_dir_all(format!("{}/shared", directory)).unwrap();
+        write(
+            format!("{}/main.toml", directory),
+            "include = [\"shared/base.toml\", \"shared/extra.toml\"]\nname = \"main\"\n",
+        )
+        .unwrap();
+        write(
+            format!("{}/shared/base.toml", directory),
+            "include = [\"common.toml\"]\nname = \"base\"\nlevel = 1\n",
+        )
+        .unwrap();
+        write(format!("{}/shared/common.toml", directory), "level = 0\ncolour = \"red\"\n").unwrap();
+        write(format!("{}/shared/extra.toml", directory), "include = [\"common.toml\"]\nsize = 3\n").unwrap();
+
+        let document = load_toml_with_includes(&format!("{}/main.toml", directory));
+
+        let _ = remove_dir_all(directory);
+
+        let document = document.unwrap();
+        assert_eq!(document.get_string("name"), Ok("main".to_string()));
+        assert_eq!(document.get_i64("level"), Ok(0));
+        assert_eq!(document.get_i64("size"), Ok(3));
+        assert_eq!(document.get_string("colour"), Ok("red".to_string()));
+        assert!(!document.contains(INCLUDE_KEY));
+        assert_eq!(
+            document.entry("colour").unwrap().location(),
+            format!("{}/shared/common.toml:2", directory)
+        );
+    }
+
+    #[test]
+    fn test_include_errors() {
+        let directory = "test_include_errors";
+        create_dir_all(directory).unwrap();
+        write(format!("{}/a.toml", directory), "include = [\"b.toml\"]\n").unwrap();
+        write(format!("{}/b.toml", directory), "include = [\"a.toml\"]\n").unwrap();
+        write(format!("{}/self.toml", directory), "include = [\"self.toml\"]\n").unwrap();
+        write(format!("{}/missing.toml", directory), "\ninclude = [\"nowhere.toml\"]\n").unwrap();
+        write(format!("{}/not_a_list.toml", directory), "include = \"a.toml\"\n").unwrap();
+        write(format!("{}/malformed.toml", directory), "include = [\"a.toml\"\n").unwrap();
+
+        let cycle = load_toml_with_includes(&format!("{}/a.toml", directory));
+        let self_include = load_toml_with_includes(&format!("{}/self.toml", directory));
+        let missing = load_toml_with_includes(&format!("{}/missing.toml", directory));
+        let not_a_list = load_toml_with_includes(&format!("{}/not_a_list.toml", directory));
+        let malformed = load_toml_with_includes(&format!("{}/malformed.toml", directory));
+
+        let _ = remove_dir_all(directory);
+
+        match cycle {
+            Err(ReadTomlError::IncludeCycle { files }) => {
+                assert_eq!(files.len(), 3);
+                assert!(files[0].ends_with("a.toml") && files[1].ends_with("b.toml") && files[2].ends_with("a.toml"));
+            }
+            other => panic!("expected an include cycle, got {:?}", other),
+        }
+        assert!(matches!(self_include, Err(ReadTomlError::IncludeCycle { files }) if files.len() == 2));
+        match missing {
+            Err(ReadTomlError::OpenFailed { path, message }) => {
+                assert_eq!(path, format!("{}/nowhere.toml", directory));
+                assert!(message.ends_with(&format!("(included from {}/missing.toml:2)", directory)));
+            }
+            other => panic!("expected OpenFailed, got {:?}", other),
+        }
+        assert!(matches!(not_a_list, Err(ReadTomlError::TypeMismatch { .. })));
+        assert!(matches!(malformed, Err(ReadTomlError::MalformedLine { field, .. }) if field == INCLUDE_KEY));
+    }
+}
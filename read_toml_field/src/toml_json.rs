@@ -0,0 +1,1007 @@
+//! Conversion between TOML and JSON.
+//!
+//! Some programs keep their configuration in JSON and others in TOML; this
+//! module turns a [`TomlDocument`] into a [`JsonValue`] and a JSON value back
+//! into TOML text, keeping keys in the order they are written. The
+//! `toml_json` binary wraps it for the command line.
+//!
+//! Not everything survives the trip. JSON has no date-times (they become
+//! strings) and no NaN or infinity; TOML has no `null`, no arrays that mix
+//! tables and values, and (in the subset this crate reads) no inline
+//! tables, and a table's plain values always come before its sub-tables.
+//! [`check_toml_round_trip`] and [`check_json_round_trip`] list what would
+//! be lost. Comments and formatting are never carried over.
+
+use std::fmt;
+
+use crate::read_toml_field::{escape_basic_string, format_toml_path, TomlPathSegment, TomlValue};
+use crate::toml_document::{TomlDocument, TomlItem, TomlTable};
+
+/// Deepest nesting of arrays and objects [`parse_json`] accepts.
+const MAX_JSON_DEPTH: usize = 256;
+
+/// A JSON value. Objects keep their keys in the order they were written.
+///
+/// Numbers keep their text, so a large integer is not silently rounded
+/// before it is checked against what TOML can hold.
+///
+/// `Display` writes the value as indented JSON, with arrays of plain values
+/// on one line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValue {
+    Null,
+    Boolean(bool),
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(String, JsonValue)>),
+}
+
+/// Something that does not survive a conversion, at the lookup path of the
+/// value concerned (see [`format_toml_path`]).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlJsonLoss {
+    pub path: String,
+    pub message: String,
+}
+
+/// Text that is not valid JSON, with the 1-based line and column where
+/// reading stopped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TomlJsonLoss {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(formatter, "document: {}", self.message)
+        } else {
+            write!(formatter, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+impl fmt::Display for JsonParseError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "Invalid JSON at line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for JsonParseError {}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut text = String::new();
+        write_json(self, 0, &mut text);
+        write!(formatter, "{}", text)
+    }
+}
+
+impl JsonValue {
+    /// The value on one line, for messages.
+    pub fn to_compact_string(&self) -> String {
+        match self {
+            JsonValue::Null => "null".to_string(),
+            JsonValue::Boolean(flag) => flag.to_string(),
+            JsonValue::Number(number) => number.clone(),
+            JsonValue::String(text) => format!("\"{}\"", escape_json_string(text)),
+            JsonValue::Array(elements) => {
+                let elements: Vec<String> = elements.iter().map(JsonValue::to_compact_string).collect();
+                format!("[{}]", elements.join(", "))
+            }
+            JsonValue::Object(members) => {
+                let members: Vec<String> = members
+                    .iter()
+                    .map(|(key, value)| format!("\"{}\": {}", escape_json_string(key), value.to_compact_string()))
+                    .collect();
+                format!("{{{}}}", members.join(", "))
+            }
+        }
+    }
+
+    fn is_object(&self) -> bool {
+        matches!(self, JsonValue::Object(_))
+    }
+}
+
+// ----------------------------------------------------------------------------
+// TOML to JSON
+// ----------------------------------------------------------------------------
+
+/// Converts a document to a JSON object, in file order. Tables become
+/// objects, arrays of tables arrays of objects, and date-times strings.
+///
+/// # Returns
+/// * `Result<JsonValue, Vec<TomlJsonLoss>>` - The JSON object, or every value JSON cannot hold (NaN, infinity, values that are not valid TOML)
+///
+/// # Example
+/// ```
+/// # use read_toml_field::toml_document::TomlDocument;
+/// # use read_toml_field::toml_json::toml_to_json;
+/// let document = TomlDocument::parse("name = \"demo\"\n[server]\nport = 8080\n");
+/// let json = toml_to_json(&document).unwrap();
+/// assert_eq!(json.to_compact_string(), "{\"name\": \"demo\", \"server\": {\"port\": 8080}}");
+/// ```
+pub fn toml_to_json(document: &TomlDocument) -> Result<JsonValue, Vec<TomlJsonLoss>> {
+    let mut conversion = TomlToJson::new(document);
+    let json = conversion.table(document.root(), &mut Vec::new());
+    if conversion.dropped.is_empty() {
+        Ok(json)
+    } else {
+        Err(conversion.dropped)
+    }
+}
+
+/// Walks a document, collecting what JSON cannot hold.
+struct TomlToJson {
+    /// Values left out of the JSON
+    dropped: Vec<TomlJsonLoss>,
+    /// Values written as a different JSON type
+    retyped: Vec<TomlJsonLoss>,
+}
+
+impl TomlToJson {
+    /// Starts with the fields of `document` that cannot be read at all.
+    fn new(document: &TomlDocument) -> TomlToJson {
+        let dropped = document
+            .problem_fields()
+            .into_iter()
+            .map(|(path, error)| TomlJsonLoss { path, message: error.to_string() })
+            .collect();
+        TomlToJson { dropped, retyped: Vec::new() }
+    }
+
+    fn table(&mut self, table: &TomlTable, path: &mut Vec<TomlPathSegment>) -> JsonValue {
+        let mut members = Vec::new();
+        for key in table.keys() {
+            path.push(TomlPathSegment::Key(key.to_string()));
+            let converted = match table.get(key) {
+                Some(TomlItem::Value(entry)) => match entry.value() {
+                    Some(value) => self.value(value, path),
+                    None => {
+                        self.drop_value(path, format!("'{}' is not a valid TOML value", entry.raw_value()));
+                        None
+                    }
+                },
+                Some(TomlItem::Table(sub_table)) => Some(self.table(sub_table, path)),
+                Some(TomlItem::ArrayOfTables(tables)) => {
+                    let mut elements = Vec::new();
+                    for (index, element) in tables.iter().enumerate() {
+                        path.push(TomlPathSegment::Index(index));
+                        elements.push(self.table(element, path));
+                        path.pop();
+                    }
+                    Some(JsonValue::Array(elements))
+                }
+                None => None,
+            };
+            if let Some(converted) = converted {
+                members.push((key.to_string(), converted));
+            }
+            path.pop();
+        }
+        JsonValue::Object(members)
+    }
+
+    fn value(&mut self, value: &TomlValue, path: &mut Vec<TomlPathSegment>) -> Option<JsonValue> {
+        match value {
+            TomlValue::String(text) => Some(JsonValue::String(text.clone())),
+            TomlValue::Integer(number) => Some(JsonValue::Number(number.to_string())),
+            TomlValue::Float(number) if !number.is_finite() => {
+                self.drop_value(path, format!("JSON has no number {}", value));
+                None
+            }
+            TomlValue::Float(_) => Some(JsonValue::Number(value.to_string())),
+            TomlValue::Boolean(flag) => Some(JsonValue::Boolean(*flag)),
+            TomlValue::Datetime(datetime) => {
+                self.retyped.push(TomlJsonLoss {
+                    path: format_toml_path(path),
+                    message: format!("date-time {} becomes a JSON string", datetime),
+                });
+                Some(JsonValue::String(datetime.to_string()))
+            }
+            TomlValue::Array(elements) => {
+                let mut converted = Vec::new();
+                for (index, element) in elements.iter().enumerate() {
+                    path.push(TomlPathSegment::Index(index));
+                    if let Some(element) = self.value(element, path) {
+                        converted.push(element);
+                    }
+                    path.pop();
+                }
+                Some(JsonValue::Array(converted))
+            }
+        }
+    }
+
+    fn drop_value(&mut self, path: &[TomlPathSegment], message: String) {
+        self.dropped.push(TomlJsonLoss { path: format_toml_path(path), message });
+    }
+}
+
+// ----------------------------------------------------------------------------
+// JSON to TOML
+// ----------------------------------------------------------------------------
+
+/// Writes a JSON object as TOML text: nested objects become `[table]`
+/// sections, arrays of objects `[[array-of-tables]]` sections and strings
+/// with line breaks `"""` multi-line strings.
+///
+/// Keys keep their order, except that in each table the plain values are
+/// written before the sub-tables, as TOML requires without inline tables.
+///
+/// # Returns
+/// * `Result<String, Vec<TomlJsonLoss>>` - The TOML text, or every value TOML cannot hold (`null`, integers beyond 64 bits, arrays mixing objects and values, objects inside nested arrays)
+///
+/// # Example
+/// ```
+/// # use read_toml_field::toml_json::{json_to_toml, parse_json};
+/// let json = parse_json(r#"{"server": {"name": "demo", "ports": [80, 443]}, "log": true}"#).unwrap();
+/// assert_eq!(
+///     json_to_toml(&json).unwrap(),
+///     "log = true\n\n[server]\nname = \"demo\"\nports = [80, 443]\n"
+/// );
+/// ```
+pub fn json_to_toml(value: &JsonValue) -> Result<String, Vec<TomlJsonLoss>> {
+    let mut conversion = JsonToToml::default();
+    conversion.document(value);
+    if conversion.dropped.is_empty() {
+        Ok(conversion.text)
+    } else {
+        Err(conversion.dropped)
+    }
+}
+
+/// Writes TOML text, collecting the values left out.
+#[derive(Default)]
+struct JsonToToml {
+    text: String,
+    dropped: Vec<TomlJsonLoss>,
+}
+
+impl JsonToToml {
+    fn document(&mut self, value: &JsonValue) {
+        match value {
+            JsonValue::Object(members) => self.table_body(members, &mut Vec::new(), &mut Vec::new()),
+            other => self.dropped.push(TomlJsonLoss {
+                path: String::new(),
+                message: format!("a TOML document must be a JSON object, not {}", json_type_name(other)),
+            }),
+        }
+    }
+
+    /// Writes the plain values of a table, then its sub-tables and arrays
+    /// of tables as sections. `header` holds the keys of the section
+    /// header, `path` the lookup path (with array indices) for messages.
+    fn table_body(
+        &mut self,
+        members: &[(String, JsonValue)],
+        header: &mut Vec<TomlPathSegment>,
+        path: &mut Vec<TomlPathSegment>,
+    ) {
+        for (key, value) in members {
+            if is_table_like(value) {
+                continue;
+            }
+            path.push(TomlPathSegment::Key(key.clone()));
+            if let Some(toml_text) = self.inline_value(value, path) {
+                let key_text = format_toml_path(&[TomlPathSegment::Key(key.clone())]);
+                self.text.push_str(&format!("{} = {}\n", key_text, toml_text));
+            }
+            path.pop();
+        }
+
+        for (key, value) in members {
+            header.push(TomlPathSegment::Key(key.clone()));
+            path.push(TomlPathSegment::Key(key.clone()));
+            match value {
+                JsonValue::Object(sub_members) => {
+                    self.section_header(&format!("[{}]", format_toml_path(header)));
+                    self.table_body(sub_members, header, path);
+                }
+                JsonValue::Array(elements) if is_table_like(value) => {
+                    for (index, element) in elements.iter().enumerate() {
+                        if let JsonValue::Object(element_members) = element {
+                            path.push(TomlPathSegment::Index(index));
+                            self.section_header(&format!("[[{}]]", format_toml_path(header)));
+                            self.table_body(element_members, header, path);
+                            path.pop();
+                        }
+                    }
+                }
+                _ => {}
+            }
+            path.pop();
+            header.pop();
+        }
+    }
+
+    fn section_header(&mut self, header_text: &str) {
+        if !self.text.is_empty() {
+            self.text.push('\n');
+        }
+        self.text.push_str(header_text);
+        self.text.push('\n');
+    }
+
+    /// TOML text of a value written after `key = `, or `None` (with the
+    /// loss recorded) if TOML cannot hold it.
+    fn inline_value(&mut self, value: &JsonValue, path: &mut Vec<TomlPathSegment>) -> Option<String> {
+        match value {
+            JsonValue::Null => {
+                self.drop_value(path, "TOML has no null".to_string());
+                None
+            }
+            JsonValue::Boolean(flag) => Some(flag.to_string()),
+            JsonValue::Number(number) => match json_number_to_toml(number) {
+                Ok(toml_value) => Some(toml_value.to_string()),
+                Err(message) => {
+                    self.drop_value(path, message);
+                    None
+                }
+            },
+            JsonValue::String(text) if text.contains('\n') => {
+                let lines: Vec<String> = text.split('\n').map(escape_basic_string).collect();
+                Some(format!("\"\"\"\n{}\"\"\"", lines.join("\n")))
+            }
+            JsonValue::String(text) => Some(format!("\"{}\"", escape_basic_string(text))),
+            JsonValue::Array(elements) => {
+                if elements.iter().any(JsonValue::is_object) {
+                    let message = if elements.iter().all(JsonValue::is_object) {
+                        "TOML cannot write tables inside nested arrays"
+                    } else {
+                        "TOML cannot mix tables and values in one array"
+                    };
+                    self.drop_value(path, message.to_string());
+                    return None;
+                }
+                let mut converted = Vec::new();
+                for (index, element) in elements.iter().enumerate() {
+                    path.push(TomlPathSegment::Index(index));
+                    let element_text = self.inline_value(element, path);
+                    path.pop();
+                    converted.push(element_text?);
+                }
+                Some(format!("[{}]", converted.join(", ")))
+            }
+            JsonValue::Object(_) => {
+                self.drop_value(path, "TOML cannot write tables inside arrays of values".to_string());
+                None
+            }
+        }
+    }
+
+    fn drop_value(&mut self, path: &[TomlPathSegment], message: String) {
+        self.dropped.push(TomlJsonLoss { path: format_toml_path(path), message });
+    }
+}
+
+/// Whether a member value is written as a `[table]` or `[[table]]`
+/// section rather than after `key = `.
+fn is_table_like(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Object(_) => true,
+        JsonValue::Array(elements) => !elements.is_empty() && elements.iter().all(JsonValue::is_object),
+        _ => false,
+    }
+}
+
+/// The TOML integer or float for a JSON number's text.
+fn json_number_to_toml(number: &str) -> Result<TomlValue, String> {
+    if number.contains(['.', 'e', 'E']) {
+        match number.parse::<f64>() {
+            Ok(float) if float.is_finite() => Ok(TomlValue::Float(float)),
+            _ => Err(format!("number {} is too large for a TOML float", number)),
+        }
+    } else {
+        number
+            .parse::<i64>()
+            .map(TomlValue::Integer)
+            .map_err(|_| format!("integer {} does not fit in a 64-bit TOML integer", number))
+    }
+}
+
+fn json_type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Boolean(_) => "a boolean",
+        JsonValue::Number(_) => "a number",
+        JsonValue::String(_) => "a string",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Round-trip checks
+// ----------------------------------------------------------------------------
+
+/// Converts a document to JSON and back and lists everything that does not
+/// come back the same: values JSON cannot hold, date-times that come back
+/// as strings, and keys whose order changes. An empty list means the
+/// document survives the round trip.
+///
+/// # Example
+/// ```
+/// # use read_toml_field::toml_document::TomlDocument;
+/// # use read_toml_field::toml_json::check_toml_round_trip;
+/// let document = TomlDocument::parse("created = 1979-05-27\nratio = nan\n");
+/// let losses: Vec<String> = check_toml_round_trip(&document).iter().map(|loss| loss.to_string()).collect();
+/// assert_eq!(losses, [
+///     "ratio: JSON has no number nan",
+///     "created: date-time 1979-05-27 becomes a JSON string",
+/// ]);
+/// ```
+pub fn check_toml_round_trip(document: &TomlDocument) -> Vec<TomlJsonLoss> {
+    let mut conversion = TomlToJson::new(document);
+    let json = conversion.table(document.root(), &mut Vec::new());
+    let mut losses = conversion.dropped;
+    losses.extend(conversion.retyped);
+    losses.extend(check_json_round_trip(&json));
+    losses
+}
+
+/// Converts a JSON value to TOML and back and lists everything that does
+/// not come back the same: values TOML cannot hold and keys whose order
+/// changes. An empty list means the value survives the round trip.
+///
+/// # Example
+/// ```
+/// # use read_toml_field::toml_json::{check_json_round_trip, parse_json};
+/// let json = parse_json(r#"{"server": {"port": 80}, "name": null, "log": true}"#).unwrap();
+/// let losses: Vec<String> = check_json_round_trip(&json).iter().map(|loss| loss.to_string()).collect();
+/// assert_eq!(losses, [
+///     "name: TOML has no null",
+///     "document: key order changes from [server, log] to [log, server]",
+/// ]);
+/// ```
+pub fn check_json_round_trip(value: &JsonValue) -> Vec<TomlJsonLoss> {
+    let mut conversion = JsonToToml::default();
+    conversion.document(value);
+    let mut losses = conversion.dropped;
+    if !losses.is_empty() && !value.is_object() {
+        return losses;
+    }
+
+    let round_tripped = match TomlDocument::parse_strict(&conversion.text, None) {
+        Ok(document) => toml_to_json(&document),
+        Err(errors) => {
+            losses.extend(errors.iter().map(|error| TomlJsonLoss {
+                path: String::new(),
+                message: format!("the TOML written does not read back: {}", error),
+            }));
+            return losses;
+        }
+    };
+    match round_tripped {
+        Ok(round_tripped) => {
+            let mut differences = Vec::new();
+            compare_json(value, &round_tripped, &mut Vec::new(), &mut differences);
+            // A value left out because of a loss already reported (its own
+            // or one of its elements') is not reported again
+            differences.retain(|difference| {
+                difference.message != DROPPED || !losses.iter().any(|loss| is_within(&loss.path, &difference.path))
+            });
+            losses.extend(differences);
+        }
+        Err(dropped) => losses.extend(dropped),
+    }
+    losses
+}
+
+/// Message for a key that does not come back at all.
+const DROPPED: &str = "is dropped";
+
+/// Whether lookup path `path` is `parent` or lies inside it.
+fn is_within(path: &str, parent: &str) -> bool {
+    path.strip_prefix(parent)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']))
+}
+
+/// Records where `after` differs from `before`.
+fn compare_json(
+    before: &JsonValue,
+    after: &JsonValue,
+    path: &mut Vec<TomlPathSegment>,
+    differences: &mut Vec<TomlJsonLoss>,
+) {
+    let mut record = |path: &[TomlPathSegment], message: String| {
+        differences.push(TomlJsonLoss { path: format_toml_path(path), message })
+    };
+    match (before, after) {
+        (JsonValue::Object(before_members), JsonValue::Object(after_members)) => {
+            let kept_keys: Vec<&str> = before_members
+                .iter()
+                .map(|(key, _)| key.as_str())
+                .filter(|key| after_members.iter().any(|(after_key, _)| after_key == key))
+                .collect();
+            let after_keys: Vec<&str> = after_members
+                .iter()
+                .map(|(key, _)| key.as_str())
+                .filter(|key| kept_keys.contains(key))
+                .collect();
+            if kept_keys != after_keys {
+                record(
+                    path,
+                    format!("key order changes from [{}] to [{}]", kept_keys.join(", "), after_keys.join(", ")),
+                );
+            }
+            for (key, before_value) in before_members {
+                path.push(TomlPathSegment::Key(key.clone()));
+                match after_members.iter().find(|(after_key, _)| after_key == key) {
+                    Some((_, after_value)) => compare_json(before_value, after_value, path, differences),
+                    None => differences.push(TomlJsonLoss {
+                        path: format_toml_path(path),
+                        message: DROPPED.to_string(),
+                    }),
+                }
+                path.pop();
+            }
+        }
+        (JsonValue::Array(before_elements), JsonValue::Array(after_elements))
+            if before_elements.len() == after_elements.len() =>
+        {
+            for (index, (before_element, after_element)) in before_elements.iter().zip(after_elements).enumerate() {
+                path.push(TomlPathSegment::Index(index));
+                compare_json(before_element, after_element, path, differences);
+                path.pop();
+            }
+        }
+        (JsonValue::Number(before_number), JsonValue::Number(after_number))
+            if json_number_to_toml(before_number) != json_number_to_toml(after_number) =>
+        {
+            record(path, format!("changes from {} to {}", before_number, after_number));
+        }
+        (JsonValue::Number(_), JsonValue::Number(_)) => {}
+        _ if before != after => record(
+            path,
+            format!("changes from {} to {}", before.to_compact_string(), after.to_compact_string()),
+        ),
+        _ => {}
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Reading and writing JSON text
+// ----------------------------------------------------------------------------
+
+/// Parses JSON text (RFC 8259). Object keys keep their order; a key that
+/// appears twice in one object is an error, as it would be in TOML.
+///
+/// # Example
+/// ```
+/// # use read_toml_field::toml_json::{parse_json, JsonValue};
+/// let json = parse_json("{\"b\": 1, \"a\": [true, \"x\\u00e9\"]}").unwrap();
+/// assert_eq!(json.to_compact_string(), "{\"b\": 1, \"a\": [true, \"xé\"]}");
+///
+/// let error = parse_json("{\"a\": 1,\n \"a\": 2}").unwrap_err();
+/// assert_eq!(error.to_string(), "Invalid JSON at line 2, column 2: duplicate key 'a'");
+/// ```
+pub fn parse_json(text: &str) -> Result<JsonValue, JsonParseError> {
+    let mut parser = JsonParser { text, position: 0 };
+    parser.skip_whitespace();
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.position < text.len() {
+        return Err(parser.error("unexpected text after the JSON value"));
+    }
+    Ok(value)
+}
+
+struct JsonParser<'a> {
+    text: &'a str,
+    /// Byte offset of the next unread character
+    position: usize,
+}
+
+impl JsonParser<'_> {
+    fn value(&mut self, depth: usize) -> Result<JsonValue, JsonParseError> {
+        if depth > MAX_JSON_DEPTH {
+            return Err(self.error(&format!("nested deeper than {} levels", MAX_JSON_DEPTH)));
+        }
+        match self.peek() {
+            Some('{') => self.object(depth),
+            Some('[') => self.array(depth),
+            Some('"') => Ok(JsonValue::String(self.string()?)),
+            Some('-' | '0'..='9') => self.number(),
+            Some(_) if self.take_literal("true") => Ok(JsonValue::Boolean(true)),
+            Some(_) if self.take_literal("false") => Ok(JsonValue::Boolean(false)),
+            Some(_) if self.take_literal("null") => Ok(JsonValue::Null),
+            Some(_) => Err(self.error("expected a JSON value")),
+            None => Err(self.error("unexpected end of text")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<JsonValue, JsonParseError> {
+        self.position += 1;
+        let mut members: Vec<(String, JsonValue)> = Vec::new();
+        self.skip_whitespace();
+        if self.take('}') {
+            return Ok(JsonValue::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a string key"));
+            }
+            let key_position = self.position;
+            let key = self.string()?;
+            if members.iter().any(|(known_key, _)| *known_key == key) {
+                self.position = key_position;
+                return Err(self.error(&format!("duplicate key '{}'", key)));
+            }
+            self.skip_whitespace();
+            if !self.take(':') {
+                return Err(self.error("expected ':' after the key"));
+            }
+            self.skip_whitespace();
+            let value = self.value(depth + 1)?;
+            members.push((key, value));
+            self.skip_whitespace();
+            if self.take('}') {
+                return Ok(JsonValue::Object(members));
+            }
+            if !self.take(',') {
+                return Err(self.error("expected ',' or '}' in object"));
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<JsonValue, JsonParseError> {
+        self.position += 1;
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.take(']') {
+            return Ok(JsonValue::Array(elements));
+        }
+        loop {
+            self.skip_whitespace();
+            elements.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            if self.take(']') {
+                return Ok(JsonValue::Array(elements));
+            }
+            if !self.take(',') {
+                return Err(self.error("expected ',' or ']' in array"));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonParseError> {
+        self.position += 1;
+        let mut decoded = String::new();
+        loop {
+            let Some(character) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            match character {
+                '"' => {
+                    self.position += 1;
+                    return Ok(decoded);
+                }
+                '\\' => {
+                    self.position += 1;
+                    let escaped = match self.peek() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{0008}',
+                        Some('f') => '\u{000C}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            self.position += 1;
+                            decoded.push(self.unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.error("invalid escape sequence")),
+                    };
+                    self.position += 1;
+                    decoded.push(escaped);
+                }
+                control if (control as u32) < 0x20 => {
+                    return Err(self.error("control character in string"));
+                }
+                other => {
+                    self.position += other.len_utf8();
+                    decoded.push(other);
+                }
+            }
+        }
+    }
+
+    /// Decodes the `XXXX` of a `\uXXXX` escape, and the low half that must
+    /// follow a high surrogate.
+    fn unicode_escape(&mut self) -> Result<char, JsonParseError> {
+        let high = self.hex4()?;
+        let code_point = if (0xD800..0xDC00).contains(&high) {
+            if !self.text[self.position..].starts_with("\\u") {
+                return Err(self.error("unpaired surrogate in \\u escape"));
+            }
+            self.position += 2;
+            let low = self.hex4()?;
+            if !(0xDC00..0xE000).contains(&low) {
+                return Err(self.error("unpaired surrogate in \\u escape"));
+            }
+            0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+        } else {
+            high
+        };
+        char::from_u32(code_point).ok_or_else(|| self.error("unpaired surrogate in \\u escape"))
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonParseError> {
+        let digits = self.text.get(self.position..self.position + 4).unwrap_or("");
+        if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(self.error("expected four hex digits after \\u"));
+        }
+        self.position += 4;
+        Ok(u32::from_str_radix(digits, 16).unwrap_or(0))
+    }
+
+    fn number(&mut self) -> Result<JsonValue, JsonParseError> {
+        let start = self.position;
+        self.take('-');
+        if !self.take('0') && self.digits() == 0 {
+            return Err(self.error("expected a digit"));
+        }
+        if self.take('.') && self.digits() == 0 {
+            return Err(self.error("expected a digit after '.'"));
+        }
+        if self.take('e') || self.take('E') {
+            if !self.take('+') {
+                self.take('-');
+            }
+            if self.digits() == 0 {
+                return Err(self.error("expected a digit in the exponent"));
+            }
+        }
+        Ok(JsonValue::Number(self.text[start..self.position].to_string()))
+    }
+
+    fn digits(&mut self) -> usize {
+        let count = self.text[self.position..].bytes().take_while(u8::is_ascii_digit).count();
+        self.position += count;
+        count
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ' | '\t' | '\n' | '\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn take(&mut self, expected: char) -> bool {
+        if self.peek() == Some(expected) {
+            self.position += expected.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn take_literal(&mut self, literal: &str) -> bool {
+        if self.text[self.position..].starts_with(literal) {
+            self.position += literal.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn error(&self, message: &str) -> JsonParseError {
+        let before = &self.text[..self.position];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        JsonParseError {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Appends `value` as JSON indented by `indent` levels of two spaces.
+fn write_json(value: &JsonValue, indent: usize, text: &mut String) {
+    let inner_indent = "  ".repeat(indent + 1);
+    match value {
+        JsonValue::Array(elements)
+            if !elements.is_empty() && elements.iter().any(|e| matches!(e, JsonValue::Array(_) | JsonValue::Object(_))) =>
+        {
+            text.push_str("[\n");
+            for (index, element) in elements.iter().enumerate() {
+                text.push_str(&inner_indent);
+                write_json(element, indent + 1, text);
+                text.push_str(if index + 1 < elements.len() { ",\n" } else { "\n" });
+            }
+            text.push_str(&"  ".repeat(indent));
+            text.push(']');
+        }
+        JsonValue::Object(members) if !members.is_empty() => {
+            text.push_str("{\n");
+            for (index, (key, member)) in members.iter().enumerate() {
+                text.push_str(&format!("{}\"{}\": ", inner_indent, escape_json_string(key)));
+                write_json(member, indent + 1, text);
+                text.push_str(if index + 1 < members.len() { ",\n" } else { "\n" });
+            }
+            text.push_str(&"  ".repeat(indent));
+            text.push('}');
+        }
+        other => text.push_str(&other.to_compact_string()),
+    }
+}
+
+/// Escapes text for the inside of a JSON string.
+fn escape_json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\u{0008}' => escaped.push_str("\\b"),
+            '\u{000C}' => escaped.push_str("\\f"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            control if (control as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", control as u32)),
+            other => escaped.push(other),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toml_json_round_trip_keeps_order_and_values() {
+        let toml_text = concat!(
+            "zeta = \"last letter\"\n",
+            "alpha = 1\n",
+            "ratio = 0.5\n",
+            "nested = [[1, 2], [\"a\"]]\n",
+            "notes = \"\"\"\nline one\nsaid \"hi\"\n\"\"\"\n",
+            "\n",
+            "[server]\n",
+            "port = 8080\n",
+            "\"odd key\" = true\n",
+            "[server.tls]\n",
+            "enabled = false\n",
+            "\n",
+            "[[products]]\n",
+            "name = \"Hammer\"\n",
+            "[products.details]\n",
+            "weight = 1e3\n",
+            "[[products]]\n",
+            "name = \"Nail\"\n",
+            "\n",
+            "[empty]\n",
+        );
+        let document = TomlDocument::parse_strict(toml_text, None).unwrap();
+        let json = toml_to_json(&document).unwrap();
+
+        assert_eq!(
+            json.to_string(),
+            concat!(
+                "{\n",
+                "  \"zeta\": \"last letter\",\n",
+                "  \"alpha\": 1,\n",
+                "  \"ratio\": 0.5,\n",
+                "  \"nested\": [\n    [1, 2],\n    [\"a\"]\n  ],\n",
+                "  \"notes\": \"line one\\nsaid \\\"hi\\\"\\n\",\n",
+                "  \"server\": {\n",
+                "    \"port\": 8080,\n",
+                "    \"odd key\": true,\n",
+                "    \"tls\": {\n      \"enabled\": false\n    }\n",
+                "  },\n",
+                "  \"products\": [\n",
+                "    {\n      \"name\": \"Hammer\",\n      \"details\": {\n        \"weight\": 1000.0\n      }\n    },\n",
+                "    {\n      \"name\": \"Nail\"\n    }\n",
+                "  ],\n",
+                "  \"empty\": {}\n",
+                "}"
+            )
+        );
+        assert_eq!(parse_json(&json.to_string()).unwrap(), json);
+
+        let toml_again = json_to_toml(&json).unwrap();
+        assert_eq!(
+            toml_again,
+            concat!(
+                "zeta = \"last letter\"\n",
+                "alpha = 1\n",
+                "ratio = 0.5\n",
+                "nested = [[1, 2], [\"a\"]]\n",
+                "notes = \"\"\"\nline one\nsaid \\\"hi\\\"\n\"\"\"\n",
+                "\n[server]\n",
+                "port = 8080\n",
+                "\"odd key\" = true\n",
+                "\n[server.tls]\n",
+                "enabled = false\n",
+                "\n[[products]]\n",
+                "name = \"Hammer\"\n",
+                "\n[products.details]\n",
+                "weight = 1000.0\n",
+                "\n[[products]]\n",
+                "name = \"Nail\"\n",
+                "\n[empty]\n",
+            )
+        );
+        let reread = TomlDocument::parse_strict(&toml_again, None).unwrap();
+        assert_eq!(toml_to_json(&reread).unwrap(), json);
+        assert_eq!(check_toml_round_trip(&document), []);
+        assert_eq!(check_json_round_trip(&json), []);
+    }
+
+    #[test]
+    fn test_round_trip_reports_losses() {
+        let document = TomlDocument::parse("b.x = 1\na = 2\n[when]\nat = 07:32:00\nbad = [1,\n");
+        let losses: Vec<String> = check_toml_round_trip(&document).iter().map(|loss| loss.to_string()).collect();
+        assert_eq!(
+            losses,
+            [
+                "when.bad: Invalid value for field 'when.bad' at line 5, column 7: closing bracket not found",
+                "when.at: date-time 07:32:00 becomes a JSON string",
+                "document: key order changes from [b, a, when] to [a, b, when]",
+            ]
+        );
+        assert!(toml_to_json(&document).is_err());
+
+        let json = parse_json(concat!(
+            "{\"big\": 92233720368547758070, \"mixed\": [1, {\"a\": 1}], ",
+            "\"deep\": [[{\"a\": 1}]], \"list\": [1, null], \"ok\": \"x\", ",
+            "\"tables\": [{\"n\": null}]}"
+        ))
+        .unwrap();
+        let losses: Vec<String> = check_json_round_trip(&json).iter().map(|loss| loss.to_string()).collect();
+        assert_eq!(
+            losses,
+            [
+                "big: integer 92233720368547758070 does not fit in a 64-bit TOML integer",
+                "mixed: TOML cannot mix tables and values in one array",
+                "deep[0]: TOML cannot write tables inside nested arrays",
+                "list[1]: TOML has no null",
+                "tables[0].n: TOML has no null",
+            ]
+        );
+        assert_eq!(json_to_toml(&json).unwrap_err().len(), 5);
+
+        let top_level = check_json_round_trip(&JsonValue::Array(Vec::new()));
+        assert_eq!(top_level[0].to_string(), "document: a TOML document must be a JSON object, not an array");
+    }
+
+    #[test]
+    fn test_parse_json_errors_and_escapes() {
+        let value = parse_json("{\"s\": \"tab\\tquote\\\" \\ud83d\\ude00 \\/\", \"n\": -1.5e-3}").unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Object(vec![
+                ("s".to_string(), JsonValue::String("tab\tquote\" 😀 /".to_string())),
+                ("n".to_string(), JsonValue::Number("-1.5e-3".to_string())),
+            ])
+        );
+        assert_eq!(
+            json_to_toml(&value).unwrap(),
+            "s = \"tab\\tquote\\\" 😀 /\"\nn = -0.0015\n"
+        );
+
+        let error_at = |text: &str| {
+            let error = parse_json(text).unwrap_err();
+            (error.line, error.column, error.message)
+        };
+        assert_eq!(error_at("{\"a\": 01}"), (1, 8, "expected ',' or '}' in object".to_string()));
+        assert_eq!(error_at("[1,\n 2,\n ]"), (3, 2, "expected a JSON value".to_string()));
+        assert_eq!(error_at("\"é\\x\""), (1, 4, "invalid escape sequence".to_string()));
+        assert_eq!(error_at("\"\\ud800\""), (1, 8, "unpaired surrogate in \\u escape".to_string()));
+        assert_eq!(error_at("{} {}"), (1, 4, "unexpected text after the JSON value".to_string()));
+        assert_eq!(error_at(&"[".repeat(MAX_JSON_DEPTH + 2)).2, "nested deeper than 256 levels");
+    }
+}
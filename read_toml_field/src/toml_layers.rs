@@ -0,0 +1,438 @@
+//! Layered configuration lookup on top of a TOML file.
+//!
+//! The same TOML file is deployed to several hosts, and each host needs a
+//! few values of its own without editing the file. [`TomlLayers`] resolves
+//! a field from, in order:
+//!
+//! 1. a `--set key=value` argument on the command line,
+//! 2. an environment variable such as `APP_SERVER_PORT` for `server.port`,
+//! 3. the TOML file,
+//! 4. a default given by the program,
+//!
+//! and reports which of these layers supplied each value.
+
+use std::fmt;
+
+use crate::read_toml_field::{
+    format_toml_path, parse_field_path, parse_toml_lookup_path, parse_toml_value, type_mismatch, ReadTomlError,
+    TomlPathSegment, TomlValue,
+};
+use crate::toml_document::TomlDocument;
+
+/// Where a value of [`TomlLayers`] came from, highest priority first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TomlLayer {
+    /// A `--set key=value` argument
+    CommandLine,
+    /// An environment variable named after the key
+    Environment,
+    /// The TOML file
+    File,
+    /// A default given by the program
+    Default,
+}
+
+impl TomlLayer {
+    /// Name used in reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TomlLayer::CommandLine => "command line",
+            TomlLayer::Environment => "environment",
+            TomlLayer::File => "file",
+            TomlLayer::Default => "default",
+        }
+    }
+}
+
+/// A resolved field: its value, the layer that supplied it, and where in
+/// that layer it was found (`--set`, `APP_SERVER_PORT`, `config.toml:4`,
+/// `default`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlLayeredValue {
+    pub field: String,
+    pub value: TomlValue,
+    pub layer: TomlLayer,
+    pub source: String,
+}
+
+/// One line of a report: `server.port = 8080 (environment: APP_SERVER_PORT)`.
+impl fmt::Display for TomlLayeredValue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        if self.layer == TomlLayer::Default {
+            write!(formatter, "{} = {} (default)", self.field, self.value)
+        } else {
+            write!(formatter, "{} = {} ({}: {})", self.field, self.value, self.layer.name(), self.source)
+        }
+    }
+}
+
+/// `(key, value text)` overrides, in the order they were given.
+pub type TomlOverrides = Vec<(String, String)>;
+
+/// A TOML file with command-line, environment and default layers around it.
+///
+/// Override text from the command line or the environment is read as a
+/// TOML value when it is one (`8080`, `true`, `"quoted"`, `[1, 2]`) and as
+/// a plain string otherwise, so `--set name=edge-01` needs no quotes.
+///
+/// # Example
+/// ```
+/// # use read_toml_field::toml_layers::{TomlLayer, TomlLayers};
+/// # use read_toml_field::read_toml_field::TomlValue;
+/// let layers = TomlLayers::load("config.toml")?
+///     .with_overrides(vec![("server.port".to_string(), "9090".to_string())])
+///     .with_environment("APP", vec![("APP_LOG".to_string(), "false".to_string())])
+///     .with_default("server.timeout", TomlValue::Integer(30));
+///
+/// assert_eq!(layers.get_i64("server.port")?, 9090);
+/// assert_eq!(layers.get_bool("log")?, false);
+/// assert_eq!(layers.get_string("server.name")?, "config-server");
+/// assert_eq!(layers.resolve("server.timeout")?.layer, TomlLayer::Default);
+/// # Ok::<(), read_toml_field::read_toml_field::ReadTomlError>(())
+/// ```
+#[derive(Debug)]
+pub struct TomlLayers {
+    document: TomlDocument,
+    file_name: String,
+    overrides: TomlOverrides,
+    environment_prefix: Option<String>,
+    environment: Vec<(String, String)>,
+    defaults: Vec<(String, TomlValue)>,
+}
+
+impl TomlLayers {
+    /// Reads and parses the TOML file that forms the file layer.
+    pub fn load(path: &str) -> Result<TomlLayers, ReadTomlError> {
+        Ok(TomlLayers::new(path, TomlDocument::load(path)?))
+    }
+
+    /// Uses an already parsed document as the file layer, such as one
+    /// merged by [`crate::toml_include::load_toml_files`]. `file_name` is
+    /// only used in reports, for values that do not record their own file.
+    pub fn new(file_name: &str, document: TomlDocument) -> TomlLayers {
+        TomlLayers {
+            document,
+            file_name: file_name.to_string(),
+            overrides: Vec::new(),
+            environment_prefix: None,
+            environment: Vec::new(),
+            defaults: Vec::new(),
+        }
+    }
+
+    /// Adds `(key, value)` overrides, usually from
+    /// [`parse_set_arguments`]. A key given twice keeps its last value.
+    pub fn with_overrides(mut self, overrides: TomlOverrides) -> Self {
+        self.overrides.extend(overrides);
+        self
+    }
+
+    /// Turns on the environment layer with the variables of this process;
+    /// see [`environment_variable_name`] for how keys map to names.
+    pub fn with_process_environment(self, prefix: &str) -> Self {
+        self.with_environment(prefix, std::env::vars().collect())
+    }
+
+    /// Turns on the environment layer with the given variables instead of
+    /// those of the process.
+    pub fn with_environment(mut self, prefix: &str, variables: Vec<(String, String)>) -> Self {
+        self.environment_prefix = Some(prefix.to_string());
+        self.environment = variables;
+        self
+    }
+
+    /// Sets the value used when no other layer has `field_name`.
+    pub fn with_default(mut self, field_name: &str, value: TomlValue) -> Self {
+        match parse_toml_lookup_path(field_name) {
+            Ok(field_path) => self.defaults.retain(|(name, _)| !names_field(name, &field_path)),
+            Err(_) => self.defaults.retain(|(name, _)| name != field_name),
+        }
+        self.defaults.push((field_name.to_string(), value));
+        self
+    }
+
+    /// The parsed file layer.
+    pub fn document(&self) -> &TomlDocument {
+        &self.document
+    }
+
+    /// Resolves `field_name` through the layers. A field that no layer
+    /// has is `NotFound`; a field the file defines badly is only an error
+    /// when no layer above the file overrides it.
+    pub fn resolve(&self, field_name: &str) -> Result<TomlLayeredValue, ReadTomlError> {
+        let field_path = parse_field_path(field_name)?;
+        let resolved = |value: TomlValue, layer: TomlLayer, source: String| TomlLayeredValue {
+            field: field_name.to_string(),
+            value,
+            layer,
+            source,
+        };
+
+        if let Some((_, text)) = self.overrides.iter().rev().find(|(key, _)| names_field(key, &field_path)) {
+            return Ok(resolved(override_value(text), TomlLayer::CommandLine, "--set".to_string()));
+        }
+
+        if let Some(prefix) = &self.environment_prefix {
+            let variable_name = environment_variable_name(prefix, &format_toml_path(&field_path));
+            if let Some((_, text)) = self.environment.iter().find(|(name, _)| *name == variable_name) {
+                return Ok(resolved(override_value(text), TomlLayer::Environment, variable_name));
+            }
+        }
+
+        match self.document.entry(field_name) {
+            Ok(entry) => {
+                let value = self.document.get(field_name)?.clone();
+                let source = match entry.file() {
+                    Some(_) => entry.location(),
+                    None => format!("{}:{}", self.file_name, entry.line_number()),
+                };
+                return Ok(resolved(value, TomlLayer::File, source));
+            }
+            Err(ReadTomlError::NotFound { .. }) => {}
+            Err(e) => return Err(e),
+        }
+
+        match self.defaults.iter().find(|(name, _)| names_field(name, &field_path)) {
+            Some((_, value)) => Ok(resolved(value.clone(), TomlLayer::Default, "default".to_string())),
+            None => Err(ReadTomlError::NotFound { field: field_name.to_string() }),
+        }
+    }
+
+    /// Resolves each of `field_names`, for printing which layer supplied
+    /// which value.
+    pub fn report(&self, field_names: &[&str]) -> Vec<Result<TomlLayeredValue, ReadTomlError>> {
+        field_names.iter().map(|field_name| self.resolve(field_name)).collect()
+    }
+
+    /// Any field as a typed [`TomlValue`].
+    pub fn get(&self, field_name: &str) -> Result<TomlValue, ReadTomlError> {
+        Ok(self.resolve(field_name)?.value)
+    }
+
+    /// A field as text: strings without their quotes, other values as TOML.
+    pub fn get_string(&self, field_name: &str) -> Result<String, ReadTomlError> {
+        Ok(match self.get(field_name)? {
+            TomlValue::String(text) => text,
+            other => other.to_string(),
+        })
+    }
+
+    pub fn get_bool(&self, field_name: &str) -> Result<bool, ReadTomlError> {
+        let value = self.get(field_name)?;
+        value.as_bool().ok_or_else(|| type_mismatch(field_name, "boolean", &value))
+    }
+
+    pub fn get_i64(&self, field_name: &str) -> Result<i64, ReadTomlError> {
+        let value = self.get(field_name)?;
+        value.as_i64().ok_or_else(|| type_mismatch(field_name, "integer", &value))
+    }
+
+    pub fn get_f64(&self, field_name: &str) -> Result<f64, ReadTomlError> {
+        let value = self.get(field_name)?;
+        value.as_f64().ok_or_else(|| type_mismatch(field_name, "float", &value))
+    }
+}
+
+/// Whether `key`, as given to an override or a default, is the field at
+/// `field_path`: `server . port` and `server."port"` both name `server.port`.
+fn names_field(key: &str, field_path: &[TomlPathSegment]) -> bool {
+    parse_toml_lookup_path(key).is_ok_and(|key_path| key_path == field_path)
+}
+
+/// The value of override text: a TOML value if it parses as one, a
+/// plain string otherwise.
+fn override_value(text: &str) -> TomlValue {
+    parse_toml_value(text.trim()).unwrap_or_else(|_| TomlValue::String(text.to_string()))
+}
+
+/// Name of the environment variable that overrides `field_name`: the
+/// prefix and the key in upper case, joined by `_`, with every character
+/// that is not a letter or digit turned into `_`.
+/// `("APP", "server.port")` gives `APP_SERVER_PORT`.
+pub fn environment_variable_name(prefix: &str, field_name: &str) -> String {
+    let key: String = field_name
+        .chars()
+        .map(|character| if character.is_ascii_alphanumeric() { character.to_ascii_uppercase() } else { '_' })
+        .collect();
+    let key = key.trim_end_matches('_');
+    if prefix.is_empty() { key.to_string() } else { format!("{}_{}", prefix, key) }
+}
+
+/// Collects the `--set key=value` (or `--set=key=value`) pairs from
+/// command-line arguments, in order. Other arguments are returned
+/// separately for the program to handle.
+///
+/// # Returns
+/// * `Result<(TomlOverrides, Vec<String>), ReadTomlError>` - The overrides and the
+///   remaining arguments, or `InvalidFieldName` for a `--set` without a valid `key=`
+pub fn parse_set_arguments<I>(arguments: I) -> Result<(TomlOverrides, Vec<String>), ReadTomlError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut overrides = Vec::new();
+    let mut other_arguments = Vec::new();
+
+    let mut arguments = arguments.into_iter();
+    while let Some(argument) = arguments.next() {
+        let assignment = if argument == "--set" {
+            arguments.next().ok_or_else(|| ReadTomlError::InvalidFieldName {
+                field: String::new(),
+                message: "--set needs a key=value argument".to_string(),
+            })?
+        } else if let Some(assignment) = argument.strip_prefix("--set=") {
+            assignment.to_string()
+        } else {
+            other_arguments.push(argument);
+            continue;
+        };
+
+        let Some((key, value)) = assignment.split_once('=') else {
+            return Err(ReadTomlError::InvalidFieldName {
+                field: assignment,
+                message: "expected key=value after --set".to_string(),
+            });
+        };
+        let key = key.trim();
+        parse_field_path(key)?;
+        overrides.push((key.to_string(), value.to_string()));
+    }
+
+    Ok((overrides, other_arguments))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arguments(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|text| text.to_string()).collect()
+    }
+
+    #[test]
+    fn test_layer_priority() {
+        let document = TomlDocument::parse("name = \"file\"\nport = 80\nlog = true\n[server]\nhost = \"a\"\n");
+        let layers = TomlLayers::new("test.toml", document)
+            .with_overrides(vec![("name".to_string(), "cli".to_string())])
+            .with_environment(
+                "APP",
+                vec![
+                    ("APP_NAME".to_string(), "env".to_string()),
+                    ("APP_PORT".to_string(), "8080".to_string()),
+                    ("OTHER_LOG".to_string(), "false".to_string()),
+                ],
+            )
+            .with_default("port", TomlValue::Integer(1))
+            .with_default("timeout", TomlValue::Integer(30));
+
+        assert_eq!(
+            layers.report(&["name", "port", "log", "timeout", "server.host", "missing"]),
+            vec![
+                Ok(TomlLayeredValue {
+                    field: "name".to_string(),
+                    value: TomlValue::String("cli".to_string()),
+                    layer: TomlLayer::CommandLine,
+                    source: "--set".to_string(),
+                }),
+                Ok(TomlLayeredValue {
+                    field: "port".to_string(),
+                    value: TomlValue::Integer(8080),
+                    layer: TomlLayer::Environment,
+                    source: "APP_PORT".to_string(),
+                }),
+                Ok(TomlLayeredValue {
+                    field: "log".to_string(),
+                    value: TomlValue::Boolean(true),
+                    layer: TomlLayer::File,
+                    source: "test.toml:3".to_string(),
+                }),
+                Ok(TomlLayeredValue {
+                    field: "timeout".to_string(),
+                    value: TomlValue::Integer(30),
+                    layer: TomlLayer::Default,
+                    source: "default".to_string(),
+                }),
+                Ok(TomlLayeredValue {
+                    field: "server.host".to_string(),
+                    value: TomlValue::String("a".to_string()),
+                    layer: TomlLayer::File,
+                    source: "test.toml:5".to_string(),
+                }),
+                Err(ReadTomlError::NotFound { field: "missing".to_string() }),
+            ]
+        );
+        assert_eq!(
+            layers.resolve("port").unwrap().to_string(),
+            "port = 8080 (environment: APP_PORT)"
+        );
+        assert_eq!(layers.resolve("timeout").unwrap().to_string(), "timeout = 30 (default)");
+        assert_eq!(layers.get_string("name"), Ok("cli".to_string()));
+        assert!(matches!(layers.get_bool("port"), Err(ReadTomlError::TypeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_overrides_hide_bad_file_values() {
+        let document = TomlDocument::parse("port = 80 oops\nport_2 = 1\nport_2 = 2\n");
+        let layers = TomlLayers::new("bad.toml", document);
+        assert!(matches!(layers.get("port"), Err(ReadTomlError::MalformedLine { .. })));
+        assert!(matches!(layers.get("port_2"), Err(ReadTomlError::DuplicateKey { .. })));
+
+        let layers = layers.with_overrides(vec![("port".to_string(), "81".to_string())]);
+        assert_eq!(layers.get_i64("port"), Ok(81));
+    }
+
+    #[test]
+    fn test_layers_match_keys_by_path() {
+        let document = TomlDocument::parse("[server]\nport = 80\n");
+        let layers = TomlLayers::new("test.toml", document)
+            .with_overrides(vec![("server.\"port\"".to_string(), "81".to_string())])
+            .with_environment("APP", vec![("APP_SERVER_HOST".to_string(), "edge".to_string())])
+            .with_default("server . timeout", TomlValue::Integer(30))
+            .with_default("server.\"timeout\"", TomlValue::Integer(60));
+
+        assert_eq!(layers.get_i64("server.port"), Ok(81));
+        assert_eq!(layers.get_i64("server . port"), Ok(81));
+        assert_eq!(layers.get_string("server . host"), Ok("edge".to_string()));
+        assert_eq!(layers.get_i64("server.timeout"), Ok(60));
+        assert_eq!(layers.resolve("server.timeout").map(|resolved| resolved.layer), Ok(TomlLayer::Default));
+    }
+
+    #[test]
+    fn test_override_text_values() {
+        assert_eq!(override_value("edge-01"), TomlValue::String("edge-01".to_string()));
+        assert_eq!(override_value("\"quoted # text\""), TomlValue::String("quoted # text".to_string()));
+        assert_eq!(override_value("2.5"), TomlValue::Float(2.5));
+        assert_eq!(override_value("[1, 2]"), TomlValue::Array(vec![TomlValue::Integer(1), TomlValue::Integer(2)]));
+        assert_eq!(override_value(""), TomlValue::String(String::new()));
+    }
+
+    #[test]
+    fn test_environment_variable_name() {
+        assert_eq!(environment_variable_name("APP", "server.port"), "APP_SERVER_PORT");
+        assert_eq!(environment_variable_name("APP", "products[0].max-size"), "APP_PRODUCTS_0__MAX_SIZE");
+        assert_eq!(environment_variable_name("", "log"), "LOG");
+    }
+
+    #[test]
+    fn test_parse_set_arguments() {
+        let (overrides, rest) =
+            parse_set_arguments(arguments(&["run", "--set", "server.port=9090", "--set=name=a=b", "-v"])).unwrap();
+        assert_eq!(
+            overrides,
+            vec![
+                ("server.port".to_string(), "9090".to_string()),
+                ("name".to_string(), "a=b".to_string()),
+            ]
+        );
+        assert_eq!(rest, arguments(&["run", "-v"]));
+
+        assert!(matches!(parse_set_arguments(arguments(&["--set"])), Err(ReadTomlError::InvalidFieldName { .. })));
+        assert!(matches!(
+            parse_set_arguments(arguments(&["--set", "port"])),
+            Err(ReadTomlError::InvalidFieldName { field, .. }) if field == "port"
+        ));
+        assert!(matches!(
+            parse_set_arguments(arguments(&["--set", "a..b=1"])),
+            Err(ReadTomlError::InvalidFieldName { .. })
+        ));
+    }
+}
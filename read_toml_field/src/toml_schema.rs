@@ -0,0 +1,536 @@
+//! Declarative schemas for TOML configuration files.
+//!
+//! Instead of a dozen `read_*` calls followed by hand-written checks, a
+//! binary lists its fields once in a [`TomlSchema`] and checks a file or a
+//! [`TomlDocument`] against it, getting back either a [`TomlSchemaRecord`]
+//! with every field's value (or default) or the full list of violations.
+
+use std::fmt;
+
+use crate::read_toml_field::{with_article, ReadTomlError, TomlDatetime, TomlValue};
+use crate::toml_document::TomlDocument;
+
+/// Type a schema field must have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TomlFieldType {
+    String,
+    Integer,
+    Float,
+    Boolean,
+    Datetime,
+    /// An array with elements of any type
+    Array,
+    StringArray,
+    IntegerArray,
+}
+
+impl TomlFieldType {
+    /// Name used in error messages, matching [`TomlValue::type_name`].
+    pub fn name(&self) -> &'static str {
+        match self {
+            TomlFieldType::String => "string",
+            TomlFieldType::Integer => "integer",
+            TomlFieldType::Float => "float",
+            TomlFieldType::Boolean => "boolean",
+            TomlFieldType::Datetime => "datetime",
+            TomlFieldType::Array => "array",
+            TomlFieldType::StringArray => "string array",
+            TomlFieldType::IntegerArray => "integer array",
+        }
+    }
+
+    /// Whether `value` has this type. Integers are not floats, as in TOML.
+    pub fn matches(&self, value: &TomlValue) -> bool {
+        match (self, value) {
+            (TomlFieldType::String, TomlValue::String(_))
+            | (TomlFieldType::Integer, TomlValue::Integer(_))
+            | (TomlFieldType::Float, TomlValue::Float(_))
+            | (TomlFieldType::Boolean, TomlValue::Boolean(_))
+            | (TomlFieldType::Datetime, TomlValue::Datetime(_))
+            | (TomlFieldType::Array, TomlValue::Array(_)) => true,
+            (TomlFieldType::StringArray, TomlValue::Array(elements)) => {
+                elements.iter().all(|element| matches!(element, TomlValue::String(_)))
+            }
+            (TomlFieldType::IntegerArray, TomlValue::Array(elements)) => {
+                elements.iter().all(|element| matches!(element, TomlValue::Integer(_)))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// One field of a [`TomlSchema`].
+///
+/// `min` and `max` are inclusive. They bound the value of integers and
+/// floats, the number of characters of strings and the number of elements
+/// of arrays. `allowed_values`, when set, lists every value the field may
+/// take, e.g. `["debug", "info", "warn"]` for a log level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TomlFieldSchema {
+    /// Field name or dotted path, as for the `read_*` functions
+    pub name: String,
+    pub field_type: TomlFieldType,
+    pub required: bool,
+    /// Value used when an optional field is missing
+    pub default: Option<TomlValue>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub allowed_values: Option<Vec<TomlValue>>,
+}
+
+impl TomlFieldSchema {
+    /// A field that must be present.
+    pub fn required(name: &str, field_type: TomlFieldType) -> Self {
+        TomlFieldSchema {
+            name: name.to_string(),
+            field_type,
+            required: true,
+            default: None,
+            min: None,
+            max: None,
+            allowed_values: None,
+        }
+    }
+
+    /// A field that may be missing, in which case `default` (if any) is
+    /// used in the record.
+    pub fn optional(name: &str, field_type: TomlFieldType, default: Option<TomlValue>) -> Self {
+        TomlFieldSchema {
+            required: false,
+            default,
+            ..TomlFieldSchema::required(name, field_type)
+        }
+    }
+
+    /// Sets the inclusive bounds, see [`TomlFieldSchema`].
+    pub fn with_range(self, min: Option<f64>, max: Option<f64>) -> Self {
+        TomlFieldSchema { min, max, ..self }
+    }
+
+    /// Sets the values the field may take.
+    pub fn with_allowed_values(self, allowed_values: Vec<TomlValue>) -> Self {
+        TomlFieldSchema {
+            allowed_values: Some(allowed_values),
+            ..self
+        }
+    }
+
+    /// The number that `min` and `max` are compared with.
+    fn measure(&self, value: &TomlValue) -> Option<f64> {
+        match value {
+            TomlValue::Integer(number) => Some(*number as f64),
+            TomlValue::Float(number) => Some(*number),
+            TomlValue::String(text) => Some(text.chars().count() as f64),
+            TomlValue::Array(elements) => Some(elements.len() as f64),
+            TomlValue::Boolean(_) | TomlValue::Datetime(_) => None,
+        }
+    }
+
+    /// Whether `value` (or its length) is within `min..=max`.
+    fn in_range(&self, value: &TomlValue) -> bool {
+        self.measure(value).is_none_or(|measure| {
+            !self.min.is_some_and(|min| measure < min) && !self.max.is_some_and(|max| measure > max)
+        })
+    }
+
+    /// Whether `value` is one of `allowed_values`, when they are set.
+    fn is_allowed(&self, value: &TomlValue) -> bool {
+        self.allowed_values.as_ref().is_none_or(|allowed_values| allowed_values.contains(value))
+    }
+
+    /// The problem with `default`, if it breaks the field's own type, range
+    /// or allowed values.
+    fn default_violation(&self) -> Option<TomlSchemaViolation> {
+        let default = self.default.as_ref()?;
+        let message = if !self.field_type.matches(default) {
+            format!("default {} is not {}", default, with_article(self.field_type.name()))
+        } else if !self.in_range(default) {
+            format!("default {} is outside the range {} to {}", default, format_bound(self.min), format_bound(self.max))
+        } else if !self.is_allowed(default) {
+            let allowed: Vec<String> =
+                self.allowed_values.iter().flatten().map(|allowed| allowed.to_string()).collect();
+            format!("default {} is not one of {}", default, allowed.join(", "))
+        } else {
+            return None;
+        };
+        Some(TomlSchemaViolation::InvalidSchema { field: self.name.clone(), message })
+    }
+}
+
+/// A range bound for messages: the number, or `any` when unbounded.
+fn format_bound(limit: Option<f64>) -> String {
+    limit.map_or("any".to_string(), |limit| limit.to_string())
+}
+
+/// A problem found while checking a file against a [`TomlSchema`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TomlSchemaViolation {
+    /// The field could not be read: missing while required, malformed,
+    /// defined twice, of the wrong type, or the file could not be opened
+    Unreadable(ReadTomlError),
+    /// The value (or length) is outside `min..=max`
+    OutOfRange { field: String, line: usize, value: String, min: Option<f64>, max: Option<f64> },
+    /// The value is not one of `allowed_values`
+    NotAllowed { field: String, line: usize, value: String, allowed: Vec<String> },
+    /// The schema itself is wrong, e.g. a default of another type
+    InvalidSchema { field: String, message: String },
+}
+
+impl fmt::Display for TomlSchemaViolation {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TomlSchemaViolation::Unreadable(e) => write!(formatter, "{}", e),
+            TomlSchemaViolation::OutOfRange { field, line, value, min, max } => {
+                write!(
+                    formatter,
+                    "Field '{}' on line {} is {}, outside the range {} to {}",
+                    field,
+                    line,
+                    value,
+                    format_bound(*min),
+                    format_bound(*max)
+                )
+            }
+            TomlSchemaViolation::NotAllowed { field, line, value, allowed } => {
+                write!(
+                    formatter,
+                    "Field '{}' on line {} is {}, expected one of {}",
+                    field,
+                    line,
+                    value,
+                    allowed.join(", ")
+                )
+            }
+            TomlSchemaViolation::InvalidSchema { field, message } => {
+                write!(formatter, "Invalid schema for field '{}': {}", field, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TomlSchemaViolation {}
+
+/// The checked values of a [`TomlSchema`], in schema order. Optional
+/// fields that are missing and have no default are left out.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TomlSchemaRecord {
+    values: Vec<(String, TomlValue)>,
+}
+
+impl TomlSchemaRecord {
+    pub fn get(&self, field_name: &str) -> Option<&TomlValue> {
+        self.values
+            .iter()
+            .find(|(name, _)| name == field_name)
+            .map(|(_, value)| value)
+    }
+
+    pub fn contains(&self, field_name: &str) -> bool {
+        self.get(field_name).is_some()
+    }
+
+    /// Field names present in the record, in schema order.
+    pub fn field_names(&self) -> Vec<&str> {
+        self.values.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    pub fn get_str(&self, field_name: &str) -> Option<&str> {
+        self.get(field_name)?.as_str()
+    }
+
+    pub fn get_i64(&self, field_name: &str) -> Option<i64> {
+        self.get(field_name)?.as_i64()
+    }
+
+    pub fn get_f64(&self, field_name: &str) -> Option<f64> {
+        self.get(field_name)?.as_f64()
+    }
+
+    pub fn get_bool(&self, field_name: &str) -> Option<bool> {
+        self.get(field_name)?.as_bool()
+    }
+
+    pub fn get_datetime(&self, field_name: &str) -> Option<&TomlDatetime> {
+        self.get(field_name)?.as_datetime()
+    }
+
+    pub fn get_array(&self, field_name: &str) -> Option<&[TomlValue]> {
+        self.get(field_name)?.as_array()
+    }
+}
+
+/// A list of field rules that a TOML file can be checked against.
+///
+/// # Example
+/// ```
+/// # use read_toml_field::read_toml_field::TomlValue;
+/// # use read_toml_field::toml_schema::{TomlFieldSchema, TomlFieldType, TomlSchema};
+/// let schema = TomlSchema::new(vec![
+///     TomlFieldSchema::required("server.name", TomlFieldType::String),
+///     TomlFieldSchema::optional("server.workers", TomlFieldType::Integer, Some(TomlValue::Integer(4)))
+///         .with_range(Some(1.0), Some(64.0)),
+///     TomlFieldSchema::required("log", TomlFieldType::Boolean),
+/// ]);
+///
+/// let record = schema.check_file("config.toml").unwrap();
+/// assert_eq!(record.get_str("server.name"), Some("config-server"));
+/// assert_eq!(record.get_i64("server.workers"), Some(4));
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TomlSchema {
+    fields: Vec<TomlFieldSchema>,
+}
+
+impl TomlSchema {
+    pub fn new(fields: Vec<TomlFieldSchema>) -> Self {
+        TomlSchema { fields }
+    }
+
+    pub fn fields(&self) -> &[TomlFieldSchema] {
+        &self.fields
+    }
+
+    /// Names of all schema fields, e.g. as the allow-list of
+    /// [`TomlDocument::parse_strict`].
+    pub fn field_names(&self) -> Vec<&str> {
+        self.fields.iter().map(|field| field.name.as_str()).collect()
+    }
+
+    /// Checks the schema itself: every default must have its field's type
+    /// and respect its range and allowed values. Call it once the schema is
+    /// built; [`TomlSchema::check_document`] reports the same problems.
+    pub fn validate(&self) -> Result<(), Vec<TomlSchemaViolation>> {
+        let violations: Vec<TomlSchemaViolation> =
+            self.fields.iter().filter_map(|field| field.default_violation()).collect();
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    /// Loads `path` and checks it, see [`TomlSchema::check_document`].
+    pub fn check_file(&self, path: &str) -> Result<TomlSchemaRecord, Vec<TomlSchemaViolation>> {
+        let document = TomlDocument::load(path).map_err(|e| vec![TomlSchemaViolation::Unreadable(e)])?;
+        self.check_document(&document)
+    }
+
+    /// Checks every schema field against `document` and returns the record
+    /// of values, or all violations found, including those of
+    /// [`TomlSchema::validate`].
+    pub fn check_document(&self, document: &TomlDocument) -> Result<TomlSchemaRecord, Vec<TomlSchemaViolation>> {
+        let mut record = TomlSchemaRecord::default();
+        let mut violations = Vec::new();
+
+        for field in &self.fields {
+            violations.extend(field.default_violation());
+            match check_field(field, document) {
+                Ok(Some(value)) => record.values.push((field.name.clone(), value)),
+                Ok(None) => {}
+                Err(field_violations) => violations.extend(field_violations),
+            }
+        }
+
+        if violations.is_empty() {
+            Ok(record)
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+/// Checks one field; `Ok(None)` for a missing optional field without default.
+/// The default itself is checked by [`TomlFieldSchema::default_violation`].
+fn check_field(field: &TomlFieldSchema, document: &TomlDocument) -> Result<Option<TomlValue>, Vec<TomlSchemaViolation>> {
+    let entry = match document.entry(&field.name) {
+        Ok(entry) => entry,
+        Err(ReadTomlError::NotFound { .. }) if !field.required => return Ok(field.default.clone()),
+        Err(e) => return Err(vec![TomlSchemaViolation::Unreadable(e)]),
+    };
+
+    let value = document
+        .get(&field.name)
+        .map_err(|e| vec![TomlSchemaViolation::Unreadable(e)])?;
+    if !field.field_type.matches(value) {
+        return Err(vec![TomlSchemaViolation::Unreadable(ReadTomlError::TypeMismatch {
+            field: field.name.clone(),
+            expected: field.field_type.name(),
+            found: value.type_name(),
+        })]);
+    }
+
+    let mut violations = Vec::new();
+    let line = entry.line_number();
+
+    if !field.in_range(value) {
+        violations.push(TomlSchemaViolation::OutOfRange {
+            field: field.name.clone(),
+            line,
+            value: value.to_string(),
+            min: field.min,
+            max: field.max,
+        });
+    }
+
+    if let Some(allowed_values) = &field.allowed_values
+        && !field.is_allowed(value)
+    {
+        violations.push(TomlSchemaViolation::NotAllowed {
+            field: field.name.clone(),
+            line,
+            value: value.to_string(),
+            allowed: allowed_values.iter().map(|allowed| allowed.to_string()).collect(),
+        });
+    }
+
+    if violations.is_empty() {
+        Ok(Some(value.clone()))
+    } else {
+        Err(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{remove_file, write};
+
+    fn service_schema() -> TomlSchema {
+        TomlSchema::new(vec![
+            TomlFieldSchema::required("name", TomlFieldType::String).with_range(Some(1.0), Some(20.0)),
+            TomlFieldSchema::required("server.port", TomlFieldType::Integer).with_range(Some(1.0), Some(65535.0)),
+            TomlFieldSchema::optional("server.timeout", TomlFieldType::Float, Some(TomlValue::Float(2.5))),
+            TomlFieldSchema::optional("log_level", TomlFieldType::String, None).with_allowed_values(vec![
+                TomlValue::String("debug".to_string()),
+                TomlValue::String("info".to_string()),
+            ]),
+            TomlFieldSchema::optional("tags", TomlFieldType::StringArray, None),
+        ])
+    }
+
+    #[test]
+    fn test_schema_builds_record_with_defaults() {
+        let document = TomlDocument::parse("name = \"svc\"\nlog_level = \"info\"\n[server]\nport = 8080\n");
+
+        let record = service_schema().check_document(&document).unwrap();
+
+        assert_eq!(record.get_str("name"), Some("svc"));
+        assert_eq!(record.get_i64("server.port"), Some(8080));
+        assert_eq!(record.get_f64("server.timeout"), Some(2.5));
+        assert_eq!(record.get_str("log_level"), Some("info"));
+        assert!(!record.contains("tags"));
+        assert_eq!(record.field_names(), vec!["name", "server.port", "server.timeout", "log_level"]);
+    }
+
+    #[test]
+    fn test_schema_reports_every_violation() {
+        let content = r#"name = ""
+log_level = "verbose"
+tags = ["a", 1]
+
+[server]
+port = 70000
+timeout = 5
+"#;
+        let violations = service_schema().check_document(&TomlDocument::parse(content)).unwrap_err();
+
+        assert_eq!(
+            violations,
+            vec![
+                TomlSchemaViolation::OutOfRange {
+                    field: "name".to_string(),
+                    line: 1,
+                    value: "\"\"".to_string(),
+                    min: Some(1.0),
+                    max: Some(20.0),
+                },
+                TomlSchemaViolation::OutOfRange {
+                    field: "server.port".to_string(),
+                    line: 6,
+                    value: "70000".to_string(),
+                    min: Some(1.0),
+                    max: Some(65535.0),
+                },
+                TomlSchemaViolation::Unreadable(ReadTomlError::TypeMismatch {
+                    field: "server.timeout".to_string(),
+                    expected: "float",
+                    found: "integer",
+                }),
+                TomlSchemaViolation::NotAllowed {
+                    field: "log_level".to_string(),
+                    line: 2,
+                    value: "\"verbose\"".to_string(),
+                    allowed: vec!["\"debug\"".to_string(), "\"info\"".to_string()],
+                },
+                TomlSchemaViolation::Unreadable(ReadTomlError::TypeMismatch {
+                    field: "tags".to_string(),
+                    expected: "string array",
+                    found: "array",
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_missing_and_invalid_fields() {
+        let schema = TomlSchema::new(vec![
+            TomlFieldSchema::required("name", TomlFieldType::String),
+            TomlFieldSchema::optional("retries", TomlFieldType::Integer, Some(TomlValue::String("3".to_string()))),
+            TomlFieldSchema::required("count", TomlFieldType::Integer),
+        ]);
+        let violations = schema.check_document(&TomlDocument::parse("count = 1\ncount = 2\n")).unwrap_err();
+
+        assert_eq!(violations.len(), 3);
+        assert_eq!(violations[0], TomlSchemaViolation::Unreadable(ReadTomlError::NotFound { field: "name".to_string() }));
+        assert!(matches!(violations[1], TomlSchemaViolation::InvalidSchema { .. }));
+        assert!(matches!(violations[2], TomlSchemaViolation::Unreadable(ReadTomlError::DuplicateKey { .. })));
+    }
+
+    #[test]
+    fn test_schema_rejects_defaults_that_break_its_rules() {
+        let schema = TomlSchema::new(vec![
+            TomlFieldSchema::optional("workers", TomlFieldType::Integer, Some(TomlValue::Integer(0)))
+                .with_range(Some(1.0), None),
+            TomlFieldSchema::optional("log_level", TomlFieldType::String, Some(TomlValue::String("trace".to_string())))
+                .with_allowed_values(vec![TomlValue::String("info".to_string())]),
+            TomlFieldSchema::optional("retries", TomlFieldType::Integer, Some(TomlValue::Integer(3)))
+                .with_range(Some(0.0), Some(5.0)),
+        ]);
+        let expected = vec![
+            TomlSchemaViolation::InvalidSchema {
+                field: "workers".to_string(),
+                message: "default 0 is outside the range 1 to any".to_string(),
+            },
+            TomlSchemaViolation::InvalidSchema {
+                field: "log_level".to_string(),
+                message: "default \"trace\" is not one of \"info\"".to_string(),
+            },
+        ];
+
+        assert_eq!(schema.validate(), Err(expected.clone()));
+        // Whether or not the file sets the field
+        assert_eq!(schema.check_document(&TomlDocument::parse("")), Err(expected.clone()));
+        assert_eq!(
+            schema.check_document(&TomlDocument::parse("workers = 2\nlog_level = \"info\"\n")),
+            Err(expected)
+        );
+        assert_eq!(service_schema().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_schema_check_file() {
+        let test_file = "test_schema_check_file.toml";
+        write(test_file, "name = \"svc\"\n[server]\nport = 80\n").unwrap();
+
+        let record = service_schema().check_file(test_file);
+        let missing = service_schema().check_file("missing_schema_file.toml");
+
+        let _ = remove_file(test_file);
+
+        assert_eq!(record.unwrap().get_i64("server.port"), Some(80));
+        assert!(matches!(
+            missing.unwrap_err()[..],
+            [TomlSchemaViolation::Unreadable(ReadTomlError::OpenFailed { .. })]
+        ));
+    }
+}
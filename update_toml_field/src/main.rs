@@ -1,100 +1,14 @@
-use std::fs::{self, File};
-use std::io::{self, Write};
-use std::path::{Path, PathBuf};  // Added PathBuf import
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
 
-/// Updates a specified field in a TOML file with a new value.
-/// 
-/// # Arguments
-/// 
-/// * `path` - A PathBuf containing the path to the TOML file
-/// * `new_string` - A string slice containing the new value to be set
-/// * `field` - A string slice containing the name of the field to update
-/// 
-/// # Returns
-/// 
-/// * `io::Result<()>` - Ok(()) on success, or an error if the operation fails
-/// 
-/// # Example
-/// 
-/// ```
-/// # use std::fs;
-/// # use std::path::PathBuf;
-/// # fs::write("example.toml", "field = \"old_value\"").unwrap();
-/// let path = PathBuf::from("example.toml");
-/// let result = update_toml_field(&path, "new_value", "field");
-/// # fs::remove_file("example.toml").unwrap();
-/// ```
-pub fn update_toml_field(path: &PathBuf, new_string: &str, field: &str) -> io::Result<()> {
-    // Read the entire file content using PathBuf's as_path() method
-    let content = fs::read_to_string(path.as_path())?;
-    
-    // Create a temporary file with the same name plus .tmp
-    let temp_path = path.with_extension("tmp");
-    let mut temp_file = File::create(&temp_path)?;
-    
-    let mut field_found = false;
-    
-    // Process each line
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if trimmed.starts_with(field) && trimmed.contains('=') {
-            // Write the new line for the matching field
-            writeln!(temp_file, "{} = \"{}\"", field, new_string)?;
-            field_found = true;
-        } else {
-            // Write the original line
-            writeln!(temp_file, "{}", line)?;
-        }
-    }
-    
-    // If field wasn't found, append it
-    if !field_found {
-        writeln!(temp_file, "{} = \"{}\"", field, new_string)?;
-    }
-    
-    // Ensure all data is written
-    temp_file.flush()?;
-    
-    // Replace the original file with the temporary file
-    fs::rename(temp_path, path)?;
-    
-    Ok(())
-}
+mod update_toml_field;
 
-/// A safer wrapper function that includes additional error checking.
-/// 
-/// # Arguments
-/// 
-/// * `path` - A PathBuf containing the path to the TOML file
-/// * `new_string` - A string slice containing the new value to be set
-/// * `field` - A string slice containing the name of the field to update
-/// 
-/// # Returns
-/// 
-/// * `Result<(), String>` - Ok(()) on success, or an error message if the operation fails
-///
-/// Example Use:
-/// ```
-/// use std::path::PathBuf;
-/// let config_path = PathBuf::from("config.toml");
-/// match safe_update_toml_field(&config_path, "alice", "user_name") {
-///     Ok(_) => println!("Successfully updated TOML file"),
-///     Err(e) => eprintln!("Error: {}", e)
-/// }
-/// ```
-pub fn safe_update_toml_field(path: &PathBuf, new_string: &str, field: &str) -> Result<(), String> {
-    // Validate inputs
-    if field.is_empty() {
-        return Err("Field name cannot be empty".to_string());
-    }
-    
-    if !path.exists() {
-        return Err(format!("File not found: {}", path.display()));
-    }
-    
-    update_toml_field(path, new_string, field)
-        .map_err(|e| format!("Failed to update TOML file: {}", e))
-}
+use update_toml_field::{
+    safe_update_toml_field, update_toml_array_field, update_toml_bool_field, update_toml_field_value,
+    update_toml_float_field, update_toml_integer_field, update_toml_multi_line_string_field, TomlFieldValue,
+    TomlTransaction,
+};
 
 fn main() {
     // Create a PathBuf for the config file
@@ -110,30 +24,40 @@ fn main() {
         Ok(_) => println!("Successfully updated TOML file"),
         Err(e) => eprintln!("Error: {}", e)
     }
-}
 
-/// run with: cargo test
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use std::fs;
-    
-    #[test]
-    fn test_update_field() {
-        // Create a test file using PathBuf
-        let test_content = "directory_path = \"old/path\"\nupdated_at_timestamp = 1735690073";
-        let test_path = PathBuf::from("test_config.toml");
-        fs::write(&test_path, test_content).expect("Failed to create test file");
-        
-        // Update the field
-        let result = update_toml_field(&test_path, "new/path", "directory_path");
-        assert!(result.is_ok());
-        
-        // Verify the update
-        let updated_content = fs::read_to_string(&test_path).expect("Failed to read test file");
-        assert!(updated_content.contains("directory_path = \"new/path\""));
-        
-        // Cleanup
-        fs::remove_file(&test_path).expect("Failed to remove test file");
+    // Typed values and a transaction, on a scratch file so that
+    // config.toml is left as it was
+    let demo_path = PathBuf::from("demo.toml");
+    fs::write(&demo_path, "[server]\nname = \"old\"  # shown in logs\nport = 80\n\n[unused]\nx = 1\n")
+        .expect("Failed to create demo file");
+
+    match run_typed_demo(&demo_path) {
+        Ok(content) => print!("Updated demo file:\n{}", content),
+        Err(e) => eprintln!("Error: {}", e)
     }
-}
\ No newline at end of file
+
+    let _ = fs::remove_file(&demo_path);
+    let _ = fs::remove_file("demo.toml.lock");
+}
+
+/// Sets typed values one at a time, then renames and removes keys and
+/// tables in one write, and returns the resulting file.
+fn run_typed_demo(path: &Path) -> io::Result<String> {
+    update_toml_integer_field(path, 8080, "server.port")?;
+    update_toml_float_field(path, 2.5, "server.timeout")?;
+    update_toml_bool_field(path, true, "server.tls")?;
+    update_toml_array_field(path, &[TomlFieldValue::Integer(1), TomlFieldValue::Integer(2)], "server.ids")?;
+    update_toml_multi_line_string_field(path, "Welcome\nto the demo", "motd")?;
+    update_toml_field_value(path, &TomlFieldValue::String("edge-01".to_string()), "server.name")?;
+
+    TomlTransaction::new()
+        .set("server.name", "edge-02")
+        .set_value("server.workers", TomlFieldValue::Integer(4))
+        .rename_key("server.tls", "use_tls")
+        .remove_key("server.timeout")
+        .remove_table("unused")
+        .rename_table("server", "web")
+        .commit(path)?;
+
+    fs::read_to_string(path)
+}
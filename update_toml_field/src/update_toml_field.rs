@@ -0,0 +1,1661 @@
+//! Format-preserving, section-aware updates of one field in a TOML file.
+//!
+//! The field is named by its full table path (`path`, `server.port`,
+//! `network."node id"`) and must match a key exactly: updating `path` never
+//! touches `path_backup`, and `server.port` is only the `port` key of the
+//! `[server]` table (or the dotted key `server.port = ...`).
+//!
+//! Only the bytes of the old value are replaced. Indentation, spacing around
+//! `=`, inline comments, other lines and line endings (LF or CRLF) are kept
+//! as they were, and the new value is written in the old value's style:
+//!
+//! | Old value          | New value written as                                   |
+//! |--------------------|--------------------------------------------------------|
+//! | `"basic"`          | `"basic"`, escaped                                     |
+//! | `'literal'`        | `'literal'`, or `"basic"` if it cannot be literal      |
+//! | `"""multi-line"""` | `"""multi-line"""`, escaped                            |
+//! | `'''multi-line'''` | `'''multi-line'''`, or `"""..."""` if it cannot be     |
+//! | `8080`, `true`, .. | unquoted if the new text is a TOML number, boolean or  |
+//! |                    | datetime, otherwise `"basic"`                          |
+//!
+//! [`update_toml_field`] writes text. To write a typed value (integer, float,
+//! boolean, array, or a string that must stay a string), use
+//! [`update_toml_field_value`] or the setters next to it. The type is then
+//! exactly the one asked for: `updated_at_timestamp = 1735690073` stays an
+//! integer, and a string is always quoted.
+//!
+//! To make several edits at once (sets, and removals and renames of keys
+//! and tables) in a single write that lands whole or not at all, use a
+//! [`TomlTransaction`].
+//!
+//! A missing key is added to its own table: after the last key of an
+//! existing `[table]`, next to dotted keys already defining the table, or
+//! in a new `[table]` appended at the end of the file. Top-level keys are
+//! added before the first table header.
+//!
+//! The file must be valid TOML as far as this editor reads it (headers,
+//! keys, and the extent of every value, including multi-line strings and
+//! arrays); anything else is refused rather than guessed at. Keys inside
+//! inline tables and arrays of tables (`[[...]]`) are not edited.
+//!
+//! Every update reads, edits and rewrites the file under an advisory lock
+//! on `<file name>.lock`, so concurrent updaters take turns instead of
+//! losing each other's writes. The new text goes to a unique temporary file
+//! in the same directory, is synced and then renamed over the original, so
+//! a crash leaves the old file or the new one, and the file keeps its
+//! permissions.
+
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+mod toml_transaction;
+
+pub use toml_transaction::TomlTransaction;
+
+/// Updates a specified field in a TOML file with a new value.
+///
+/// # Arguments
+///
+/// * `path` - The path to the TOML file (a `PathBuf` or any other `Path`)
+/// * `new_string` - A string slice containing the new value to be set
+/// * `field` - A string slice containing the full table path of the field
+///   to update (`name`, `server.port`)
+///
+/// # Returns
+///
+/// * `io::Result<()>` - Ok(()) on success, or an error if the operation fails.
+///   An invalid field path is `InvalidInput`; a file this editor cannot read,
+///   or a field it cannot edit in place, is `InvalidData`. On any error the
+///   file is left as it was.
+///
+/// Waits for other updaters of the same file to finish first; see the
+/// module documentation for how the file is written.
+///
+/// # Example
+///
+/// ```
+/// # use std::fs;
+/// # use std::path::PathBuf;
+/// # fs::write("example.toml", "[server]\nport = 80  # public\n").unwrap();
+/// let path = PathBuf::from("example.toml");
+/// let result = update_toml_field(&path, "8080", "server.port");
+/// // example.toml now holds: [server]\nport = 8080  # public\n
+/// # fs::remove_file("example.toml").unwrap();
+/// # fs::remove_file("example.toml.lock").unwrap();
+/// ```
+pub fn update_toml_field(path: &Path, new_string: &str, field: &str) -> io::Result<()> {
+    rewrite_toml_file(path, |content| update_toml_field_in_content(content, new_string, field))
+}
+
+/// Sets `field` to a typed `value`, written with its TOML type and escaped
+/// as needed. Matching, formatting and errors are as for
+/// [`update_toml_field`]; a [`TomlFieldValue::String`] keeps the old
+/// value's quoting style but is never written unquoted.
+///
+/// # Example
+///
+/// ```
+/// # use std::fs;
+/// # use std::path::PathBuf;
+/// # fs::write("example.toml", "updated_at_timestamp = 1735690073\n").unwrap();
+/// let path = PathBuf::from("example.toml");
+/// update_toml_field_value(&path, &TomlFieldValue::Integer(1735690999), "updated_at_timestamp")?;
+/// let ids = TomlFieldValue::Array(vec![TomlFieldValue::Integer(1), TomlFieldValue::Integer(2)]);
+/// update_toml_field_value(&path, &ids, "ids")?;
+/// // example.toml now holds: updated_at_timestamp = 1735690999\nids = [1, 2]\n
+/// # fs::remove_file("example.toml").unwrap();
+/// # fs::remove_file("example.toml.lock").unwrap();
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn update_toml_field_value(path: &Path, value: &TomlFieldValue, field: &str) -> io::Result<()> {
+    rewrite_toml_file(path, |content| update_toml_field_value_in_content(content, value, field))
+}
+
+/// Sets `field` to an integer, e.g. `updated_at_timestamp = 1735690073`.
+pub fn update_toml_integer_field(path: &Path, value: i64, field: &str) -> io::Result<()> {
+    update_toml_field_value(path, &TomlFieldValue::Integer(value), field)
+}
+
+/// Sets `field` to a float; `nan`, `inf` and `-inf` included.
+pub fn update_toml_float_field(path: &Path, value: f64, field: &str) -> io::Result<()> {
+    update_toml_field_value(path, &TomlFieldValue::Float(value), field)
+}
+
+/// Sets `field` to `true` or `false`.
+pub fn update_toml_bool_field(path: &Path, value: bool, field: &str) -> io::Result<()> {
+    update_toml_field_value(path, &TomlFieldValue::Boolean(value), field)
+}
+
+/// Sets `field` to a one-line array, e.g. `ids = [1, 2, 3]`.
+pub fn update_toml_array_field(path: &Path, elements: &[TomlFieldValue], field: &str) -> io::Result<()> {
+    update_toml_field_value(path, &TomlFieldValue::Array(elements.to_vec()), field)
+}
+
+/// Sets `field` to a `"""multi-line"""` string, or a `'''multi-line'''`
+/// one if the old value was and `text` can be written that way.
+pub fn update_toml_multi_line_string_field(path: &Path, text: &str, field: &str) -> io::Result<()> {
+    update_toml_field_value(path, &TomlFieldValue::MultiLineString(text.to_string()), field)
+}
+
+/// Reads the TOML file at `path`, applies `update` to its text and replaces
+/// the file with the result. The whole read-update-write runs under the
+/// file's update lock (see [`lock_toml_file`]), and the file is replaced
+/// crash-safely (see [`replace_file_contents`]). A symbolic link is
+/// followed, so the file it points to is the one replaced and locked.
+fn rewrite_toml_file(path: &Path, update: impl FnOnce(&str) -> io::Result<String>) -> io::Result<()> {
+    let resolved_path;
+    let path = if fs::symlink_metadata(path)?.file_type().is_symlink() {
+        resolved_path = fs::canonicalize(path)?;
+        resolved_path.as_path()
+    } else {
+        path
+    };
+
+    let _lock = lock_toml_file(path)?;
+    let content = fs::read_to_string(path)?;
+    let updated_content = update(&content)?;
+    replace_file_contents(path, updated_content.as_bytes())
+}
+
+/// A safer wrapper function that includes additional error checking.
+///
+/// # Arguments
+///
+/// * `path` - The path to the TOML file (a `PathBuf` or any other `Path`)
+/// * `new_string` - A string slice containing the new value to be set
+/// * `field` - A string slice containing the name of the field to update
+///
+/// # Returns
+///
+/// * `Result<(), String>` - Ok(()) on success, or an error message if the operation fails
+///
+/// Example Use:
+/// ```
+/// use std::path::PathBuf;
+/// let config_path = PathBuf::from("config.toml");
+/// match safe_update_toml_field(&config_path, "alice", "user_name") {
+///     Ok(_) => println!("Successfully updated TOML file"),
+///     Err(e) => eprintln!("Error: {}", e)
+/// }
+/// ```
+pub fn safe_update_toml_field(path: &Path, new_string: &str, field: &str) -> Result<(), String> {
+    // Validate inputs
+    if field.is_empty() {
+        return Err("Field name cannot be empty".to_string());
+    }
+
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+
+    update_toml_field(path, new_string, field)
+        .map_err(|e| format!("Failed to update TOML file: {}", e))
+}
+
+/// Returns `content` with `field` set to `new_string`, as
+/// [`update_toml_field`] would write it. Nothing is read or written.
+///
+/// # Example
+///
+/// ```
+/// let content = "path = 'old'  # where\npath_backup = 'keep'\n";
+/// let updated = update_toml_field_in_content(content, "new", "path").unwrap();
+/// assert_eq!(updated, "path = 'new'  # where\npath_backup = 'keep'\n");
+/// ```
+pub fn update_toml_field_in_content(content: &str, new_string: &str, field: &str) -> io::Result<String> {
+    set_field_in_content(content, field, |old_raw_value| render_string_like(old_raw_value, new_string))
+}
+
+/// Returns `content` with `field` set to the typed `value`, as
+/// [`update_toml_field_value`] would write it. Nothing is read or written.
+pub fn update_toml_field_value_in_content(content: &str, value: &TomlFieldValue, field: &str) -> io::Result<String> {
+    set_field_in_content(content, field, |old_raw_value| value.render_like(old_raw_value))
+}
+
+/// Sets `field` in `content` to `render(old_raw_value)`, where
+/// `old_raw_value` is the old value as written, or `""` for a new key.
+fn set_field_in_content(content: &str, field: &str, render: impl Fn(&str) -> String) -> io::Result<String> {
+    let target_path = parse_field_path(field)?;
+    let statements = scan_toml_statements(content)?;
+    let plan = plan_field_update(content, &statements, &target_path)?;
+
+    let mut updated_content = String::with_capacity(content.len() + field.len() + 64);
+    match plan {
+        FieldUpdatePlan::ReplaceValue { value_start, value_end, .. } => {
+            updated_content.push_str(&content[..value_start]);
+            updated_content.push_str(&render(&content[value_start..value_end]));
+            updated_content.push_str(&content[value_end..]);
+        }
+        FieldUpdatePlan::InsertLine { offset, relative_key, blank_line_after } => {
+            let line_ending = detect_line_ending(content);
+            updated_content.push_str(&content[..offset]);
+            if offset > 0 && !content[..offset].ends_with('\n') {
+                updated_content.push_str(line_ending);
+            }
+            updated_content.push_str(&format_key_path(relative_key));
+            updated_content.push_str(" = ");
+            updated_content.push_str(&render(""));
+            updated_content.push_str(line_ending);
+            if blank_line_after {
+                updated_content.push_str(line_ending);
+            }
+            updated_content.push_str(&content[offset..]);
+        }
+        FieldUpdatePlan::AppendTable { table_path, relative_key } => {
+            let line_ending = detect_line_ending(content);
+            updated_content.push_str(content);
+            if !content.is_empty() {
+                if !content.ends_with('\n') {
+                    updated_content.push_str(line_ending);
+                }
+                if !content.trim_end_matches(['\r', '\n']).is_empty() {
+                    updated_content.push_str(line_ending);
+                }
+            }
+            updated_content.push('[');
+            updated_content.push_str(&format_key_path(table_path));
+            updated_content.push(']');
+            updated_content.push_str(line_ending);
+            updated_content.push_str(&format_key_path(relative_key));
+            updated_content.push_str(" = ");
+            updated_content.push_str(&render(""));
+            updated_content.push_str(line_ending);
+        }
+    }
+    Ok(updated_content)
+}
+
+// ----------------------------------------------------------------------------
+// Writing the file
+// ----------------------------------------------------------------------------
+
+/// The update lock of one TOML file, released when dropped (or when the
+/// process exits, however it exits).
+struct TomlFileLock {
+    _lock_file: File,
+}
+
+/// Waits for and takes the advisory lock that serializes updaters of the
+/// TOML file at `path`, so that two of them cannot both read the old file
+/// and have the second write drop the first one's change.
+///
+/// The lock is held on a sibling `<file name>.lock` file, not on the TOML
+/// file, which every update replaces with a new one. The lock file is left
+/// in place: deleting it could let two updaters lock two different files.
+/// A leftover lock file holds no lock, so a crashed updater never blocks
+/// the next one. Programs that write the file without this lock are not
+/// held back.
+fn lock_toml_file(path: &Path) -> io::Result<TomlFileLock> {
+    let lock_path = path.with_file_name(file_name_with_suffix(path, ".lock")?);
+    let lock_file = OpenOptions::new().read(true).write(true).create(true).truncate(false).open(lock_path)?;
+    lock_file.lock()?;
+    Ok(TomlFileLock { _lock_file: lock_file })
+}
+
+/// Replaces the file at `path` with `contents` so that a crash at any
+/// point leaves either the old file or the new one, never a mix:
+///
+/// 1. `contents` is written to a new, uniquely named temporary file in the
+///    same directory, given the original file's permissions, and synced;
+/// 2. the temporary file is renamed over `path`, which is atomic;
+/// 3. the directory is synced, so the rename itself survives a crash.
+///
+/// If a step fails before the rename, the original file is untouched and
+/// the temporary file is removed.
+fn replace_file_contents(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let permissions = fs::metadata(path)?.permissions();
+
+    let (temp_path, mut temp_file) = create_temp_file_beside(path)?;
+    let mut temp_file_guard = TempFileGuard { path: Some(temp_path.clone()) };
+    temp_file.set_permissions(permissions)?;
+    temp_file.write_all(contents)?;
+    temp_file.sync_all()?;
+    drop(temp_file);
+
+    fs::rename(&temp_path, path)?;
+    temp_file_guard.path = None;
+    sync_parent_directory(path)
+}
+
+/// Distinguishes the temporary files of one process.
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Creates a new file next to `path` named `.<file name>.<pid>.<n>.tmp`,
+/// which no other updater, and no other file such as `a.json` beside
+/// `a.toml`, can be using.
+fn create_temp_file_beside(path: &Path) -> io::Result<(PathBuf, File)> {
+    loop {
+        let suffix = format!(".{}.{}.tmp", process::id(), TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed));
+        let mut temp_name = OsString::from(".");
+        temp_name.push(file_name_with_suffix(path, &suffix)?);
+        let temp_path = path.with_file_name(temp_name);
+        match OpenOptions::new().write(true).create_new(true).open(&temp_path) {
+            Ok(temp_file) => return Ok((temp_path, temp_file)),
+            // Left behind by a crashed process that had the same id.
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// The file name of `path` with `suffix` appended (`config.toml.lock`).
+fn file_name_with_suffix(path: &Path, suffix: &str) -> io::Result<OsString> {
+    let mut file_name = path
+        .file_name()
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, format!("`{}` does not name a file", path.display()))
+        })?
+        .to_os_string();
+    file_name.push(suffix);
+    Ok(file_name)
+}
+
+/// Removes the temporary file when dropped, unless it was renamed into
+/// place (`path` set to `None`).
+struct TempFileGuard {
+    path: Option<PathBuf>,
+}
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        if let Some(path) = self.path.take() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Syncs the directory holding `path`, making a rename in it durable.
+#[cfg(unix)]
+fn sync_parent_directory(path: &Path) -> io::Result<()> {
+    let directory = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(directory)?.sync_all()
+}
+
+/// Directories cannot be opened for syncing here; the rename is made
+/// durable by the file system on its own schedule.
+#[cfg(not(unix))]
+fn sync_parent_directory(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+// ----------------------------------------------------------------------------
+// Planning the edit
+// ----------------------------------------------------------------------------
+
+/// Where the new value goes.
+#[derive(Debug)]
+enum FieldUpdatePlan<'a> {
+    /// Replace the old value, `content[value_start..value_end]`, of the
+    /// statement at `statement_index`.
+    ReplaceValue { statement_index: usize, value_start: usize, value_end: usize },
+    /// Insert `relative_key = value` as a new line at `offset` (a line start,
+    /// or the end of the file).
+    InsertLine { offset: usize, relative_key: &'a [String], blank_line_after: bool },
+    /// Append a new `[table_path]` holding `relative_key = value`.
+    AppendTable { table_path: &'a [String], relative_key: &'a [String] },
+}
+
+/// Finds the one place `target_path` is defined, or where to add it, and
+/// refuses paths that cannot be set without restructuring the document.
+fn plan_field_update<'a>(
+    content: &str,
+    statements: &[TomlStatement],
+    target_path: &'a [String],
+) -> io::Result<FieldUpdatePlan<'a>> {
+    let table_path = &target_path[..target_path.len() - 1];
+    let field = format_key_path(target_path);
+
+    let mut found_value: Option<(usize, usize)> = None;
+    let mut table_header_index: Option<usize> = None;
+    let mut last_dotted_sibling_index: Option<usize> = None;
+    let mut first_header_index: Option<usize> = None;
+    let mut last_root_key_index: Option<usize> = None;
+
+    for (statement_index, statement) in statements.iter().enumerate() {
+        match statement {
+            TomlStatement::Header { path, is_array_of_tables, line_number, .. } => {
+                first_header_index.get_or_insert(statement_index);
+                if path.starts_with(target_path) {
+                    return Err(invalid_data(format!(
+                        "line {}: `{}` is a table, not a value",
+                        line_number, field
+                    )));
+                }
+                if *is_array_of_tables && target_path.starts_with(path) {
+                    return Err(invalid_data(format!(
+                        "line {}: `{}` is inside an array of tables, which this editor does not modify",
+                        line_number, field
+                    )));
+                }
+                if !*is_array_of_tables && path.as_slice() == table_path {
+                    table_header_index = Some(statement_index);
+                }
+            }
+            TomlStatement::KeyValue { full_path, table_length, in_array_of_tables, line_number, .. } => {
+                if *table_length == 0 {
+                    last_root_key_index = Some(statement_index);
+                }
+                if *in_array_of_tables {
+                    continue;
+                }
+                if full_path.as_slice() == target_path {
+                    if let Some((_, first_line_number)) = found_value {
+                        return Err(invalid_data(format!(
+                            "`{}` is defined more than once (lines {} and {})",
+                            field, first_line_number, line_number
+                        )));
+                    }
+                    found_value = Some((statement_index, *line_number));
+                } else if target_path.starts_with(full_path) {
+                    return Err(invalid_data(format!(
+                        "line {}: `{}` is a value (or inline table), so `{}` cannot be set in place",
+                        line_number,
+                        format_key_path(full_path),
+                        field
+                    )));
+                } else if full_path.starts_with(target_path) {
+                    return Err(invalid_data(format!(
+                        "line {}: `{}` is a table, not a value",
+                        line_number, field
+                    )));
+                } else if *table_length < table_path.len() && full_path.starts_with(table_path) {
+                    // `server.host = ...` above or outside `[server]`: the
+                    // target table is defined by dotted keys; add a sibling.
+                    last_dotted_sibling_index = Some(statement_index);
+                }
+            }
+        }
+    }
+
+    if let Some((statement_index, _)) = found_value {
+        if let TomlStatement::KeyValue { value_start, value_end, .. } = statements[statement_index] {
+            return Ok(FieldUpdatePlan::ReplaceValue { statement_index, value_start, value_end });
+        }
+    }
+
+    if let Some(header_index) = table_header_index {
+        // After the table's last key, or right under its header.
+        let mut insert_after = header_index;
+        for (statement_index, statement) in statements.iter().enumerate().skip(header_index + 1) {
+            match statement {
+                TomlStatement::Header { .. } => break,
+                TomlStatement::KeyValue { .. } => insert_after = statement_index,
+            }
+        }
+        return Ok(FieldUpdatePlan::InsertLine {
+            offset: statements[insert_after].next_line_start(),
+            relative_key: &target_path[table_path.len()..],
+            blank_line_after: false,
+        });
+    }
+
+    if let Some(sibling_index) = last_dotted_sibling_index {
+        if let TomlStatement::KeyValue { table_length, .. } = &statements[sibling_index] {
+            return Ok(FieldUpdatePlan::InsertLine {
+                offset: statements[sibling_index].next_line_start(),
+                relative_key: &target_path[*table_length..],
+                blank_line_after: false,
+            });
+        }
+    }
+
+    if !table_path.is_empty() {
+        return Ok(FieldUpdatePlan::AppendTable { table_path, relative_key: &target_path[table_path.len()..] });
+    }
+
+    // A top-level key: after the last top-level key, else above the first
+    // header (and the comment block introducing it), else at the end.
+    if let Some(root_key_index) = last_root_key_index {
+        return Ok(FieldUpdatePlan::InsertLine {
+            offset: statements[root_key_index].next_line_start(),
+            relative_key: target_path,
+            blank_line_after: false,
+        });
+    }
+    match first_header_index {
+        Some(header_index) => Ok(FieldUpdatePlan::InsertLine {
+            offset: start_of_leading_comment_block(content, statements[header_index].line_start()),
+            relative_key: target_path,
+            blank_line_after: true,
+        }),
+        None => Ok(FieldUpdatePlan::InsertLine {
+            offset: content.len(),
+            relative_key: target_path,
+            blank_line_after: false,
+        }),
+    }
+}
+
+/// Start of the comment lines directly above the line at `line_start`
+/// (no blank line between), or `line_start` itself if there are none.
+fn start_of_leading_comment_block(content: &str, line_start: usize) -> usize {
+    let mut block_start = line_start;
+    while block_start > 0 {
+        let previous_line_start = content[..block_start - 1].rfind('\n').map_or(0, |index| index + 1);
+        let previous_line = content[previous_line_start..block_start - 1].trim();
+        if !previous_line.starts_with('#') {
+            break;
+        }
+        block_start = previous_line_start;
+    }
+    block_start
+}
+
+/// `"\r\n"` if the file already uses CRLF line endings, `"\n"` otherwise.
+fn detect_line_ending(content: &str) -> &'static str {
+    if content.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// ----------------------------------------------------------------------------
+// Rendering values and keys
+// ----------------------------------------------------------------------------
+
+/// A typed value for [`update_toml_field_value`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TomlFieldValue {
+    /// A string on one line; quotes, backslashes and newlines are escaped.
+    String(String),
+    /// A `"""multi-line"""` string; newlines are written as line breaks.
+    MultiLineString(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+    /// A one-line array of any values, nested arrays included.
+    Array(Vec<TomlFieldValue>),
+}
+
+impl TomlFieldValue {
+    /// The value as TOML. Strings follow the quoting style of
+    /// `old_raw_value` (the old value as written, or `""` for a new key)
+    /// where they can.
+    fn render_like(&self, old_raw_value: &str) -> String {
+        match self {
+            TomlFieldValue::String(text) => render_quoted_string_like(old_raw_value, text),
+            TomlFieldValue::MultiLineString(text) => {
+                if old_raw_value.starts_with("'''") && can_be_multi_line_literal(text) {
+                    format!("'''{}{}'''", leading_newline_guard(text), text)
+                } else {
+                    render_multi_line_basic_string(text)
+                }
+            }
+            TomlFieldValue::Integer(number) => number.to_string(),
+            TomlFieldValue::Float(number) => format_toml_float(*number),
+            TomlFieldValue::Boolean(flag) => flag.to_string(),
+            TomlFieldValue::Array(elements) => {
+                let rendered_elements: Vec<String> = elements.iter().map(|element| element.render_like("")).collect();
+                format!("[{}]", rendered_elements.join(", "))
+            }
+        }
+    }
+}
+
+/// A float as TOML: finite values always carry a `.` or an exponent so
+/// they read back as floats, and `nan` / `inf` are spelled the TOML way.
+fn format_toml_float(number: f64) -> String {
+    if number.is_nan() {
+        "nan".to_string()
+    } else if number.is_infinite() {
+        if number > 0.0 { "inf" } else { "-inf" }.to_string()
+    } else {
+        // Debug output is the shortest text that reads back as the same
+        // f64 and always has a `.` or an `e` (`1.0`, `1e20`, `-0.0`).
+        format!("{:?}", number)
+    }
+}
+
+/// `new_string` as a TOML value in the style of `old_raw_value` (the old
+/// value as written, or `""` for a new key), per the table in the module
+/// documentation.
+fn render_string_like(old_raw_value: &str, new_string: &str) -> String {
+    let old_is_bare = !old_raw_value.is_empty() && !old_raw_value.starts_with(['"', '\'', '[', '{']);
+    if old_is_bare && is_bare_toml_value(new_string) {
+        return new_string.to_string();
+    }
+    render_quoted_string_like(old_raw_value, new_string)
+}
+
+/// `text` as a quoted string in the style of `old_raw_value` if that is a
+/// string `text` can be written as, otherwise as a `"basic"` string.
+fn render_quoted_string_like(old_raw_value: &str, text: &str) -> String {
+    if old_raw_value.starts_with("'''") {
+        if can_be_multi_line_literal(text) {
+            return format!("'''{}{}'''", leading_newline_guard(text), text);
+        }
+        return render_multi_line_basic_string(text);
+    }
+    if old_raw_value.starts_with("\"\"\"") {
+        return render_multi_line_basic_string(text);
+    }
+    if old_raw_value.starts_with('\'') && can_be_literal(text) {
+        return format!("'{}'", text);
+    }
+    format!("\"{}\"", escape_basic_string(text))
+}
+
+/// A multi-line basic string holding `text`: newlines stay as they are,
+/// quotes, backslashes and other control characters (a CR included) are
+/// escaped, so the string reads back exactly as `text`.
+fn render_multi_line_basic_string(text: &str) -> String {
+    let mut rendered = String::from("\"\"\"");
+    rendered.push_str(leading_newline_guard(text));
+    for character in text.chars() {
+        match character {
+            '\n' => rendered.push('\n'),
+            other => push_escaped_char(&mut rendered, other),
+        }
+    }
+    rendered.push_str("\"\"\"");
+    rendered
+}
+
+/// A newline right after the opening delimiter of a multi-line string is
+/// dropped by TOML readers; one is added so a leading newline survives.
+fn leading_newline_guard(text: &str) -> &'static str {
+    if text.starts_with('\n') || text.starts_with("\r\n") {
+        "\n"
+    } else {
+        ""
+    }
+}
+
+/// Escapes text for the inside of a `"basic"` TOML string.
+fn escape_basic_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        push_escaped_char(&mut escaped, character);
+    }
+    escaped
+}
+
+fn push_escaped_char(output: &mut String, character: char) {
+    match character {
+        '"' => output.push_str("\\\""),
+        '\\' => output.push_str("\\\\"),
+        '\n' => output.push_str("\\n"),
+        '\r' => output.push_str("\\r"),
+        '\t' => output.push('\t'),
+        '\u{0008}' => output.push_str("\\b"),
+        '\u{000C}' => output.push_str("\\f"),
+        control if control.is_control() => output.push_str(&format!("\\u{:04X}", control as u32)),
+        other => output.push(other),
+    }
+}
+
+/// Whether `text` can be written as a `'literal'` string unchanged.
+fn can_be_literal(text: &str) -> bool {
+    !text.chars().any(|character| character == '\'' || (character.is_control() && character != '\t'))
+}
+
+/// Whether `text` can be written as a `'''multi-line literal'''` string
+/// (a CR only as part of a CRLF line break).
+fn can_be_multi_line_literal(text: &str) -> bool {
+    !text.contains("'''")
+        && !text.ends_with('\'')
+        && !text
+            .replace("\r\n", "\n")
+            .chars()
+            .any(|character| character.is_control() && !matches!(character, '\t' | '\n'))
+}
+
+/// Whether `text` is an unquoted TOML value: a boolean, integer, float or
+/// date/time. Used to keep `port = 8080` unquoted when set to `"9090"`.
+fn is_bare_toml_value(text: &str) -> bool {
+    matches!(text, "true" | "false") || is_toml_integer(text) || is_toml_float(text) || is_toml_datetime(text)
+}
+
+/// Digits (per `is_digit`) with single underscores between them.
+fn is_digit_run(text: &str, is_digit: impl Fn(char) -> bool) -> bool {
+    !text.is_empty()
+        && !text.starts_with('_')
+        && !text.ends_with('_')
+        && !text.contains("__")
+        && text.chars().all(|character| character == '_' || is_digit(character))
+}
+
+fn is_decimal_integer(text: &str) -> bool {
+    let unsigned = text.strip_prefix(['+', '-']).unwrap_or(text);
+    is_digit_run(unsigned, |character| character.is_ascii_digit()) && (unsigned == "0" || !unsigned.starts_with('0'))
+}
+
+fn is_toml_integer(text: &str) -> bool {
+    if let Some(digits) = text.strip_prefix("0x") {
+        return is_digit_run(digits, |character| character.is_ascii_hexdigit());
+    }
+    if let Some(digits) = text.strip_prefix("0o") {
+        return is_digit_run(digits, |character| ('0'..='7').contains(&character));
+    }
+    if let Some(digits) = text.strip_prefix("0b") {
+        return is_digit_run(digits, |character| character == '0' || character == '1');
+    }
+    is_decimal_integer(text)
+}
+
+fn is_toml_float(text: &str) -> bool {
+    let unsigned = text.strip_prefix(['+', '-']).unwrap_or(text);
+    if unsigned == "inf" || unsigned == "nan" {
+        return true;
+    }
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(exponent_index) => (&unsigned[..exponent_index], Some(&unsigned[exponent_index + 1..])),
+        None => (unsigned, None),
+    };
+    let (integer_part, fraction) = match mantissa.split_once('.') {
+        Some((integer_part, fraction)) => (integer_part, Some(fraction)),
+        None => (mantissa, None),
+    };
+    if fraction.is_none() && exponent.is_none() {
+        return false;
+    }
+    is_decimal_integer(integer_part)
+        && !integer_part.starts_with(['+', '-'])
+        && fraction.is_none_or(|fraction| is_digit_run(fraction, |character| character.is_ascii_digit()))
+        && exponent.is_none_or(|exponent| {
+            is_digit_run(exponent.strip_prefix(['+', '-']).unwrap_or(exponent), |character| character.is_ascii_digit())
+        })
+}
+
+/// A local or offset date, time or date-time, by shape (`1979-05-27`,
+/// `07:32:00`, `1979-05-27T07:32:00.5-07:00`, ...).
+fn is_toml_datetime(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    let is_date = bytes.len() >= 10
+        && bytes[..4].iter().all(u8::is_ascii_digit)
+        && bytes[4] == b'-'
+        && bytes[5..7].iter().all(u8::is_ascii_digit)
+        && bytes[7] == b'-'
+        && bytes[8..10].iter().all(u8::is_ascii_digit);
+    let is_time = |time: &str| {
+        let time_bytes = time.as_bytes();
+        time_bytes.len() >= 8
+            && time_bytes[..2].iter().all(u8::is_ascii_digit)
+            && time_bytes[2] == b':'
+            && time_bytes[3..5].iter().all(u8::is_ascii_digit)
+            && time_bytes[5] == b':'
+            && time_bytes[6..8].iter().all(u8::is_ascii_digit)
+            && {
+                let mut rest = &time[8..];
+                if let Some(fraction) = rest.strip_prefix('.') {
+                    let digit_count = fraction.bytes().take_while(u8::is_ascii_digit).count();
+                    if digit_count == 0 {
+                        return false;
+                    }
+                    rest = &fraction[digit_count..];
+                }
+                matches!(rest, "" | "Z" | "z")
+                    || (rest.len() == 6
+                        && rest.starts_with(['+', '-'])
+                        && rest.as_bytes()[1..3].iter().all(u8::is_ascii_digit)
+                        && rest.as_bytes()[3] == b':'
+                        && rest.as_bytes()[4..6].iter().all(u8::is_ascii_digit))
+            }
+    };
+    if is_date {
+        return bytes.len() == 10 || (matches!(bytes[10], b'T' | b't' | b' ') && is_time(&text[11..]));
+    }
+    is_time(text) && !text[8..].starts_with(['Z', 'z', '+', '-'])
+}
+
+/// A key path as it would be written: bare segments where possible,
+/// `"quoted"` otherwise, joined by dots.
+fn format_key_path(path: &[String]) -> String {
+    path.iter()
+        .map(|segment| {
+            if is_bare_key(segment) {
+                segment.clone()
+            } else {
+                format!("\"{}\"", escape_basic_string(segment))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+fn is_bare_key(segment: &str) -> bool {
+    !segment.is_empty() && segment.chars().all(is_bare_key_char)
+}
+
+fn is_bare_key_char(character: char) -> bool {
+    character.is_ascii_alphanumeric() || character == '_' || character == '-'
+}
+
+// ----------------------------------------------------------------------------
+// Reading the document
+// ----------------------------------------------------------------------------
+
+/// A `[table]` header or a `key = value` line of the document, by byte
+/// offsets into its text. Blank and comment lines are not recorded.
+#[derive(Debug)]
+enum TomlStatement {
+    Header {
+        path: Vec<String>,
+        is_array_of_tables: bool,
+        line_number: usize,
+        line_start: usize,
+        /// The table path as written between the brackets, without blanks.
+        key_start: usize,
+        key_end: usize,
+        /// Offset just past the line terminator (or the end of the text).
+        next_line_start: usize,
+    },
+    KeyValue {
+        /// Table path plus key, e.g. `["server", "port"]`.
+        full_path: Vec<String>,
+        /// How many leading segments of `full_path` name the table.
+        table_length: usize,
+        in_array_of_tables: bool,
+        line_number: usize,
+        line_start: usize,
+        /// The (possibly dotted) key as written, without blanks.
+        key_start: usize,
+        key_end: usize,
+        /// The value as written; a multi-line value ends on a later line.
+        value_start: usize,
+        value_end: usize,
+        /// Offset just past the terminator of the line the value ends on.
+        next_line_start: usize,
+    },
+}
+
+impl TomlStatement {
+    fn line_number(&self) -> usize {
+        match self {
+            TomlStatement::Header { line_number, .. } | TomlStatement::KeyValue { line_number, .. } => *line_number,
+        }
+    }
+
+    fn line_start(&self) -> usize {
+        match self {
+            TomlStatement::Header { line_start, .. } | TomlStatement::KeyValue { line_start, .. } => *line_start,
+        }
+    }
+
+    fn next_line_start(&self) -> usize {
+        match self {
+            TomlStatement::Header { next_line_start, .. } | TomlStatement::KeyValue { next_line_start, .. } => {
+                *next_line_start
+            }
+        }
+    }
+}
+
+/// Parses `field` (`name`, `server.port`, `a."b.c"`) into key segments.
+fn parse_field_path(field: &str) -> io::Result<Vec<String>> {
+    let invalid_field = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+    if field.trim().is_empty() {
+        return Err(invalid_field("Field name cannot be empty".to_string()));
+    }
+    let mut scanner = TomlScanner::new(field);
+    let path = scanner
+        .parse_key()
+        .map_err(|message| invalid_field(format!("invalid field path `{}`: {}", field, message)))?;
+    scanner.skip_blanks();
+    if !scanner.at_end() {
+        return Err(invalid_field(format!("invalid field path `{}`: unexpected `{}`", field, scanner.rest())));
+    }
+    Ok(path)
+}
+
+/// Reads every header and key/value statement of `content`, following
+/// multi-line strings and arrays so their contents are never mistaken for
+/// keys or headers.
+fn scan_toml_statements(content: &str) -> io::Result<Vec<TomlStatement>> {
+    let mut scanner = TomlScanner::new(content);
+    let mut statements = Vec::new();
+    let mut table_path: Vec<String> = Vec::new();
+    let mut in_array_of_tables = false;
+
+    while !scanner.at_end() {
+        let line_number = scanner.line_number;
+        let line_start = scanner.position;
+        let parsed_line = scanner
+            .parse_line()
+            .map_err(|message| invalid_data(format!("line {}: {}", scanner.line_number, message)))?;
+        let next_line_start = scanner.position;
+
+        match parsed_line {
+            ParsedLine::Blank => {}
+            ParsedLine::Header { path, is_array_of_tables, key_start, key_end } => {
+                table_path = path.clone();
+                in_array_of_tables = is_array_of_tables;
+                statements.push(TomlStatement::Header {
+                    path,
+                    is_array_of_tables,
+                    line_number,
+                    line_start,
+                    key_start,
+                    key_end,
+                    next_line_start,
+                });
+            }
+            ParsedLine::KeyValue { key, key_start, key_end, value_start, value_end } => {
+                let mut full_path = table_path.clone();
+                full_path.extend(key);
+                statements.push(TomlStatement::KeyValue {
+                    full_path,
+                    table_length: table_path.len(),
+                    in_array_of_tables,
+                    line_number,
+                    line_start,
+                    key_start,
+                    key_end,
+                    value_start,
+                    value_end,
+                    next_line_start,
+                });
+            }
+        }
+    }
+    Ok(statements)
+}
+
+/// What one line (with any continuation lines of its value) holds.
+enum ParsedLine {
+    /// A blank or comment-only line.
+    Blank,
+    Header { path: Vec<String>, is_array_of_tables: bool, key_start: usize, key_end: usize },
+    KeyValue { key: Vec<String>, key_start: usize, key_end: usize, value_start: usize, value_end: usize },
+}
+
+/// A cursor over TOML text that knows the extent of keys and values.
+struct TomlScanner<'a> {
+    text: &'a str,
+    position: usize,
+    line_number: usize,
+}
+
+impl<'a> TomlScanner<'a> {
+    fn new(text: &'a str) -> Self {
+        TomlScanner { text, position: 0, line_number: 1 }
+    }
+
+    fn at_end(&self) -> bool {
+        self.position >= self.text.len()
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    fn advance(&mut self, byte_count: usize) {
+        self.line_number += self.text[self.position..self.position + byte_count].matches('\n').count();
+        self.position += byte_count;
+    }
+
+    fn skip_blanks(&mut self) {
+        let blank_length = self.rest().len() - self.rest().trim_start_matches([' ', '\t']).len();
+        self.advance(blank_length);
+    }
+
+    /// The current position, less any blanks just before it.
+    fn position_before_blanks(&self) -> usize {
+        self.text[..self.position].trim_end_matches([' ', '\t']).len()
+    }
+
+    /// Parses the statement starting at the current line and moves past
+    /// its (last) line terminator.
+    fn parse_line(&mut self) -> Result<ParsedLine, String> {
+        self.skip_blanks();
+        let parsed_line = match self.peek() {
+            None | Some('\n') | Some('\r') | Some('#') => ParsedLine::Blank,
+            Some('[') => {
+                let is_array_of_tables = self.rest().starts_with("[[");
+                self.advance(if is_array_of_tables { 2 } else { 1 });
+                self.skip_blanks();
+                let key_start = self.position;
+                let path = self.parse_key()?;
+                let key_end = self.position_before_blanks();
+                let closing = if is_array_of_tables { "]]" } else { "]" };
+                if !self.rest().starts_with(closing) {
+                    return Err(format!("expected `{}` to close the table header", closing));
+                }
+                self.advance(closing.len());
+                ParsedLine::Header { path, is_array_of_tables, key_start, key_end }
+            }
+            Some(_) => {
+                let key_start = self.position;
+                let key = self.parse_key()?;
+                let key_end = self.position_before_blanks();
+                if self.peek() != Some('=') {
+                    return Err("expected `=` after the key".to_string());
+                }
+                self.advance(1);
+                self.skip_blanks();
+                let value_start = self.position;
+                self.skip_value()?;
+                ParsedLine::KeyValue { key, key_start, key_end, value_start, value_end: self.position }
+            }
+        };
+        self.finish_line()?;
+        Ok(parsed_line)
+    }
+
+    /// Skips blanks and a comment, then the line terminator.
+    fn finish_line(&mut self) -> Result<(), String> {
+        self.skip_blanks();
+        if self.peek() == Some('#') {
+            let comment_length = self.rest().find('\n').unwrap_or(self.rest().len());
+            self.advance(self.rest()[..comment_length].trim_end_matches('\r').len());
+        }
+        if self.rest().starts_with("\r\n") {
+            self.advance(2);
+        } else if self.rest().starts_with('\n') {
+            self.advance(1);
+        } else if !self.at_end() {
+            return Err(format!("unexpected text `{}`", self.rest().lines().next().unwrap_or("").trim_end()));
+        }
+        Ok(())
+    }
+
+    /// Skips blanks, newlines and comments (inside arrays).
+    fn skip_blanks_comments_and_newlines(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_blanks();
+            match self.peek() {
+                Some('#') | Some('\n') | Some('\r') => {
+                    self.finish_line()?;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    /// Parses a dotted key into its (unquoted, unescaped) segments.
+    fn parse_key(&mut self) -> Result<Vec<String>, String> {
+        let mut segments = Vec::new();
+        loop {
+            self.skip_blanks();
+            let segment = match self.peek() {
+                Some('"') => {
+                    let (start, end) = self.skip_single_line_string('"')?;
+                    decode_basic_string(&self.text[start + 1..end - 1])?
+                }
+                Some('\'') => {
+                    let (start, end) = self.skip_single_line_string('\'')?;
+                    self.text[start + 1..end - 1].to_string()
+                }
+                Some(character) if is_bare_key_char(character) => {
+                    let length = self.rest().len() - self.rest().trim_start_matches(is_bare_key_char).len();
+                    let segment = self.rest()[..length].to_string();
+                    self.advance(length);
+                    segment
+                }
+                _ => return Err("expected a key".to_string()),
+            };
+            segments.push(segment);
+            self.skip_blanks();
+            if self.peek() != Some('.') {
+                return Ok(segments);
+            }
+            self.advance(1);
+        }
+    }
+
+    /// Skips one value of any kind, which may span several lines.
+    fn skip_value(&mut self) -> Result<(), String> {
+        let rest = self.rest();
+        if rest.starts_with("\"\"\"") || rest.starts_with("'''") {
+            return self.skip_multi_line_string();
+        }
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => self.skip_single_line_string(quote).map(|_| ()),
+            Some('[') => self.skip_array(),
+            Some('{') => self.skip_inline_table(),
+            _ => self.skip_bare_value(),
+        }
+    }
+
+    /// Skips a one-line `"basic"` or `'literal'` string; returns its range.
+    fn skip_single_line_string(&mut self, quote: char) -> Result<(usize, usize), String> {
+        let start = self.position;
+        let mut characters = self.rest().char_indices().skip(1);
+        while let Some((offset, character)) = characters.next() {
+            match character {
+                '\\' if quote == '"' => {
+                    characters.next();
+                }
+                '\n' => break,
+                closing if closing == quote => {
+                    self.advance(offset + 1);
+                    return Ok((start, self.position));
+                }
+                _ => {}
+            }
+        }
+        Err("string is not closed on its line".to_string())
+    }
+
+    /// Skips a `"""` or `'''` string, up to two quotes past the closing
+    /// delimiter included (they belong to the string).
+    fn skip_multi_line_string(&mut self) -> Result<(), String> {
+        let delimiter = &self.rest()[..3];
+        let quote = delimiter.as_bytes()[0];
+        let body_bytes = &self.rest().as_bytes()[3..];
+        let mut offset = 0;
+        while offset < body_bytes.len() {
+            if quote == b'"' && body_bytes[offset] == b'\\' {
+                offset += 2;
+                continue;
+            }
+            if body_bytes[offset..].starts_with(delimiter.as_bytes()) {
+                let quote_run = body_bytes[offset..].iter().take_while(|&&byte| byte == quote).count();
+                if quote_run > 5 {
+                    return Err("too many quotes closing a multi-line string".to_string());
+                }
+                self.advance(3 + offset + quote_run);
+                return Ok(());
+            }
+            offset += 1;
+        }
+        Err(format!("multi-line string opened with {} is never closed", delimiter))
+    }
+
+    fn skip_array(&mut self) -> Result<(), String> {
+        self.advance(1);
+        loop {
+            self.skip_blanks_comments_and_newlines()?;
+            if self.peek() == Some(']') {
+                self.advance(1);
+                return Ok(());
+            }
+            if self.at_end() {
+                return Err("array is never closed".to_string());
+            }
+            self.skip_value()?;
+            self.skip_blanks_comments_and_newlines()?;
+            match self.peek() {
+                Some(',') => self.advance(1),
+                Some(']') => {
+                    self.advance(1);
+                    return Ok(());
+                }
+                _ => return Err("expected `,` or `]` in array".to_string()),
+            }
+        }
+    }
+
+    fn skip_inline_table(&mut self) -> Result<(), String> {
+        self.advance(1);
+        self.skip_blanks();
+        if self.peek() == Some('}') {
+            self.advance(1);
+            return Ok(());
+        }
+        loop {
+            self.parse_key()?;
+            self.skip_blanks();
+            if self.peek() != Some('=') {
+                return Err("expected `=` in inline table".to_string());
+            }
+            self.advance(1);
+            self.skip_blanks();
+            self.skip_value()?;
+            self.skip_blanks();
+            match self.peek() {
+                Some(',') => {
+                    self.advance(1);
+                    self.skip_blanks();
+                }
+                Some('}') => {
+                    self.advance(1);
+                    return Ok(());
+                }
+                _ => return Err("expected `,` or `}` in inline table".to_string()),
+            }
+        }
+    }
+
+    /// Skips a number, boolean or date/time (`1979-05-27 07:32:00` keeps
+    /// its space). Only its extent matters here, not its validity.
+    fn skip_bare_value(&mut self) -> Result<(), String> {
+        let token_length = |text: &str| text.find([' ', '\t', '\r', '\n', '#', ',', ']', '}']).unwrap_or(text.len());
+        let rest = self.rest();
+        let mut length = token_length(rest);
+        if length == 0 {
+            return Err("expected a value".to_string());
+        }
+        if length == 10 && is_toml_datetime(&rest[..10]) && rest[10..].starts_with(' ') {
+            let time_length = token_length(&rest[11..]);
+            if is_toml_datetime(&format!("{}T{}", &rest[..10], &rest[11..11 + time_length])) {
+                length = 11 + time_length;
+            }
+        }
+        self.advance(length);
+        Ok(())
+    }
+}
+
+/// Decodes the escapes of a one-line basic string's contents.
+fn decode_basic_string(body: &str) -> Result<String, String> {
+    let mut decoded = String::with_capacity(body.len());
+    let mut characters = body.chars();
+    while let Some(character) = characters.next() {
+        if character != '\\' {
+            decoded.push(character);
+            continue;
+        }
+        match characters.next() {
+            Some('b') => decoded.push('\u{0008}'),
+            Some('t') => decoded.push('\t'),
+            Some('n') => decoded.push('\n'),
+            Some('f') => decoded.push('\u{000C}'),
+            Some('r') => decoded.push('\r'),
+            Some('"') => decoded.push('"'),
+            Some('\\') => decoded.push('\\'),
+            Some(escape @ ('u' | 'U')) => {
+                let digit_count = if escape == 'u' { 4 } else { 8 };
+                let hex_digits: String = characters.by_ref().take(digit_count).collect();
+                let unicode_character = u32::from_str_radix(&hex_digits, 16)
+                    .ok()
+                    .filter(|_| hex_digits.len() == digit_count)
+                    .and_then(char::from_u32)
+                    .ok_or_else(|| format!("invalid escape `\\{}{}`", escape, hex_digits))?;
+                decoded.push(unicode_character);
+            }
+            Some(other) => return Err(format!("invalid escape `\\{}`", other)),
+            None => return Err("string ends with a lone `\\`".to_string()),
+        }
+    }
+    Ok(decoded)
+}
+
+/// run with: cargo test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// `content` with `field` set to `new_string`, or the error message.
+    fn updated(content: &str, new_string: &str, field: &str) -> Result<String, String> {
+        update_toml_field_in_content(content, new_string, field).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_update_field() {
+        // Create a test file using PathBuf
+        let test_content = "directory_path = \"old/path\"\nupdated_at_timestamp = 1735690073";
+        let test_path = PathBuf::from("test_config.toml");
+        fs::write(&test_path, test_content).expect("Failed to create test file");
+
+        // Update the field
+        let result = update_toml_field(&test_path, "new/path", "directory_path");
+        assert!(result.is_ok());
+
+        // Verify the update
+        let updated_content = fs::read_to_string(&test_path).expect("Failed to read test file");
+        assert!(updated_content.contains("directory_path = \"new/path\""));
+
+        // Cleanup
+        fs::remove_file(&test_path).expect("Failed to remove test file");
+    }
+
+    #[test]
+    fn test_matches_whole_keys_only() {
+        let content = "path_backup = \"/old\"\npath = \"/srv\"\npath.txt = 1\n";
+        assert_eq!(
+            updated("path_backup = \"/old\"\npath = \"/srv\"\n", "/data", "path").unwrap(),
+            "path_backup = \"/old\"\npath = \"/data\"\n"
+        );
+        // `path` is both a value and a table here: not valid TOML to edit.
+        assert!(updated(content, "/data", "path").unwrap_err().contains("is a table"));
+    }
+
+    #[test]
+    fn test_targets_table_paths() {
+        let content = "\
+name = \"top\"
+
+[server]
+name = \"web\"   # the server
+port = 80
+
+[client]
+name = \"cli\"
+";
+        assert_eq!(
+            updated(content, "api", "server.name").unwrap(),
+            content.replace("\"web\"", "\"api\"")
+        );
+        assert_eq!(updated(content, "root", "name").unwrap(), content.replace("\"top\"", "\"root\""));
+        assert_eq!(
+            updated(content, "x", "client.name").unwrap(),
+            content.replace("\"cli\"", "\"x\"")
+        );
+
+        // Dotted keys, with blanks around the dot, count as the same path.
+        let dotted = "server . port = 80\nserver.host = \"a\"\n";
+        assert_eq!(updated(dotted, "8080", "server.port").unwrap(), "server . port = 8080\nserver.host = \"a\"\n");
+        // Quoted key segments are matched unquoted.
+        assert_eq!(
+            updated("[\"my table\"]\n'key.with.dots' = 1\n", "2", "\"my table\".\"key.with.dots\"").unwrap(),
+            "[\"my table\"]\n'key.with.dots' = 2\n"
+        );
+    }
+
+    #[test]
+    fn test_preserves_formatting_and_quoting_style() {
+        // Indentation, spacing, comments and CRLF line endings are kept.
+        assert_eq!(
+            updated("  # header\r\n  port\t=   80   # http\r\nname='a'\r\n", "8080", "port").unwrap(),
+            "  # header\r\n  port\t=   8080   # http\r\nname='a'\r\n"
+        );
+        for (old_line, new_string, new_line) in [
+            ("v = 'old'", "new", "v = 'new'"),
+            ("v = 'old'", "it's", "v = \"it's\""),
+            ("v = \"old\"", "say \"hi\" \\o/", "v = \"say \\\"hi\\\" \\\\o/\""),
+            ("v = \"old\"", "two\nlines", "v = \"two\\nlines\""),
+            ("v = 80", "8_080", "v = 8_080"),
+            ("v = true", "false", "v = false"),
+            ("v = 1.5", "-2e3", "v = -2e3"),
+            ("v = 1979-05-27", "1979-05-27 07:32:00Z", "v = 1979-05-27 07:32:00Z"),
+            ("v = 80", "eighty", "v = \"eighty\""),
+            ("v = [1, 2]", "3", "v = \"3\""),
+            ("v = '''old'''", "a\nb", "v = '''a\nb'''"),
+            ("v = '''old'''", "it'''s", "v = \"\"\"it'''s\"\"\""),
+            ("v = \"\"\"old\"\"\"", "a\n\"b\"", "v = \"\"\"a\n\\\"b\\\"\"\"\""),
+        ] {
+            assert_eq!(updated(&format!("{}\n", old_line), new_string, "v").unwrap(), format!("{}\n", new_line));
+        }
+    }
+
+    #[test]
+    fn test_skips_multi_line_values() {
+        // Lines inside multi-line strings and arrays are not keys.
+        let content = "\
+notes = \"\"\"
+port = 1
+[fake]
+\"\"\"
+ports = [
+  80,   # http
+  443,
+]
+port = 2
+";
+        let result = updated(content, "3", "port").unwrap();
+        assert!(result.contains("port = 1\n[fake]"));
+        assert!(result.ends_with("]\nport = 3\n"));
+
+        // A multi-line value is replaced as a whole.
+        assert_eq!(
+            updated(content, "none", "ports").unwrap(),
+            content.replace("[\n  80,   # http\n  443,\n]", "\"none\"")
+        );
+    }
+
+    #[test]
+    fn test_adds_missing_keys_to_their_table() {
+        let content = "\
+# settings
+name = \"app\"
+
+# The server
+[server]
+host = \"a\"   # keep
+
+[client]
+retries = 3
+";
+        // After the last key of an existing table, before the next one.
+        assert_eq!(
+            updated(content, "80", "server.port").unwrap(),
+            content.replace("host = \"a\"   # keep\n", "host = \"a\"   # keep\nport = \"80\"\n")
+        );
+        // A top-level key after the other top-level keys.
+        assert_eq!(
+            updated(content, "1.0", "version").unwrap(),
+            content.replace("name = \"app\"\n", "name = \"app\"\nversion = \"1.0\"\n")
+        );
+        // A new table at the end of the file.
+        assert_eq!(
+            updated(content, "x", "db.url").unwrap(),
+            format!("{}\n[db]\nurl = \"x\"\n", content)
+        );
+        assert_eq!(updated("a = 1", "x", "db.url").unwrap(), "a = 1\n\n[db]\nurl = \"x\"\n");
+        assert_eq!(updated("", "x", "db.url").unwrap(), "[db]\nurl = \"x\"\n");
+
+        // Next to dotted keys that already define the table.
+        assert_eq!(
+            updated("server.host = \"a\"\n\n[client]\n", "80", "server.port").unwrap(),
+            "server.host = \"a\"\nserver.port = \"80\"\n\n[client]\n"
+        );
+        // A top-level key in a file with only tables goes above the first
+        // header and the comments introducing it.
+        assert_eq!(
+            updated("# The server\n[server]\nhost = \"a\"\n", "app", "name").unwrap(),
+            "name = \"app\"\n\n# The server\n[server]\nhost = \"a\"\n"
+        );
+        // CRLF files get CRLF lines; a last line without a newline gets one.
+        assert_eq!(
+            updated("[server]\r\nhost = \"a\"", "80", "server.port").unwrap(),
+            "[server]\r\nhost = \"a\"\r\nport = \"80\"\r\n"
+        );
+    }
+
+    #[test]
+    fn test_refuses_what_it_cannot_edit_in_place() {
+        assert!(updated("a = 1\na = 2\n", "3", "a").unwrap_err().contains("more than once"));
+        assert!(updated("[server]\nport = 1\n", "x", "server").unwrap_err().contains("is a table"));
+        assert!(updated("server = { port = 1 }\n", "2", "server.port").unwrap_err().contains("inline table"));
+        assert!(updated("[[hosts]]\nname = \"a\"\n", "b", "hosts.name").unwrap_err().contains("array of tables"));
+        assert!(updated("a = \"open\nb = 1\n", "2", "b").unwrap_err().contains("line 1"));
+        assert!(updated("a = 1 2\n", "2", "a").unwrap_err().contains("unexpected text"));
+
+        let invalid_input = update_toml_field_in_content("a = 1\n", "2", "a..b").unwrap_err();
+        assert_eq!(invalid_input.kind(), io::ErrorKind::InvalidInput);
+        let invalid_data = update_toml_field_in_content("a = 1\na = 2\n", "2", "a").unwrap_err();
+        assert_eq!(invalid_data.kind(), io::ErrorKind::InvalidData);
+    }
+
+    /// `content` with `field` set to the typed `value`, or the error message.
+    fn updated_value(content: &str, value: TomlFieldValue, field: &str) -> Result<String, String> {
+        update_toml_field_value_in_content(content, &value, field).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_typed_setters_keep_the_type() {
+        let test_path = PathBuf::from("test_typed_config.toml");
+        fs::write(&test_path, "updated_at_timestamp = 1735690073\nname = \"a\"\n").expect("Failed to create test file");
+
+        update_toml_integer_field(&test_path, 1735690999, "updated_at_timestamp").unwrap();
+        update_toml_float_field(&test_path, 0.5, "ratio").unwrap();
+        update_toml_bool_field(&test_path, true, "enabled").unwrap();
+        update_toml_array_field(&test_path, &[TomlFieldValue::Integer(1), TomlFieldValue::Integer(-2)], "ids").unwrap();
+        update_toml_multi_line_string_field(&test_path, "one\ntwo", "name").unwrap();
+
+        let updated_content = fs::read_to_string(&test_path).expect("Failed to read test file");
+        fs::remove_file(&test_path).expect("Failed to remove test file");
+        fs::remove_file("test_typed_config.toml.lock").expect("Failed to remove lock file");
+        assert_eq!(
+            updated_content,
+            "updated_at_timestamp = 1735690999\nname = \"\"\"one\ntwo\"\"\"\n\
+             ratio = 0.5\nenabled = true\nids = [1, -2]\n"
+        );
+    }
+
+    #[test]
+    fn test_inserts_missing_keys_with_their_type() {
+        use TomlFieldValue::*;
+        // Into an existing table, a new table, and the root table.
+        assert_eq!(
+            updated_value("[server]\nname = \"a\"\n", Integer(80), "server.port").unwrap(),
+            "[server]\nname = \"a\"\nport = 80\n"
+        );
+        assert_eq!(
+            updated_value("name = \"a\"\n", Array(vec![Boolean(true), Float(0.5)]), "limits.flags").unwrap(),
+            "name = \"a\"\n\n[limits]\nflags = [true, 0.5]\n"
+        );
+        assert_eq!(
+            TomlTransaction::new().set_value("port", Integer(80)).apply_to_content("[server]\n").unwrap(),
+            "port = 80\n\n[server]\n"
+        );
+        // Only the string setter writes a new key as a string.
+        assert_eq!(updated("", "80", "port").unwrap(), "port = \"80\"\n");
+    }
+
+    #[test]
+    fn test_renders_typed_values() {
+        use TomlFieldValue::*;
+        assert_eq!(updated_value("n = 1\n", Integer(-42), "n").unwrap(), "n = -42\n");
+        assert_eq!(updated_value("n = 1\n", Boolean(false), "n").unwrap(), "n = false\n");
+        // Floats always read back as floats.
+        assert_eq!(updated_value("x = 1\n", Float(3.0), "x").unwrap(), "x = 3.0\n");
+        assert_eq!(updated_value("x = 1\n", Float(1e300), "x").unwrap(), "x = 1e300\n");
+        assert_eq!(updated_value("x = 1\n", Float(f64::NEG_INFINITY), "x").unwrap(), "x = -inf\n");
+        assert_eq!(updated_value("x = 1\n", Float(f64::NAN), "x").unwrap(), "x = nan\n");
+        // A string stays a string, even over a bare value.
+        assert_eq!(updated_value("port = 80\n", String("8080".into()), "port").unwrap(), "port = \"8080\"\n");
+        assert_eq!(updated_value("p = 'a'\n", String("b".into()), "p").unwrap(), "p = 'b'\n");
+        // Arrays of anything, over any old value, comments kept.
+        let nested = Array(vec![String("a\"b".into()), Array(vec![Float(0.25), Boolean(true)]), Array(vec![])]);
+        assert_eq!(
+            updated_value("list = [\n  1,\n]  # ids\n", nested, "list").unwrap(),
+            "list = [\"a\\\"b\", [0.25, true], []]  # ids\n"
+        );
+        // Multi-line strings: a literal stays literal when it can.
+        assert_eq!(
+            updated_value("t = '''\nold'''\n", MultiLineString("C:\\dir\nx".into()), "t").unwrap(),
+            "t = '''C:\\dir\nx'''\n"
+        );
+        assert_eq!(
+            updated_value("t = '''x'''\n", MultiLineString("it's'''".into()), "t").unwrap(),
+            "t = \"\"\"it's'''\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_escapes_quotes_backslashes_and_newlines() {
+        use TomlFieldValue::*;
+        assert_eq!(
+            updated_value("s = \"\"\n", String("say \"hi\"\\\n\r\ttab\u{1}".into()), "s").unwrap(),
+            "s = \"say \\\"hi\\\"\\\\\\n\\r\ttab\\u0001\"\n"
+        );
+        // A literal string cannot hold a quote or a newline: basic instead.
+        assert_eq!(updated_value("s = 'a'\n", String("it's".into()), "s").unwrap(), "s = \"it's\"\n");
+        assert_eq!(updated_value("s = 'a'\n", String("a\nb".into()), "s").unwrap(), "s = \"a\\nb\"\n");
+        // In a multi-line basic string newlines stay, a lone CR does not,
+        // and no run of quotes can close the string early.
+        assert_eq!(
+            updated_value("s = 1\n", MultiLineString("\nx\\y\r\n\"\"\"".into()), "s").unwrap(),
+            "s = \"\"\"\n\nx\\\\y\\r\n\\\"\\\"\\\"\"\"\"\n"
+        );
+        // The untyped setter escapes the same way.
+        assert_eq!(updated("s = \"\"\n", "a\"\\\nb", "s").unwrap(), "s = \"a\\\"\\\\\\nb\"\n");
+    }
+
+    /// A new, empty directory for one test's files.
+    fn test_directory(name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("update_toml_field_{}_{}", name, process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).expect("Failed to create test directory");
+        directory
+    }
+
+    /// The names of the files in `directory`, sorted.
+    fn file_names(directory: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(directory)
+            .expect("Failed to list test directory")
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_replaces_the_file_through_a_unique_temp_file() {
+        let directory = test_directory("temp_file");
+        let test_path = directory.join("a.toml");
+        fs::write(&test_path, "a = 1\na = 2\n").unwrap();
+        // The old temp file name of `a.toml`, here another file's data.
+        fs::write(directory.join("a.tmp"), "not mine").unwrap();
+
+        // A failed update leaves the file as it was and no temp file.
+        assert!(update_toml_integer_field(&test_path, 3, "a").is_err());
+        assert_eq!(fs::read_to_string(&test_path).unwrap(), "a = 1\na = 2\n");
+        assert_eq!(file_names(&directory), ["a.tmp", "a.toml", "a.toml.lock"]);
+
+        fs::write(&test_path, "a = 1\n").unwrap();
+        update_toml_integer_field(&test_path, 3, "a").unwrap();
+        assert_eq!(fs::read_to_string(&test_path).unwrap(), "a = 3\n");
+        assert_eq!(fs::read_to_string(directory.join("a.tmp")).unwrap(), "not mine");
+        assert_eq!(file_names(&directory), ["a.tmp", "a.toml", "a.toml.lock"]);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_keeps_the_file_permissions_and_symbolic_links() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let directory = test_directory("permissions");
+        let test_path = directory.join("secret.toml");
+        fs::write(&test_path, "token = \"old\"\n").unwrap();
+        fs::set_permissions(&test_path, fs::Permissions::from_mode(0o600)).unwrap();
+
+        update_toml_field(&test_path, "new", "token").unwrap();
+        assert_eq!(fs::metadata(&test_path).unwrap().permissions().mode() & 0o777, 0o600);
+        assert_eq!(fs::read_to_string(&test_path).unwrap(), "token = \"new\"\n");
+
+        // Through a symbolic link, the target is updated and the link kept.
+        let link_path = directory.join("link.toml");
+        std::os::unix::fs::symlink(&test_path, &link_path).unwrap();
+        update_toml_field(&link_path, "newer", "token").unwrap();
+        assert!(fs::symlink_metadata(&link_path).unwrap().file_type().is_symlink());
+        assert_eq!(fs::read_to_string(&test_path).unwrap(), "token = \"newer\"\n");
+        assert_eq!(fs::metadata(&test_path).unwrap().permissions().mode() & 0o777, 0o600);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn test_concurrent_updaters_do_not_lose_writes() {
+        const UPDATERS: i64 = 8;
+        const UPDATES: i64 = 20;
+        let directory = test_directory("concurrent");
+        let test_path = directory.join("counters.toml");
+        fs::write(&test_path, "# counters\n").unwrap();
+
+        // Each updater adds and then bumps its own key; without the lock
+        // one updater's rewrite drops keys another has just added.
+        let updaters: Vec<_> = (0..UPDATERS)
+            .map(|updater| {
+                let test_path = test_path.clone();
+                std::thread::spawn(move || {
+                    for update in 1..=UPDATES {
+                        update_toml_integer_field(&test_path, update, &format!("counter_{}", updater)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for updater in updaters {
+            updater.join().unwrap();
+        }
+
+        let content = fs::read_to_string(&test_path).unwrap();
+        for updater in 0..UPDATERS {
+            assert!(content.contains(&format!("counter_{} = {}\n", updater, UPDATES)), "{}", content);
+        }
+        assert_eq!(file_names(&directory), ["counters.toml", "counters.toml.lock"]);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
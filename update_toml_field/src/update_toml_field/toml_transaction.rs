@@ -0,0 +1,624 @@
+//! Several edits of one TOML file, applied in a single rewrite.
+//!
+//! A [`TomlTransaction`] collects sets, removals and renames of keys and
+//! tables. [`TomlTransaction::commit`] applies them in order to the file's
+//! text, and writes the result once: either every edit lands, or (if any
+//! edit cannot be made, or the write fails) the file is left untouched.
+//!
+//! Edits work like [`update_toml_field`](super::update_toml_field): they
+//! change only the lines they must, and refuse what they cannot do in
+//! place. Each later edit sees the text left by the earlier ones, so a key
+//! can be renamed and then set under its new name.
+
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+use super::{
+    format_key_path, invalid_data, parse_field_path, plan_field_update, rewrite_toml_file, scan_toml_statements,
+    start_of_leading_comment_block, update_toml_field_in_content, update_toml_field_value_in_content, FieldUpdatePlan,
+    TomlFieldValue, TomlStatement,
+};
+
+/// A list of edits to make to one TOML file, all or none.
+///
+/// # Example
+///
+/// ```
+/// # use std::fs;
+/// # use std::path::PathBuf;
+/// # fs::write("example.toml", "[srv]\nport = 80\nlegacy = true\n").unwrap();
+/// TomlTransaction::new()
+///     .rename_table("srv", "server")
+///     .set_value("server.port", TomlFieldValue::Integer(8080))
+///     .remove_key("server.legacy")
+///     .set("server.host", "example.com")
+///     .commit(&PathBuf::from("example.toml"))?;
+/// // example.toml now holds: [server]\nport = 8080\nhost = "example.com"\n
+/// # fs::remove_file("example.toml").unwrap();
+/// # fs::remove_file("example.toml.lock").unwrap();
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TomlTransaction {
+    edits: Vec<TomlEdit>,
+}
+
+/// One edit of a transaction; fields and tables are full table paths.
+#[derive(Debug, Clone, PartialEq)]
+enum TomlEdit {
+    Set { field: String, new_string: String },
+    SetValue { field: String, value: TomlFieldValue },
+    RemoveKey { field: String },
+    RemoveTable { table: String },
+    RenameKey { field: String, new_key: String },
+    RenameTable { table: String, new_table: String },
+}
+
+impl fmt::Display for TomlEdit {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TomlEdit::Set { field, .. } | TomlEdit::SetValue { field, .. } => write!(formatter, "set `{}`", field),
+            TomlEdit::RemoveKey { field } => write!(formatter, "remove key `{}`", field),
+            TomlEdit::RemoveTable { table } => write!(formatter, "remove table `{}`", table),
+            TomlEdit::RenameKey { field, new_key } => write!(formatter, "rename key `{}` to `{}`", field, new_key),
+            TomlEdit::RenameTable { table, new_table } => {
+                write!(formatter, "rename table `{}` to `{}`", table, new_table)
+            }
+        }
+    }
+}
+
+impl TomlTransaction {
+    /// An empty transaction.
+    pub fn new() -> TomlTransaction {
+        TomlTransaction::default()
+    }
+
+    /// Sets `field` to `new_string`, as [`update_toml_field`](super::update_toml_field)
+    /// does (adding the key if it is missing).
+    pub fn set(mut self, field: &str, new_string: &str) -> Self {
+        self.edits.push(TomlEdit::Set { field: field.to_string(), new_string: new_string.to_string() });
+        self
+    }
+
+    /// Sets `field` to a typed `value`, as
+    /// [`update_toml_field_value`](super::update_toml_field_value) does.
+    pub fn set_value(mut self, field: &str, value: TomlFieldValue) -> Self {
+        self.edits.push(TomlEdit::SetValue { field: field.to_string(), value });
+        self
+    }
+
+    /// Removes the line (or lines, for a multi-line value) defining
+    /// `field`. A key that is not defined is an error (`NotFound`).
+    pub fn remove_key(mut self, field: &str) -> Self {
+        self.edits.push(TomlEdit::RemoveKey { field: field.to_string() });
+        self
+    }
+
+    /// Removes the table `table` with all its keys and sub-tables: its
+    /// `[table]` header and the comments just above it, every header below
+    /// it (`[table.sub]`, `[[table.items]]`), and dotted keys or an inline
+    /// table defining it from outside (`table.key = 1`, `table = { .. }`).
+    /// A table that is not defined is an error (`NotFound`).
+    pub fn remove_table(mut self, table: &str) -> Self {
+        self.edits.push(TomlEdit::RemoveTable { table: table.to_string() });
+        self
+    }
+
+    /// Renames the key `field` to `new_key`, a single key in the same table
+    /// written as in a field path (`port`, `"listen port"`), keeping its
+    /// value and comment. The new key must not be defined yet.
+    pub fn rename_key(mut self, field: &str, new_key: &str) -> Self {
+        self.edits.push(TomlEdit::RenameKey { field: field.to_string(), new_key: new_key.to_string() });
+        self
+    }
+
+    /// Renames (or moves) the table `table` to the full path `new_table`,
+    /// rewriting every header and dotted key that names it. The new table
+    /// must not be defined yet, and a table defined by dotted keys under
+    /// another table's header can only move within that table.
+    pub fn rename_table(mut self, table: &str, new_table: &str) -> Self {
+        self.edits.push(TomlEdit::RenameTable { table: table.to_string(), new_table: new_table.to_string() });
+        self
+    }
+
+    /// Returns `content` with every edit applied in order. Nothing is read
+    /// or written. An error names the first edit that could not be made,
+    /// and keeps the kind of the underlying error.
+    pub fn apply_to_content(&self, content: &str) -> io::Result<String> {
+        let mut updated_content = content.to_string();
+        for (edit_index, edit) in self.edits.iter().enumerate() {
+            updated_content = apply_edit(&updated_content, edit).map_err(|error| {
+                io::Error::new(error.kind(), format!("edit {} ({}): {}", edit_index + 1, edit, error))
+            })?;
+        }
+        Ok(updated_content)
+    }
+
+    /// Applies every edit to the TOML file at `path` and writes it once,
+    /// locked and crash-safely like [`update_toml_field`](super::update_toml_field).
+    /// On any error the file is left as it was.
+    pub fn commit(&self, path: &Path) -> io::Result<()> {
+        rewrite_toml_file(path, |content| self.apply_to_content(content))
+    }
+}
+
+fn apply_edit(content: &str, edit: &TomlEdit) -> io::Result<String> {
+    match edit {
+        TomlEdit::Set { field, new_string } => update_toml_field_in_content(content, new_string, field),
+        TomlEdit::SetValue { field, value } => update_toml_field_value_in_content(content, value, field),
+        TomlEdit::RemoveKey { field } => remove_key_in_content(content, field),
+        TomlEdit::RemoveTable { table } => remove_table_in_content(content, table),
+        TomlEdit::RenameKey { field, new_key } => rename_key_in_content(content, field, new_key),
+        TomlEdit::RenameTable { table, new_table } => rename_table_in_content(content, table, new_table),
+    }
+}
+
+fn not_found(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::NotFound, message)
+}
+
+/// The index of the statement defining the value `target_path`, with the
+/// same checks as setting it.
+fn find_key_value(content: &str, statements: &[TomlStatement], target_path: &[String]) -> io::Result<usize> {
+    match plan_field_update(content, statements, target_path)? {
+        FieldUpdatePlan::ReplaceValue { statement_index, .. } => Ok(statement_index),
+        _ => Err(not_found(format!("`{}` is not defined", format_key_path(target_path)))),
+    }
+}
+
+fn remove_key_in_content(content: &str, field: &str) -> io::Result<String> {
+    let target_path = parse_field_path(field)?;
+    let statements = scan_toml_statements(content)?;
+    let statement = &statements[find_key_value(content, &statements, &target_path)?];
+    Ok(without_ranges(content, vec![(statement.line_start(), statement.next_line_start())]))
+}
+
+fn remove_table_in_content(content: &str, table: &str) -> io::Result<String> {
+    let table_path = parse_field_path(table)?;
+    let statements = scan_toml_statements(content)?;
+    let table = format_key_path(&table_path);
+    let mut removed_ranges = Vec::new();
+
+    for (statement_index, statement) in statements.iter().enumerate() {
+        match statement {
+            TomlStatement::Header { path, is_array_of_tables, line_number, .. } => {
+                if path.starts_with(&table_path) {
+                    // The header and the comments introducing it, through
+                    // its keys, up to the comments introducing the next one.
+                    let section_end = statements[statement_index + 1..]
+                        .iter()
+                        .find(|next| matches!(next, TomlStatement::Header { .. }))
+                        .map_or(content.len(), |next_header| {
+                            start_of_leading_comment_block(content, next_header.line_start())
+                        });
+                    removed_ranges
+                        .push((start_of_leading_comment_block(content, statement.line_start()), section_end));
+                } else if *is_array_of_tables && table_path.starts_with(path) {
+                    return Err(invalid_data(format!(
+                        "line {}: `{}` is inside an array of tables, which this editor does not modify",
+                        line_number, table
+                    )));
+                }
+            }
+            TomlStatement::KeyValue {
+                full_path, table_length, in_array_of_tables, line_number, value_start, ..
+            } => {
+                if *in_array_of_tables || *table_length >= table_path.len() {
+                    // Under an array of tables (removed with its header if
+                    // it is part of the table), or under the table's own
+                    // headers, which are removed whole.
+                    continue;
+                }
+                if full_path.as_slice() == table_path.as_slice() && !content[*value_start..].starts_with('{') {
+                    return Err(invalid_data(format!("line {}: `{}` is a value, not a table", line_number, table)));
+                }
+                if full_path.starts_with(&table_path) {
+                    removed_ranges.push((statement.line_start(), statement.next_line_start()));
+                } else if table_path.starts_with(full_path) {
+                    return Err(invalid_data(format!(
+                        "line {}: `{}` is a value (or inline table), so `{}` cannot be removed in place",
+                        line_number,
+                        format_key_path(full_path),
+                        table
+                    )));
+                }
+            }
+        }
+    }
+
+    if removed_ranges.is_empty() {
+        return Err(not_found(format!("table `{}` is not defined", table)));
+    }
+    Ok(without_ranges(content, removed_ranges))
+}
+
+fn rename_key_in_content(content: &str, field: &str, new_key: &str) -> io::Result<String> {
+    let target_path = parse_field_path(field)?;
+    let new_key_path = parse_field_path(new_key)?;
+    if new_key_path.len() != 1 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("new key `{}` must be a single key; use a table rename to move keys between tables", new_key),
+        ));
+    }
+    let statements = scan_toml_statements(content)?;
+    let statement_index = find_key_value(content, &statements, &target_path)?;
+    let TomlStatement::KeyValue { full_path, table_length, key_start, key_end, .. } = &statements[statement_index]
+    else {
+        return Err(invalid_data(format!("`{}` is not a key/value pair", format_key_path(&target_path))));
+    };
+
+    let mut new_path = full_path.clone();
+    match new_path.last_mut() {
+        Some(key) => *key = new_key_path[0].clone(),
+        None => return Err(invalid_data(format!("`{}` has an empty key path", format_key_path(&target_path)))),
+    }
+    if new_path == *full_path {
+        return Ok(content.to_string());
+    }
+    if let FieldUpdatePlan::ReplaceValue { statement_index, .. } =
+        plan_field_update(content, &statements, &new_path)?
+    {
+        return Err(invalid_data(format!(
+            "`{}` is already defined (line {})",
+            format_key_path(&new_path),
+            statements[statement_index].line_number()
+        )));
+    }
+
+    let new_written_key = format_key_path(&new_path[*table_length..]);
+    Ok(with_replacements(content, vec![(*key_start, *key_end, new_written_key)]))
+}
+
+fn rename_table_in_content(content: &str, table: &str, new_table: &str) -> io::Result<String> {
+    let table_path = parse_field_path(table)?;
+    let new_table_path = parse_field_path(new_table)?;
+    let statements = scan_toml_statements(content)?;
+    let (table, new_table) = (format_key_path(&table_path), format_key_path(&new_table_path));
+    if new_table_path == table_path {
+        return Ok(content.to_string());
+    }
+    if new_table_path.starts_with(&table_path) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("cannot move table `{}` into itself (`{}`)", table, new_table),
+        ));
+    }
+
+    // The new path must be free: not a table or value already, and not
+    // below a value or inside an array of tables.
+    for statement in &statements {
+        let (path, is_array_of_tables) = match statement {
+            TomlStatement::Header { path, is_array_of_tables, .. } => (path, *is_array_of_tables),
+            TomlStatement::KeyValue { full_path, .. } => (full_path, false),
+        };
+        let is_value = matches!(statement, TomlStatement::KeyValue { .. });
+        if path.starts_with(&new_table_path) {
+            return Err(invalid_data(format!(
+                "`{}` is already defined (line {})",
+                new_table,
+                statement.line_number()
+            )));
+        }
+        if (is_value || is_array_of_tables) && new_table_path.starts_with(path) {
+            return Err(invalid_data(format!(
+                "line {}: `{}` is a value or an array of tables, so `{}` cannot be created in place",
+                statement.line_number(),
+                format_key_path(path),
+                new_table
+            )));
+        }
+    }
+
+    // Headers that stay as they are; a dotted key moved below one of them
+    // must not define that same table a second time.
+    let kept_headers: Vec<(&Vec<String>, usize)> = statements
+        .iter()
+        .filter_map(|statement| match statement {
+            TomlStatement::Header { path, line_number, .. } if !path.starts_with(&table_path) => {
+                Some((path, *line_number))
+            }
+            _ => None,
+        })
+        .collect();
+    let renamed_path = |old_path: &[String]| {
+        let mut new_path = new_table_path.clone();
+        new_path.extend_from_slice(&old_path[table_path.len()..]);
+        new_path
+    };
+    let mut replacements = Vec::new();
+    for statement in &statements {
+        match statement {
+            TomlStatement::Header { path, is_array_of_tables, line_number, key_start, key_end, .. } => {
+                if path.starts_with(&table_path) {
+                    replacements.push((*key_start, *key_end, format_key_path(&renamed_path(path))));
+                } else if *is_array_of_tables && table_path.starts_with(path) {
+                    return Err(invalid_data(format!(
+                        "line {}: `{}` is inside an array of tables, which this editor does not modify",
+                        line_number, table
+                    )));
+                }
+            }
+            TomlStatement::KeyValue {
+                full_path, table_length, in_array_of_tables, line_number, key_start, key_end, value_start, ..
+            } => {
+                if *in_array_of_tables || *table_length >= table_path.len() {
+                    continue;
+                }
+                if full_path.as_slice() == table_path.as_slice() && !content[*value_start..].starts_with('{') {
+                    return Err(invalid_data(format!("line {}: `{}` is a value, not a table", line_number, table)));
+                }
+                if full_path.starts_with(&table_path) {
+                    // A dotted key (or inline table) written under the
+                    // table `full_path[..table_length]`, which must stay.
+                    let new_full_path = renamed_path(full_path);
+                    if !new_full_path.starts_with(&full_path[..*table_length]) {
+                        return Err(invalid_data(format!(
+                            "line {}: `{}` is defined under `[{}]`, so it cannot move to `{}`",
+                            line_number,
+                            table,
+                            format_key_path(&full_path[..*table_length]),
+                            new_table
+                        )));
+                    }
+                    // The dotted key defines each table between its own table
+                    // and its last segment.
+                    for defined_length in *table_length + 1..new_full_path.len() {
+                        let defined_table = &new_full_path[..defined_length];
+                        if let Some((_, header_line_number)) =
+                            kept_headers.iter().find(|(path, _)| path.as_slice() == defined_table)
+                        {
+                            return Err(invalid_data(format!(
+                                "line {}: moving `{}` to `{}` would define `[{}]` (line {}) with dotted keys too",
+                                line_number,
+                                table,
+                                new_table,
+                                format_key_path(defined_table),
+                                header_line_number
+                            )));
+                        }
+                    }
+                    replacements.push((*key_start, *key_end, format_key_path(&new_full_path[*table_length..])));
+                } else if table_path.starts_with(full_path) {
+                    return Err(invalid_data(format!(
+                        "line {}: `{}` is a value (or inline table), so `{}` cannot be renamed in place",
+                        line_number,
+                        format_key_path(full_path),
+                        table
+                    )));
+                }
+            }
+        }
+    }
+
+    if replacements.is_empty() {
+        return Err(not_found(format!("table `{}` is not defined", table)));
+    }
+    Ok(with_replacements(content, replacements))
+}
+
+/// `content` without the byte ranges `ranges` (which may overlap). A
+/// range reaching the end of the text also takes the blank lines before
+/// it, so removing the last table leaves no trailing blank lines.
+fn without_ranges(content: &str, mut ranges: Vec<(usize, usize)>) -> String {
+    ranges.sort_unstable();
+    let mut merged_ranges: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged_ranges.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = (*last_end).max(end),
+            _ => merged_ranges.push((start, end)),
+        }
+    }
+    if let Some((last_start, last_end)) = merged_ranges.last_mut() {
+        if *last_end == content.len() {
+            *last_start = start_of_blank_lines_before(content, *last_start);
+        }
+    }
+
+    let mut kept = String::with_capacity(content.len());
+    let mut position = 0;
+    for (start, end) in merged_ranges {
+        kept.push_str(&content[position..start]);
+        position = end;
+    }
+    kept.push_str(&content[position..]);
+    kept
+}
+
+/// Start of the blank lines directly above the line at `line_start`, or
+/// `line_start` itself if there are none.
+fn start_of_blank_lines_before(content: &str, line_start: usize) -> usize {
+    let mut block_start = line_start;
+    while block_start > 0 {
+        let previous_line_start = content[..block_start - 1].rfind('\n').map_or(0, |index| index + 1);
+        if !content[previous_line_start..block_start].trim().is_empty() {
+            break;
+        }
+        block_start = previous_line_start;
+    }
+    block_start
+}
+
+/// `content` with each `(start, end, text)` range replaced by `text`. The
+/// ranges must not overlap.
+fn with_replacements(content: &str, mut replacements: Vec<(usize, usize, String)>) -> String {
+    replacements.sort_unstable_by_key(|(start, _, _)| *start);
+    let mut replaced = String::with_capacity(content.len());
+    let mut position = 0;
+    for (start, end, text) in replacements {
+        replaced.push_str(&content[position..start]);
+        replaced.push_str(&text);
+        position = end;
+    }
+    replaced.push_str(&content[position..]);
+    replaced
+}
+
+/// run with: cargo test
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// `content` after `transaction`, or the error message.
+    fn applied(transaction: TomlTransaction, content: &str) -> Result<String, String> {
+        transaction.apply_to_content(content).map_err(|e| e.to_string())
+    }
+
+    const CONFIG: &str = "\
+# Service settings
+name = \"app\"
+version = 1
+
+# The server
+[server]
+host = \"localhost\"  # bind address
+port = 80
+notes = \"\"\"
+[not.a.table]
+\"\"\"
+
+[server.tls]
+cert = 'a.pem'
+
+[client]
+retries = 3
+";
+
+    #[test]
+    fn test_commits_all_edits_in_one_write() {
+        let test_path = PathBuf::from("test_transaction_config.toml");
+        fs::write(&test_path, CONFIG).expect("Failed to create test file");
+
+        TomlTransaction::new()
+            .set("name", "service")
+            .set_value("version", TomlFieldValue::Integer(2))
+            .set_value("server.port", TomlFieldValue::Integer(8080))
+            .remove_key("server.notes")
+            .rename_key("client.retries", "attempts")
+            .commit(&test_path)
+            .unwrap();
+        let committed = fs::read_to_string(&test_path).expect("Failed to read test file");
+
+        // A failing edit leaves the file exactly as it was.
+        let error = TomlTransaction::new()
+            .set("name", "changed")
+            .remove_key("server.missing")
+            .commit(&test_path)
+            .unwrap_err();
+        let unchanged = fs::read_to_string(&test_path).expect("Failed to read test file");
+
+        fs::remove_file(&test_path).expect("Failed to remove test file");
+        fs::remove_file("test_transaction_config.toml.lock").expect("Failed to remove lock file");
+        assert_eq!(
+            committed,
+            "# Service settings\nname = \"service\"\nversion = 2\n\n# The server\n[server]\n\
+             host = \"localhost\"  # bind address\nport = 8080\n\n[server.tls]\ncert = 'a.pem'\n\n\
+             [client]\nattempts = 3\n"
+        );
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(error.to_string(), "edit 2 (remove key `server.missing`): `server.missing` is not defined");
+        assert_eq!(unchanged, committed);
+    }
+
+    #[test]
+    fn test_removes_tables_with_their_sub_tables() {
+        assert_eq!(
+            applied(TomlTransaction::new().remove_table("server"), CONFIG).unwrap(),
+            "# Service settings\nname = \"app\"\nversion = 1\n\n[client]\nretries = 3\n"
+        );
+        assert_eq!(
+            applied(TomlTransaction::new().remove_table("client"), CONFIG).unwrap(),
+            CONFIG.replace("\n\n[client]\nretries = 3\n", "\n")
+        );
+        // Dotted keys and inline tables defining the table go too.
+        assert_eq!(
+            applied(
+                TomlTransaction::new().remove_table("db"),
+                "db.url = 'x'\nkeep = 1\ndb.pool = { size = 2 }\n[db.replica]\nurl = 'y'\n"
+            )
+            .unwrap(),
+            "keep = 1\n"
+        );
+        assert_eq!(
+            applied(TomlTransaction::new().remove_table("a.b"), "[a]\nx = 1\nb = { c = 2 }\n").unwrap(),
+            "[a]\nx = 1\n"
+        );
+        assert_eq!(
+            applied(TomlTransaction::new().remove_table("hosts"), "top = 1\n\n[[hosts]]\nn = 1\n[[hosts]]\nn = 2\n")
+                .unwrap(),
+            "top = 1\n"
+        );
+    }
+
+    #[test]
+    fn test_renames_keys_and_tables() {
+        // Headers, sub-table headers and dotted keys all follow; only the
+        // key itself is rewritten.
+        assert_eq!(
+            applied(
+                TomlTransaction::new().rename_table("server", "web.\"front end\""),
+                "[server]\nport = 80\n[ server . tls ]  # certs\ncert = 'a'\n[other]\n"
+            )
+            .unwrap(),
+            "[web.\"front end\"]\nport = 80\n[ web.\"front end\".tls ]  # certs\ncert = 'a'\n[other]\n"
+        );
+        assert_eq!(
+            applied(
+                TomlTransaction::new().rename_table("server", "web"),
+                "server.id = 1\nserver.limits.max = 5\n[server.tls]\ncert = 'a'\n"
+            )
+            .unwrap(),
+            "web.id = 1\nweb.limits.max = 5\n[web.tls]\ncert = 'a'\n"
+        );
+        assert_eq!(
+            applied(TomlTransaction::new().rename_table("a.b", "a.c"), "[a]\nb.x = 1\nz = 0\n").unwrap(),
+            "[a]\nc.x = 1\nz = 0\n"
+        );
+        // A key keeps its value, comment and layout; later edits see the
+        // new name.
+        assert_eq!(
+            applied(
+                TomlTransaction::new()
+                    .rename_key("server.port", "\"listen port\"")
+                    .set("server.\"listen port\"", "9090"),
+                "[server]\n  port   =   80  # public\n"
+            )
+            .unwrap(),
+            "[server]\n  \"listen port\"   =   9090  # public\n"
+        );
+        assert_eq!(
+            applied(TomlTransaction::new().rename_key("server.port", "port"), "server.port = 1\n").unwrap(),
+            "server.port = 1\n"
+        );
+        assert_eq!(
+            applied(TomlTransaction::new().rename_key("server.port", "p"), "server.port = 1\n").unwrap(),
+            "server.p = 1\n"
+        );
+    }
+
+    #[test]
+    fn test_refuses_edits_it_cannot_make() {
+        let refused = |transaction: TomlTransaction, content: &str| applied(transaction, content).unwrap_err();
+        assert!(refused(TomlTransaction::new().remove_key("server"), CONFIG).contains("is a table"));
+        assert!(refused(TomlTransaction::new().remove_table("name"), CONFIG).contains("is a value, not a table"));
+        assert!(refused(TomlTransaction::new().remove_table("missing"), CONFIG).contains("is not defined"));
+        assert!(refused(TomlTransaction::new().rename_key("name", "version"), CONFIG).contains("already defined"));
+        assert!(refused(TomlTransaction::new().rename_key("name", "a.b"), CONFIG).contains("single key"));
+        assert!(refused(TomlTransaction::new().rename_table("server", "client"), CONFIG).contains("already defined"));
+        assert!(refused(TomlTransaction::new().rename_table("server", "name.x"), CONFIG).contains("is a value"));
+        assert!(refused(TomlTransaction::new().rename_table("server", "server.x"), CONFIG).contains("into itself"));
+        assert!(refused(TomlTransaction::new().rename_table("a.b", "c"), "[a]\nb.x = 1\n").contains("under `[a]`"));
+        // Root dotted keys `t.new.y` would define `t`, which has a header.
+        assert!(refused(TomlTransaction::new().rename_table("x", "t.new"), "x.y = 1\n[t]\nu = 1\n")
+            .contains("would define `[t]` (line 2) with dotted keys too"));
+        assert!(refused(TomlTransaction::new().remove_table("h.x"), "[[h]]\nx.y = 1\n").contains("array of tables"));
+
+        let error = TomlTransaction::new().remove_table("a..b").apply_to_content(CONFIG).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(TomlTransaction::new().apply_to_content(CONFIG).unwrap(), CONFIG);
+    }
+}